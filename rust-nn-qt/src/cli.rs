@@ -0,0 +1,174 @@
+use std::error::Error;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use crate::frontend_new::{format_hidden_layers, parse_hidden_layers, NeuralNetworkApp, ProgressUpdate};
+
+pub const USAGE: &str = "Usage: neural_network [--headless [--dataset PATH] [--epochs N] [--hidden SIZES] [--lr RATE]
+                                  [--snapshot-every N] [--stop-at ACC]]
+
+Without arguments the GUI is started. With --headless the network is trained
+with the default configuration and the given overrides, progress is printed
+and the loss and accuracy figure is written to result/lossfigure.png.
+
+  --dataset PATH   CSV file to train on (default csv/pollution_dataset5k.csv)
+  --epochs N       Number of training epochs
+  --hidden SIZES   Hidden layer sizes, comma separated (e.g. 32 or 64,32)
+  --lr RATE        Learning rate
+  --snapshot-every N
+                   Save the weights to result/snapshot_epoch_E.json every N
+                   logged epochs (every 100th epoch is logged)
+  --stop-at ACC    Stop once a logged epoch reaches ACC percent accuracy";
+
+/// Command line arguments, every override is None when not given
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CliArgs {
+    /// Print the usage text and exit
+    pub help: bool,
+    pub headless: bool,
+    pub dataset: Option<String>,
+    pub epochs: Option<usize>,
+    pub hidden_layers: Option<Vec<usize>>,
+    pub learning_rate: Option<f64>,
+    /// Logged epochs between weight snapshots
+    pub snapshot_every: Option<usize>,
+    /// Accuracy (%) at which training stops
+    pub stop_at: Option<f64>,
+}
+
+// Parse the arguments after the program name
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<CliArgs, String> {
+    let mut parsed = CliArgs::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value\n\n{}", arg, USAGE));
+        match arg.as_str() {
+            "--headless" => parsed.headless = true,
+            "--dataset" => parsed.dataset = Some(value()?),
+            "--epochs" => {
+                let text = value()?;
+                let epochs = text.parse::<usize>().ok().filter(|&n| n > 0)
+                    .ok_or_else(|| format!("--epochs: '{}' is not a positive whole number", text))?;
+                parsed.epochs = Some(epochs);
+            }
+            "--hidden" => {
+                let text = value()?;
+                parsed.hidden_layers = Some(parse_hidden_layers(&text).map_err(|e| format!("--hidden: {}", e))?);
+            }
+            "--lr" => {
+                let text = value()?;
+                let rate = text.parse::<f64>().ok().filter(|r| r.is_finite() && *r > 0.0)
+                    .ok_or_else(|| format!("--lr: '{}' is not a positive number", text))?;
+                parsed.learning_rate = Some(rate);
+            }
+            "--snapshot-every" => {
+                let text = value()?;
+                let every = text.parse::<usize>().ok().filter(|&n| n > 0)
+                    .ok_or_else(|| format!("--snapshot-every: '{}' is not a positive whole number", text))?;
+                parsed.snapshot_every = Some(every);
+            }
+            "--stop-at" => {
+                let text = value()?;
+                let accuracy = text.parse::<f64>().ok().filter(|a| (0.0..=100.0).contains(a))
+                    .ok_or_else(|| format!("--stop-at: '{}' is not a percentage between 0 and 100", text))?;
+                parsed.stop_at = Some(accuracy);
+            }
+            "--help" | "-h" => parsed.help = true,
+            _ => return Err(format!("Unknown argument '{}'\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let has_overrides = parsed.dataset.is_some() || parsed.epochs.is_some()
+        || parsed.hidden_layers.is_some() || parsed.learning_rate.is_some()
+        || parsed.snapshot_every.is_some() || parsed.stop_at.is_some();
+    if has_overrides && !parsed.headless {
+        return Err(format!("--dataset, --epochs, --hidden, --lr, --snapshot-every and --stop-at need --headless\n\n{}", USAGE));
+    }
+    Ok(parsed)
+}
+
+// Train once with the shared training loop, printing every logged epoch
+// instead of drawing it, then exit
+pub fn run_headless(args: &CliArgs) -> Result<(), Box<dyn Error>> {
+    let mut app = NeuralNetworkApp::new();
+    let data = app.get_training_data();
+    let config = {
+        let config_ref = app.get_network_config();
+        let mut config = config_ref.lock().unwrap();
+        if let Some(epochs) = args.epochs {
+            config.epochs = epochs;
+        }
+        if let Some(hidden_layers) = &args.hidden_layers {
+            config.hidden_layers = hidden_layers.clone();
+        }
+        if let Some(learning_rate) = args.learning_rate {
+            config.learning_rate = learning_rate;
+        }
+        // The process exits right after training, so the figure is drawn in place
+        config.background_plotting = false;
+        config.clone()
+    };
+    if let Some(dataset) = &args.dataset {
+        data.lock().unwrap().dataset_path = dataset.clone();
+    }
+    if let Some(every) = args.snapshot_every {
+        let mut logged = 0;
+        app.add_epoch_observer(move |_, control| {
+            logged += 1;
+            if logged % every == 0 {
+                control.request_snapshot();
+            }
+        });
+    }
+    if let Some(target) = args.stop_at {
+        app.add_epoch_observer(move |event, control| {
+            if event.accuracy >= target {
+                println!("Accuracy {:.2}% reached the target of {:.2}% at epoch {}", event.accuracy, target, event.epoch + 1);
+                control.request_stop();
+            }
+        });
+    }
+
+    let total_epochs = config.epochs;
+    let task_type = config.task_type;
+    let metric_label = task_type.metric_label();
+    println!(
+        "Headless training on {}: {} epochs, hidden [{}], lr {}",
+        data.lock().unwrap().dataset_path, total_epochs, format_hidden_layers(&config.hidden_layers), config.learning_rate
+    );
+
+    // The printer ends when the training loop drops the app and with it the sender
+    let (tx, rx) = mpsc::channel::<ProgressUpdate>();
+    app.set_progress_sink(tx);
+    let printer = thread::spawn(move || {
+        for update in rx {
+            // Regression runs send R² every epoch, only the logged ones are printed
+            let logged = config.is_logged_epoch(update.epoch, total_epochs);
+            let Some(accuracy) = update.accuracy.filter(|_| logged) else {
+                continue;
+            };
+            let mut line = format!(
+                "Epoch {}/{} | Loss: {:.4} | {}: {}",
+                update.epoch + 1, total_epochs, update.loss, metric_label, task_type.format_metric(accuracy)
+            );
+            if let Some((val_loss, val_accuracy)) = update.validation {
+                line.push_str(&format!(" | Val Loss: {:.4} | Val {}: {}", val_loss, metric_label, task_type.format_metric(val_accuracy)));
+            }
+            println!("{}", line);
+        }
+    });
+
+    let result = crate::train_neural_network(Arc::new(Mutex::new(app)), mpsc::channel().0);
+    let _ = printer.join();
+    result?;
+
+    let data = data.lock().unwrap();
+    println!("Training completed | Final {}: {}", metric_label, task_type.format_metric(data.accuracy));
+    if let Some(metrics) = data.regression_metrics {
+        println!("MSE: {:.6}", metrics.mse);
+    }
+    if let Some(matrix) = data.confusion_matrix {
+        println!("Precision: {:.3} | Recall: {:.3} | F1: {:.3}", matrix.precision(), matrix.recall(), matrix.f1());
+    }
+    Ok(())
+}
@@ -1,612 +1,4168 @@
-use std::sync::{Arc, Mutex};
-use eframe::egui;
-use egui_plot::{Plot, PlotPoints, Line};
-
-/// Data for tracking training progress
-#[derive(Default, Clone)]
-pub struct TrainingData {
-    pub epoch: u32,
-    pub loss: f64,
-    pub accuracy: f64,
-    pub losses: Vec<f64>,
-    pub accuracies: Vec<f64>,
-    pub training_in_progress: bool,
-    pub completed: bool,
-    pub should_stop: bool,
-    pub show_stop_confirm: bool,
-    pub dataset_path: String,
-    pub available_datasets: Vec<String>,
-}
-
-impl TrainingData {
-    pub fn new() -> Self {
-        Self {
-            epoch: 0,
-            loss: 0.0,
-            accuracy: 0.0,
-            training_in_progress: false,
-            completed: false,
-            should_stop: false,
-            show_stop_confirm: false,
-            losses: Vec::new(),
-            accuracies: Vec::new(),
-            dataset_path: "csv/pollution_dataset5k.csv".to_string(), // Default dataset
-            available_datasets: vec![
-                "pollution_dataset5k.csv".to_string(),
-                "pollution_dataset2k.csv".to_string(),
-                "pollution_dataset1k.csv".to_string(),
-            ],
-        }
-    }
-
-    pub fn reset(&mut self) {
-        self.epoch = 0;
-        self.loss = 0.0;
-        self.accuracy = 0.0;
-        self.training_in_progress = true;
-        self.completed = false;
-        self.should_stop = false;
-        self.show_stop_confirm = false;
-        self.losses.clear();
-        self.accuracies.clear();
-    }
-}
-
-/// Configuration for the neural network
-#[derive(Clone)]
-pub struct NetworkConfig {
-    pub epochs: usize,
-    pub hidden_size: usize,
-    pub learning_rate: f64,
-}
-
-impl Default for NetworkConfig {
-    fn default() -> Self {
-        Self {
-            epochs: 1000,
-            hidden_size: 16,
-            learning_rate: 0.01,
-        }
-    }
-}
-
-#[derive(Clone)]
-pub struct NeuralNetworkApp {
-    training_data: Arc<Mutex<TrainingData>>,
-    network_config: Arc<Mutex<NetworkConfig>>,
-    train_callback: Option<Arc<dyn Fn() + Send + Sync + 'static>>,
-}
-
-impl Default for NeuralNetworkApp {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl NeuralNetworkApp {
-    pub fn new() -> Self {
-        let app = Self {
-            training_data: Arc::new(Mutex::new(TrainingData::new())),
-            network_config: Arc::new(Mutex::new(NetworkConfig::default())),
-            train_callback: None,
-        };
-        
-        // Scan for available datasets on startup
-        app.refresh_datasets();
-        
-        app
-    }
-
-    pub fn update_progress(&self, epoch: usize, loss: f64, accuracy: f64) {
-        let mut data = self.training_data.lock().unwrap();
-        data.epoch = epoch as u32;
-        data.loss = loss;
-        
-        // Only update accuracy if it's valid
-        if accuracy > 0.0 {
-            data.accuracy = accuracy;
-        } else {
-            // Estimate accuracy from loss
-            data.accuracy = self.estimate_accuracy(loss);
-        }
-        
-        // Store for plotting - create local copies to avoid borrowing issues
-        let current_accuracy = data.accuracy;
-        data.losses.push(loss);
-        data.accuracies.push(current_accuracy);
-    }
-
-    pub fn training_completed(&self, accuracy: f64) {
-        let mut data = self.training_data.lock().unwrap();
-        data.completed = true;
-        data.training_in_progress = false;
-        data.should_stop = false;  // Reset flag saat pelatihan selesai
-        data.accuracy = accuracy;
-    }
-    
-    #[allow(dead_code)]
-    pub fn stop_training(&self) {
-        let mut data = self.training_data.lock().unwrap();
-        if data.training_in_progress {
-            data.should_stop = true;
-            println!("Training stop requested");
-        }
-    }
-    
-    #[allow(dead_code)]
-    pub fn should_stop_training(&self) -> bool {
-        let data = self.training_data.lock().unwrap();
-        data.should_stop
-    }
-
-    pub fn handle_train_click(&mut self, callback: impl Fn() + Send + Sync + 'static) {
-        self.train_callback = Some(Arc::new(callback));
-    }
-    
-    pub fn get_network_config(&self) -> Arc<Mutex<NetworkConfig>> {
-        self.network_config.clone()
-    }
-    
-    pub fn get_training_data(&self) -> Arc<Mutex<TrainingData>> {
-        self.training_data.clone()
-    }
-    
-    // Calculate estimated accuracy based on loss when actual accuracy is not available
-    fn estimate_accuracy(&self, loss: f64) -> f64 {
-        // Simple rough estimate: higher loss usually means lower accuracy
-        // This is not scientific, just for visualization
-        let max_loss = 0.7; // Adjust based on your data
-        let accuracy = (1.0 - (loss / max_loss).min(1.0)) * 100.0;
-        accuracy.max(0.0)
-    }
-
-    // Scan the csv directory for available dataset files
-    pub fn scan_csv_directory(&self) -> Vec<String> {
-        let mut datasets = Vec::new();
-        
-        if let Ok(entries) = std::fs::read_dir("csv") {
-            for entry in entries.flatten() {
-                if let Ok(file_type) = entry.file_type() {
-                    if file_type.is_file() {
-                        if let Some(file_name) = entry.file_name().to_str() {
-                            if file_name.ends_with(".csv") {
-                                datasets.push(file_name.to_string());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        // If no CSV files found, provide default dataset
-        if datasets.is_empty() {
-            datasets.push("pollution_dataset5k.csv".to_string());
-        }
-        
-        datasets
-    }
-    
-    // Update the available datasets list
-    pub fn refresh_datasets(&self) {
-        let datasets = self.scan_csv_directory();
-        let mut data = self.training_data.lock().unwrap();
-        data.available_datasets = datasets;
-        
-        // If current dataset is not in the list, choose the first one
-        if !data.available_datasets.iter().any(|ds| data.dataset_path == format!("csv/{}", ds)) {
-            if let Some(first_dataset) = data.available_datasets.first() {
-                data.dataset_path = format!("csv/{}", first_dataset);
-            }
-        }
-    }
-}
-
-impl eframe::App for NeuralNetworkApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Set dark mode
-        ctx.set_visuals(egui::Visuals::dark());
-        
-        let network_config = self.network_config.clone();
-        let training_data = self.training_data.clone();
-        
-        let mut train_click = false;
-        let mut stop_click = false;
-        let mut confirm_stop = false;
-        let mut cancel_stop = false;
-        let mut new_dataset_path = None;
-        
-        // Clone all the data we need upfront to avoid borrow issues
-        let data_for_ui = {
-            let data = training_data.lock().unwrap();
-            (
-                data.epoch,
-                data.loss,
-                data.accuracy,
-                data.training_in_progress,
-                data.completed,
-                data.dataset_path.clone(),
-                data.available_datasets.clone(),
-                data.losses.clone(),
-                data.accuracies.clone(),
-                data.show_stop_confirm
-            )
-        };
-        
-        let (
-            epoch,
-            loss,
-            accuracy,
-            training_in_progress,
-            completed,
-            dataset_path,
-            available_datasets,
-            losses,
-            accuracies,
-            show_stop_confirm
-        ) = data_for_ui;
-        
-        // Confirmation dialog
-        if show_stop_confirm {
-            egui::Window::new("Confirm Stop Training")
-                .collapsible(false)
-                .resizable(false)
-                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-                .show(ctx, |ui| {
-                    ui.vertical_centered(|ui| {
-                        ui.add_space(10.0);
-                        ui.heading("Are you sure you want to stop training?");
-                        ui.add_space(5.0);
-                        ui.label("Current progress will be saved but training will end.");
-                        ui.add_space(20.0);
-                        
-                        ui.horizontal(|ui| {
-                            if ui.button(egui::RichText::new("Yes, Stop Training")
-                                .size(16.0)
-                                .color(egui::Color32::WHITE))
-                                .clicked() {
-                                confirm_stop = true;
-                            }
-                            
-                            ui.add_space(20.0);
-                            
-                            if ui.button(egui::RichText::new("Continue Training")
-                                .size(16.0)
-                                .color(egui::Color32::WHITE))
-                                .clicked() {
-                                cancel_stop = true;
-                            }
-                        });
-                        ui.add_space(10.0);
-                    });
-                });
-        }
-        
-        if confirm_stop {
-            let mut data = training_data.lock().unwrap();
-            data.should_stop = true;
-            data.show_stop_confirm = false;
-            println!("Training stop confirmed");
-        }
-        
-        if cancel_stop {
-            let mut data = training_data.lock().unwrap();
-            data.show_stop_confirm = false;
-        }
-        
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // Title & Header
-            ui.vertical_centered(|ui| {
-                ui.add_space(10.0);
-                ui.heading(egui::RichText::new("Neural Network Pollution Analysis").size(24.0));
-                ui.add_space(15.0);
-            });
-            
-            // Progress information if training
-            if training_in_progress || completed {
-                ui.vertical_centered(|ui| {
-                    let progress_text = if training_in_progress {
-                        let config = network_config.lock().unwrap();
-                        format!("Epoch: {}/{} | Loss: {:.4} | Accuracy: {:.2}%", 
-                                epoch, config.epochs, loss, accuracy)
-                    } else {
-                        format!("Training completed | Final Accuracy: {:.2}%", accuracy)
-                    };
-                    
-                    ui.colored_label(egui::Color32::from_rgb(50, 150, 200), progress_text);
-                
-                    // Show dataset information
-                    let mut dataset_name = dataset_path.clone();
-                    if let Some(file_name) = dataset_name.strip_prefix("csv/") {
-                        dataset_name = file_name.to_string();
-                    }
-                    ui.colored_label(egui::Color32::from_rgb(180, 180, 200), 
-                                     format!("Dataset: {}", dataset_name));
-                    
-                    // Progress bar
-                    if training_in_progress {
-                        let config = network_config.lock().unwrap();
-                        let progress = epoch as f32 / config.epochs as f32;
-                        ui.add(egui::ProgressBar::new(progress)
-                            .text(format!("{}/{}", epoch, config.epochs))
-                            .animate(true));
-                    }
-                });
-                
-                ui.add_space(10.0);
-            }
-            
-            // Separator
-            ui.separator();
-            
-            // Network Configuration section with a header
-            ui.vertical(|ui| {
-                ui.vertical_centered(|ui| {
-                    ui.heading(egui::RichText::new("Network Configuration").size(18.0));
-                });
-                ui.add_space(5.0);
-                
-                // Only enable configuration when not training
-                let is_enabled = !training_in_progress;
-                ui.set_enabled(is_enabled);
-                
-                // 3 Configuration controls side-by-side
-                ui.horizontal(|ui| {
-                    // Equal spacing for each control
-                    let available_width = ui.available_width();
-                    let item_width = (available_width - 40.0) / 3.0;
-                    
-                    ui.vertical(|ui| {
-                        ui.set_width(item_width);
-                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Epochs:");
-                        let mut config = network_config.lock().unwrap();
-                        ui.add_sized(
-                            [item_width, 30.0],
-                            egui::DragValue::new(&mut config.epochs)
-                                .speed(10)
-                                .clamp_range(100..=5000)
-                                .prefix("Epochs: ")
-                        );
-                    });
-                    
-                    ui.vertical(|ui| {
-                        ui.set_width(item_width);
-                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Hidden Layer Size:");
-                        let mut config = network_config.lock().unwrap();
-                        ui.add_sized(
-                            [item_width, 30.0],
-                            egui::DragValue::new(&mut config.hidden_size)
-                                .speed(1)
-                                .clamp_range(4..=128)
-                                .prefix("Neurons: ")
-                        );
-                    });
-                    
-                    ui.vertical(|ui| {
-                        ui.set_width(item_width);
-                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Learning Rate:");
-                        let mut config = network_config.lock().unwrap();
-                        ui.add_sized(
-                            [item_width, 30.0],
-                            egui::DragValue::new(&mut config.learning_rate)
-                                .speed(0.001)
-                                .clamp_range(0.0001..=0.1)
-                                .fixed_decimals(4)
-                                .prefix("Rate: ")
-                        );
-                    });
-                });
-                
-                // Dataset Selection
-                ui.add_space(10.0);
-                ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Select Dataset:");
-                
-                // Extract just the filename from the path
-                let mut current_dataset_name = dataset_path.clone();
-                if let Some(file_name) = current_dataset_name.strip_prefix("csv/") {
-                    current_dataset_name = file_name.to_string();
-                }
-                
-                ui.horizontal(|ui| {
-                    egui::ComboBox::from_id_source("dataset_selector")
-                        .selected_text(current_dataset_name.clone())
-                        .width(200.0)
-                        .show_ui(ui, |ui| {
-                            for dataset in &available_datasets {
-                                if ui.selectable_label(current_dataset_name == *dataset, dataset.clone()).clicked() {
-                                    new_dataset_path = Some(format!("csv/{}", dataset));
-                                }
-                            }
-                        });
-                    
-                    ui.add_space(10.0);
-                    
-                    if ui.button("Refresh Datasets").clicked() {
-                        // We'll handle this outside the UI closure
-                        new_dataset_path = Some("REFRESH".to_string());
-                    }
-                });
-            });
-            
-            ui.add_space(10.0);
-            ui.separator();
-            
-            // Training Charts section
-            ui.vertical(|ui| {
-                ui.vertical_centered(|ui| {
-                    ui.heading(egui::RichText::new("Training Charts").size(18.0));
-                });
-                ui.add_space(5.0);
-                
-                // Two charts side by side
-                ui.horizontal(|ui| {
-                    // Loss chart
-                    let plot = Plot::new("loss_plot")
-                        .height(200.0)
-                        .width(ui.available_width() * 0.48)
-                        .view_aspect(2.0)
-                        .allow_zoom(false)
-                        .allow_drag(false)
-                        .show_axes([true, true])
-                        .legend(egui_plot::Legend::default());
-                    
-                    plot.show(ui, |plot_ui| {
-                        if !losses.is_empty() {
-                            let points: PlotPoints = losses.iter()
-                                .enumerate()
-                                .map(|(i, &loss)| [i as f64, loss])
-                                .collect();
-                            
-                            plot_ui.line(Line::new(points).name("Loss").width(2.0).color(egui::Color32::RED));
-                        }
-                        
-                        plot_ui.text(egui_plot::Text::new(
-                            egui_plot::PlotPoint::new(losses.len().max(1) as f64 * 0.5, 0.01), 
-                            "Loss over Epochs"
-                        ).color(egui::Color32::WHITE));
-                    });
-                    
-                    ui.add_space(10.0);
-                    
-                    // Accuracy chart
-                    let plot = Plot::new("accuracy_plot")
-                        .height(200.0)
-                        .width(ui.available_width())
-                        .view_aspect(2.0)
-                        .allow_zoom(false)
-                        .allow_drag(false)
-                        .show_axes([true, true])
-                        .include_y(0.0)
-                        .include_y(100.0)
-                        .legend(egui_plot::Legend::default());
-                    
-                    plot.show(ui, |plot_ui| {
-                        if !accuracies.is_empty() {
-                            let points: PlotPoints = accuracies.iter()
-                                .enumerate()
-                                .map(|(i, &acc)| [i as f64, acc])
-                                .collect();
-                            
-                            plot_ui.line(Line::new(points).name("Accuracy").width(2.0).color(egui::Color32::BLUE));
-                        }
-                        
-                        plot_ui.text(egui_plot::Text::new(
-                            egui_plot::PlotPoint::new(accuracies.len().max(1) as f64 * 0.5, 80.0), 
-                            "Accuracy (%) over Epochs"
-                        ).color(egui::Color32::WHITE));
-                    });
-                });
-            });
-            
-            ui.add_space(15.0);
-            ui.separator();
-            
-            // Control buttons - centered buttons
-            ui.vertical_centered(|ui| {
-                ui.add_space(15.0);
-                
-                ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                    // Create button styles
-                    let button_height = 40.0;
-                    let button_width = 150.0;
-                    
-                    // Train button
-                    let can_train = !training_in_progress;
-                    let btn_text = if training_in_progress {
-                        "Training..."
-                    } else if completed {
-                        "Restart Training"
-                    } else {
-                        "Start Training"
-                    };
-                    
-                    if ui.add_sized(
-                        [button_width, button_height],
-                        egui::Button::new(
-                            egui::RichText::new(btn_text)
-                            .size(16.0)
-                            .color(egui::Color32::WHITE)
-                        )
-                        .fill(if can_train { egui::Color32::from_rgb(0, 255, 8) } else { egui::Color32::DARK_GRAY })
-                    ).clicked() && can_train {
-                        train_click = true;
-                    }
-                    
-                    ui.add_space(20.0);
-                    
-                    // Stop button (hanya aktif saat training berjalan)
-                    if ui.add_sized(
-                        [button_width, button_height],
-                        egui::Button::new(
-                            egui::RichText::new("⏹ Stop Training")
-                            .size(16.0)
-                            .color(egui::Color32::WHITE)
-                        )
-                        .fill(if training_in_progress { egui::Color32::from_rgb(255, 0, 0) } else { egui::Color32::DARK_GRAY })
-                    ).clicked() && training_in_progress {
-                        stop_click = true;
-                    }
-                });
-            });
-            
-            // Spacer to push "Developed by" text to bottom
-            let available = ui.available_height();
-            if available > 30.0 {
-                ui.add_space(available - 30.0);
-            }
-            
-            // "Developed by" text at the bottom
-            ui.vertical_centered(|ui| {
-                ui.label(
-                    egui::RichText::new("Developed by Group 7")
-                    .size(14.0)
-                    .color(egui::Color32::from_rgb(150, 150, 150))
-                );
-            });
-        });  // End of CentralPanel
-        
-        // Handle dataset changes 
-        if let Some(path) = new_dataset_path {
-            if path == "REFRESH" {
-                // Just refresh the datasets list
-                self.refresh_datasets();
-            } else {
-                // Update the selected dataset
-                let mut data = training_data.lock().unwrap();
-                data.dataset_path = path.clone();
-                println!("Dataset changed to: {}", path);
-            }
-        }
-        
-        // Handle training button click outside of the panel to avoid borrowing issues
-        if train_click {
-            let mut data = self.training_data.lock().unwrap();
-            if !data.training_in_progress {
-                // Reset data for new training session
-                if data.completed {
-                    data.reset();
-                }
-                data.training_in_progress = true;
-                data.completed = false;
-                data.epoch = 0;
-                
-                // Trigger training callback outside of the lock
-                drop(data); // Drop the lock here to avoid deadlocks
-                if let Some(callback) = &self.train_callback {
-                    callback();
-                }
-            }
-        }
-        
-        // Handle stop button click
-        if stop_click {
-            let mut data = self.training_data.lock().unwrap();
-            if data.training_in_progress {
-                // Show confirmation dialog instead of immediately stopping
-                data.show_stop_confirm = true;
-            }
-        }
-        
-        // Request continuous repainting
-        ctx.request_repaint();
-    }
-} 
\ No newline at end of file
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use egui_plot::{Plot, PlotPoints, Line};
+use ndarray::Array2;
+use crate::history::{export_history, export_misclassified, export_predictions, load_comparisons, save_comparisons, Replay, RunHistory, COMPARISONS_PATH, HISTORY_CSV_PATH, HISTORY_PATH, MISCLASSIFIED_CSV_PATH, PREDICTIONS_CSV_PATH};
+use crate::model::{load_model, parameter_count, save_model, LoadedModel, ModelWeights, Normalization, Preprocessing, MODEL_PATH};
+use crate::observer::{EpochControl, EpochEvent, EpochObserver};
+use crate::precision::PrecisionComparison;
+use crate::onnx::{export_onnx, ONNX_PATH};
+use crate::synthetic::{write_dataset_csv, Separability, SyntheticSpec};
+use crate::stats::{calibration_curve, feature_label_correlations, roc_curve, AccuracyInterval, Calibration, ClassAccuracy, ConfusionCell, ConfusionMatrix, DecisionRule, EvaluatedSamples, Histogram, RegressionMetrics, RocCurve, TieBreak};
+use crate::{ClassNames, LoadOptions, LoadProgress, LoadSummary, MissingValues, RaggedRows};
+
+/// Bars drawn in the correlation chart before the list view takes over
+const MAX_CHARTED_FEATURES: usize = 20;
+
+/// Range offered by the epochs controls
+const MIN_EPOCHS: usize = 100;
+const MAX_EPOCHS: usize = 1_000_000;
+
+/// Extra epochs added by the "continue training" button
+const CONTINUE_EPOCHS: usize = 500;
+
+/// Separators offered for CSV exports
+const EXPORT_DELIMITERS: [(u8, &str); 3] = [(b',', "Comma"), (b';', "Semicolon"), (b'\t', "Tab")];
+/// Separators a dataset can be read with when detection picks the wrong one
+const DATASET_DELIMITERS: [(u8, &str); 4] = [(b',', "Comma"), (b';', "Semicolon"), (b'\t', "Tab"), (b'|', "Pipe")];
+
+/// Samples and feature columns listed in the confusion matrix drill-down table
+const MAX_DRILLDOWN_ROWS: usize = 200;
+/// Probability bins of the reliability diagram
+const CALIBRATION_BINS: usize = 10;
+const MAX_DRILLDOWN_FEATURES: usize = 12;
+/// Line colors of the compared runs on the loss chart, reused in order
+const COMPARISON_COLORS: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(100, 150, 255),
+    egui::Color32::from_rgb(120, 220, 120),
+    egui::Color32::from_rgb(220, 120, 220),
+    egui::Color32::from_rgb(240, 220, 90),
+    egui::Color32::from_rgb(90, 220, 220),
+    egui::Color32::from_rgb(200, 160, 120),
+];
+
+/// Curves of a finished run kept for the comparison overlay on the loss and accuracy
+/// charts, saved with its config and note by "Save Comparisons"
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CompletedRun {
+    /// Legend entry derived from the run's config
+    pub label: String,
+    pub config: NetworkConfig,
+    /// Free text about the run, edited in the compared runs list
+    #[serde(default)]
+    pub note: String,
+    pub first_epoch: usize,
+    pub losses: Vec<f64>,
+    pub accuracies: Vec<f64>,
+}
+
+/// Data for tracking training progress
+#[derive(Default, Clone)]
+pub struct TrainingData {
+    pub epoch: u32,
+    pub loss: f64,
+    pub accuracy: f64,
+    pub losses: Vec<f64>,
+    pub accuracies: Vec<f64>,
+    /// (epoch, accuracy or R²) where it was measured, without the loss-based estimates in between
+    pub measured_accuracies: Vec<(usize, f64)>,
+    /// Held-out loss/accuracy per charted epoch, empty without a validation split
+    pub val_losses: Vec<f64>,
+    pub val_accuracies: Vec<f64>,
+    /// Effective learning rate per charted epoch
+    pub learning_rates: Vec<f64>,
+    /// Epoch of `losses[0]`/`accuracies[0]` once the live history limit dropped older points
+    pub first_epoch: usize,
+    pub training_in_progress: bool,
+    pub completed: bool,
+    /// The training thread holds at the next epoch until resumed
+    pub paused: bool,
+    /// When the current pause began, so it doesn't count toward elapsed time
+    pub paused_at: Option<std::time::Instant>,
+    pub show_stop_confirm: bool,
+    pub dataset_path: String,
+    pub available_datasets: Vec<String>,
+    pub error_message: Option<String>,
+    pub notice: Option<String>,
+    /// Non-fatal warning shown as a dismissable popup
+    pub toast: Option<String>,
+    /// Result of the last f32 vs f64 comparison run
+    pub precision_comparison: Option<Result<PrecisionComparison, String>>,
+    /// Whether a precision comparison is running in the background
+    pub comparing_precision: bool,
+    /// Progress of the loss figure written after training
+    pub plot_status: Option<String>,
+    pub trained_weights: Option<ModelWeights>,
+    /// Training loss of every epoch of the last run from epoch 0, without the
+    /// live history limit and warm-up cut of `losses`, for "Continue"
+    pub loss_record: Vec<f64>,
+    /// Number of epochs run so far, including continued runs
+    pub epochs_run: usize,
+    /// Total epochs of the current (possibly continued) run
+    pub target_epochs: usize,
+    /// When the training loop started and at which epoch, for the ETA (None = not running)
+    pub started_at: Option<(std::time::Instant, usize)>,
+    /// Set by the "+N Epochs" button to continue from `trained_weights`
+    pub resume_epochs: Option<usize>,
+    /// (feature index, Pearson r with the label), strongest first
+    pub feature_correlations: Vec<(usize, f64)>,
+    /// (feature index, mean |d output / d input|) of the trained network, strongest first
+    pub feature_importance: Vec<(usize, f64)>,
+    /// Constant feature columns removed before the last run (indices as in the correlation list)
+    pub dropped_features: Vec<usize>,
+    /// Z-score statistics of the last run's training rows (None = unscaled)
+    pub normalization: Option<Normalization>,
+    /// Why the selected dataset can't be trained on (None = looks usable)
+    pub dataset_problem: Option<String>,
+    /// Column names of the selected dataset, bound and name the label column picker
+    pub column_names: Option<Vec<String>>,
+    /// Names of the loaded feature columns, indexed like the correlation list
+    pub feature_names: Vec<String>,
+    /// Rows kept, skipped and filled in when the dataset was last loaded
+    pub load_summary: Option<LoadSummary>,
+    /// Progress of the dataset load at the start of a run (None = not loading)
+    pub load_progress: Option<LoadProgress>,
+    /// Bootstrap 95% confidence interval of the final accuracy
+    pub accuracy_interval: Option<AccuracyInterval>,
+    /// Outcome counts of the final predictions
+    pub confusion_matrix: Option<ConfusionMatrix>,
+    /// Accuracy within each true class of the final predictions
+    pub class_accuracy: Option<ClassAccuracy>,
+    /// Per-sample data behind the confusion matrix, for the cell drill-down
+    pub evaluated_samples: Option<EvaluatedSamples>,
+    /// Reliability diagram of the final primary-head probabilities
+    pub calibration: Option<Calibration>,
+    /// ROC curve and AUC of the final primary-head probabilities
+    pub roc: Option<RocCurve>,
+    /// Epoch of the latest sample and the weight histogram of each layer at it (w1, w2, ...)
+    pub weight_histograms: Option<(usize, Vec<Histogram>)>,
+    /// Original values behind the primary label's 0/1 codes, for display
+    pub class_names: ClassNames,
+    /// Config of the run whose results are shown, set when training starts
+    pub run_config: Option<NetworkConfig>,
+    /// Per-head outcome counts when training several output heads (head 0 first)
+    pub head_metrics: Vec<(String, ConfusionMatrix)>,
+    /// MSE and R² of the final predictions of a regression run
+    pub regression_metrics: Option<RegressionMetrics>,
+    /// Accuracy of the logistic-regression baseline trained on the same data
+    pub baseline_accuracy: Option<f64>,
+    /// Epoch (0-based) and validation value of the monitored metric of the restored best weights
+    pub best_epoch: Option<(usize, f64)>,
+}
+
+impl TrainingData {
+    pub fn new() -> Self {
+        Self {
+            epoch: 0,
+            loss: 0.0,
+            accuracy: 0.0,
+            training_in_progress: false,
+            completed: false,
+            paused: false,
+            paused_at: None,
+            show_stop_confirm: false,
+            losses: Vec::new(),
+            accuracies: Vec::new(),
+            measured_accuracies: Vec::new(),
+            val_losses: Vec::new(),
+            val_accuracies: Vec::new(),
+            learning_rates: Vec::new(),
+            first_epoch: 0,
+            dataset_path: "csv/pollution_dataset5k.csv".to_string(), // Default dataset
+            available_datasets: vec![
+                "pollution_dataset5k.csv".to_string(),
+                "pollution_dataset2k.csv".to_string(),
+                "pollution_dataset1k.csv".to_string(),
+            ],
+            error_message: None,
+            notice: None,
+            toast: None,
+            precision_comparison: None,
+            comparing_precision: false,
+            plot_status: None,
+            trained_weights: None,
+            loss_record: Vec::new(),
+            epochs_run: 0,
+            target_epochs: 0,
+            started_at: None,
+            resume_epochs: None,
+            feature_correlations: Vec::new(),
+            feature_importance: Vec::new(),
+            dataset_problem: None,
+            column_names: None,
+            feature_names: Vec::new(),
+            load_summary: None,
+            load_progress: None,
+            dropped_features: Vec::new(),
+            normalization: None,
+            accuracy_interval: None,
+            confusion_matrix: None,
+            class_accuracy: None,
+            evaluated_samples: None,
+            calibration: None,
+            roc: None,
+            weight_histograms: None,
+            class_names: ClassNames::default(),
+            run_config: None,
+            head_metrics: Vec::new(),
+            regression_metrics: None,
+            baseline_accuracy: None,
+            best_epoch: None,
+        }
+    }
+
+    // Re-binarize the cached final probabilities of a finished run with `rule`,
+    // so threshold changes update the metrics without retraining
+    pub fn apply_decision_rule(&mut self, rule: DecisionRule) {
+        if self.training_in_progress {
+            return;
+        }
+        let Some(samples) = self.evaluated_samples.as_mut().filter(|samples| samples.rule != rule) else {
+            return;
+        };
+        samples.rule = rule;
+        let matrix = samples.confusion_matrix();
+        self.confusion_matrix = Some(matrix);
+        self.class_accuracy = Some(samples.class_accuracy());
+        self.accuracy = matrix.accuracy();
+        if let Some((_, primary)) = self.head_metrics.first_mut() {
+            *primary = matrix;
+        }
+        // The bootstrap interval was resampled at the old threshold
+        self.accuracy_interval = None;
+    }
+    
+    // Seconds since the loop started and the estimated seconds left, from
+    // the average time of the epochs run so far
+    pub fn elapsed_and_eta(&self) -> Option<(f64, Option<f64>)> {
+        let (started_at, start_epoch) = self.started_at?;
+        let now = self.paused_at.unwrap_or_else(std::time::Instant::now);
+        let elapsed = now.duration_since(started_at).as_secs_f64();
+        let done = self.epochs_run.saturating_sub(start_epoch);
+        let eta = (done > 0).then(|| {
+            elapsed / done as f64 * self.target_epochs.saturating_sub(self.epochs_run) as f64
+        });
+        Some((elapsed, eta))
+    }
+    
+    // Time spent paused is moved out of the elapsed time on resume
+    pub fn set_paused(&mut self, paused: bool) {
+        match (paused, self.paused_at) {
+            (true, None) => self.paused_at = Some(std::time::Instant::now()),
+            (false, Some(paused_at)) => {
+                if let Some((started_at, _)) = self.started_at.as_mut() {
+                    *started_at += paused_at.elapsed();
+                }
+                self.paused_at = None;
+            }
+            _ => {}
+        }
+        self.paused = paused;
+    }
+    
+    pub fn reset(&mut self) {
+        self.epoch = 0;
+        self.loss = 0.0;
+        self.accuracy = 0.0;
+        self.training_in_progress = true;
+        self.completed = false;
+        self.paused = false;
+        self.paused_at = None;
+        self.show_stop_confirm = false;
+        self.losses.clear();
+        self.accuracies.clear();
+        self.measured_accuracies.clear();
+        self.val_losses.clear();
+        self.val_accuracies.clear();
+        self.learning_rates.clear();
+        self.first_epoch = 0;
+        self.error_message = None;
+        self.notice = None;
+        self.plot_status = None;
+        self.trained_weights = None;
+        self.loss_record.clear();
+        self.epochs_run = 0;
+        self.target_epochs = 0;
+        self.started_at = None;
+        self.resume_epochs = None;
+        self.accuracy_interval = None;
+        self.confusion_matrix = None;
+        self.class_accuracy = None;
+        self.evaluated_samples = None;
+        self.calibration = None;
+        self.roc = None;
+        self.weight_histograms = None;
+        self.dropped_features.clear();
+        self.normalization = None;
+        self.load_summary = None;
+        self.head_metrics.clear();
+        self.regression_metrics = None;
+        self.feature_importance.clear();
+        self.baseline_accuracy = None;
+        self.best_epoch = None;
+    }
+}
+
+/// Whether the label is a class or a continuous value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum TaskType {
+    /// Sigmoid output, cross-entropy loss, accuracy
+    #[default]
+    Classification,
+    /// Linear output, mean squared error loss, R²
+    Regression,
+}
+
+impl TaskType {
+    pub const ALL: [TaskType; 2] = [TaskType::Classification, TaskType::Regression];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskType::Classification => "Classification",
+            TaskType::Regression => "Regression",
+        }
+    }
+
+    // Name of the per-epoch metric, used for chart and status labels
+    pub fn metric_label(&self) -> &'static str {
+        match self {
+            TaskType::Classification => "Accuracy",
+            TaskType::Regression => "R²",
+        }
+    }
+
+    // Metric value as printed: accuracy in percent, R² as a plain number
+    pub fn format_metric(&self, value: f64) -> String {
+        match self {
+            TaskType::Classification => format!("{:.2}%", value),
+            TaskType::Regression => format!("{:.4}", value),
+        }
+    }
+}
+
+/// Metric that drives early stopping and best-model checkpoints
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Metric {
+    Loss,
+    Accuracy,
+    F1,
+    /// Area under the ROC curve of the primary head
+    Auc,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 4] = [Metric::Loss, Metric::Accuracy, Metric::F1, Metric::Auc];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Metric::Loss => "Loss",
+            Metric::Accuracy => "Accuracy",
+            Metric::F1 => "F1",
+            Metric::Auc => "AUC",
+        }
+    }
+
+    // The direction that means "better" for this metric
+    pub fn default_mode(&self) -> MonitorMode {
+        match self {
+            Metric::Loss => MonitorMode::Min,
+            Metric::Accuracy | Metric::F1 | Metric::Auc => MonitorMode::Max,
+        }
+    }
+}
+
+/// Activation of the hidden layers
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Activation {
+    ReLU,
+    /// ReLU with a small slope for negative inputs, so units can't die
+    LeakyReLU,
+    Tanh,
+    Sigmoid,
+}
+
+impl Activation {
+    pub const ALL: [Activation; 4] = [Activation::ReLU, Activation::LeakyReLU, Activation::Tanh, Activation::Sigmoid];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Activation::ReLU => "ReLU",
+            Activation::LeakyReLU => "Leaky ReLU",
+            Activation::Tanh => "Tanh",
+            Activation::Sigmoid => "Sigmoid",
+        }
+    }
+
+    // Name used in exported files
+    pub fn id(&self) -> &'static str {
+        match self {
+            Activation::ReLU => "relu",
+            Activation::LeakyReLU => "leaky_relu",
+            Activation::Tanh => "tanh",
+            Activation::Sigmoid => "sigmoid",
+        }
+    }
+}
+
+/// Distribution of the initial layer weights
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum InitScheme {
+    /// Unscaled N(0, 1) samples
+    #[default]
+    StandardNormal,
+    /// N(0, 1) scaled by sqrt(2 / fan_in), suited to ReLU layers
+    He,
+    /// N(0, 1) scaled by sqrt(1 / fan_in)
+    Xavier,
+}
+
+impl InitScheme {
+    pub const ALL: [InitScheme; 3] = [InitScheme::StandardNormal, InitScheme::He, InitScheme::Xavier];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InitScheme::StandardNormal => "Standard Normal",
+            InitScheme::He => "He",
+            InitScheme::Xavier => "Xavier",
+        }
+    }
+
+    // Factor applied to standard normal samples of a layer with `fan_in` inputs
+    pub fn scale(&self, fan_in: usize) -> f64 {
+        match self {
+            InitScheme::StandardNormal => 1.0,
+            InitScheme::He => (2.0 / fan_in as f64).sqrt(),
+            InitScheme::Xavier => (1.0 / fan_in as f64).sqrt(),
+        }
+    }
+}
+
+/// Weight update rule
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Optimizer {
+    /// Gradient descent: w -= learning_rate * gradient, with `NetworkConfig::momentum`
+    /// a velocity v = momentum * v - learning_rate * gradient is added instead
+    Sgd,
+    /// Adam with bias-corrected first and second moment estimates
+    Adam { beta1: f64, beta2: f64, epsilon: f64 },
+}
+
+impl Optimizer {
+    pub const ADAM_DEFAULT: Optimizer = Optimizer::Adam { beta1: 0.9, beta2: 0.999, epsilon: 1e-8 };
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Optimizer::Sgd => "SGD",
+            Optimizer::Adam { .. } => "Adam",
+        }
+    }
+}
+
+/// How the learning rate changes over the epochs, relative to the base rate
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LrSchedule {
+    Constant,
+    /// Multiply the rate by `gamma` every `step_size` epochs
+    StepDecay { step_size: usize, gamma: f64 },
+    /// Multiply the rate by `gamma` every epoch
+    ExponentialDecay { gamma: f64 },
+}
+
+impl LrSchedule {
+    pub const ALL: [LrSchedule; 3] = [
+        LrSchedule::Constant,
+        LrSchedule::StepDecay { step_size: 100, gamma: 0.5 },
+        LrSchedule::ExponentialDecay { gamma: 0.995 },
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LrSchedule::Constant => "Constant",
+            LrSchedule::StepDecay { .. } => "Step Decay",
+            LrSchedule::ExponentialDecay { .. } => "Exponential Decay",
+        }
+    }
+
+    // Effective learning rate of `epoch` (0-based) for the base rate
+    pub fn rate(&self, base: f64, epoch: usize) -> f64 {
+        match *self {
+            LrSchedule::Constant => base,
+            LrSchedule::StepDecay { step_size, gamma } => base * gamma.powi((epoch / step_size.max(1)) as i32),
+            LrSchedule::ExponentialDecay { gamma } => base * gamma.powi(epoch as i32),
+        }
+    }
+}
+
+/// Whether the monitored metric should go down or up
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MonitorMode {
+    Min,
+    Max,
+}
+
+impl MonitorMode {
+    // Whether `value` is strictly better than `best`
+    pub fn improves(&self, value: f64, best: f64) -> bool {
+        match self {
+            MonitorMode::Min => value < best,
+            MonitorMode::Max => value > best,
+        }
+    }
+}
+
+/// Configuration for the neural network. Saved models and comparisons store
+/// it, settings missing from older files take their defaults.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    pub epochs: usize,
+    /// Sizes of the hidden layers from input to output, e.g. [32, 16, 8]
+    pub hidden_layers: Vec<usize>,
+    /// Classification or regression on the label column(s)
+    pub task_type: TaskType,
+    pub activation: Activation,
+    pub init_scheme: InitScheme,
+    pub optimizer: Optimizer,
+    /// Fraction of the previous SGD step carried into the next (0 = plain SGD)
+    pub momentum: f64,
+    /// Rows per gradient update (0 = full batch)
+    pub batch_size: usize,
+    /// Visit the rows in a fresh order drawn from the run's rng every epoch
+    pub shuffle: bool,
+    /// Share of the rows held out for validation after shuffling (0.0 = none)
+    pub validation_split: f64,
+    /// Split each class of the primary label separately, so the validation rows
+    /// keep the dataset's class ratio (classification only)
+    pub stratified: bool,
+    /// End with the weights of the epoch with the best validation value of `monitor`
+    pub restore_best_weights: bool,
+    pub learning_rate: f64,
+    pub lr_schedule: LrSchedule,
+    /// Weight decay strength, adds l2_lambda / 2 * sum(w^2) to the loss (0.0 = off)
+    pub l2_lambda: f64,
+    /// Share of hidden units zeroed per training step (0.0 = no dropout), never at evaluation
+    pub dropout_rate: f64,
+    /// Largest L2 norm of each weight, bias and skip gradient matrix before the update (None = no clipping)
+    pub grad_clip: Option<f64>,
+    /// Optional text file with the 0-based data row indices to train on (empty = all rows)
+    pub row_index_file: String,
+    /// Optional text file with one multiplier per feature column, applied before training (empty = unscaled)
+    pub feature_weights_file: String,
+    /// Reproducible mode: every run with the same config and dataset gives
+    /// bit-identical results. Without a `seed`, the run's rng (weight init,
+    /// dropout masks, shuffles, validation split) uses a fixed seed instead of
+    /// `thread_rng`, and `parallel` is ignored, so the training step runs on one
+    /// thread. Nothing else is constrained.
+    pub deterministic: bool,
+    /// Run the training step's matrix products and activations on the rayon
+    /// thread pool (ignored in deterministic mode)
+    pub parallel: bool,
+    /// Seed of the RNG behind weight init, shuffling and the validation split (None = random per run)
+    pub seed: Option<u64>,
+    /// Directory with w1/b1, w2/b2, ... CSV files (one pair per layer) to start training from (empty = random init)
+    pub weights_import_dir: String,
+    /// Field separator of the CSV files this app writes (weights, history, predictions and misclassified samples)
+    pub export_delimiter: u8,
+    /// Initialize the output bias to logit(positive rate) instead of zero (random init only)
+    pub prior_bias_init: bool,
+    pub early_stopping: bool,
+    /// Metric watched by early stopping and the best-weights checkpoint, on the
+    /// validation rows when there is a split
+    pub monitor: Metric,
+    /// Whether `monitor` improves by decreasing or increasing
+    pub mode: MonitorMode,
+    /// Epochs without improvement of the monitored metric before early stopping triggers
+    pub patience: usize,
+    /// Early stopping is only considered once this many epochs have run
+    pub min_epochs: usize,
+    /// Clamp the output logits to [-x, x] before the sigmoid (None = no clamp)
+    pub logit_clamp: Option<f64>,
+    /// Render the loss figure on a separate thread instead of the training thread
+    pub background_plotting: bool,
+    /// Treat the first CSV column as an ID/index and leave it out of the features
+    pub ignore_first_column: bool,
+    /// Further 0-based CSV column indices to leave out of the features
+    pub ignored_columns: Vec<usize>,
+    /// 0-based CSV column holding the label (None = the last column)
+    pub label_column: Option<usize>,
+    /// CSV columns trained as extra binary output heads next to the label column
+    pub extra_label_columns: Vec<usize>,
+    /// Handling of rows whose field count differs from the header
+    pub ragged_rows: RaggedRows,
+    /// Handling of empty or non-numeric cells
+    pub missing_values: MissingValues,
+    /// Field separator of the dataset (None = detected from the first line)
+    pub dataset_delimiter: Option<u8>,
+    /// Epochs between logged epochs, which measure the accuracy, notify the
+    /// observers and sample the weight histograms (0 is treated as 1)
+    pub log_interval: usize,
+    /// Append a JSON line per logged epoch to result/progress.jsonl
+    pub progress_log: bool,
+    /// Bootstrap resamples for the final accuracy confidence interval (0 = off)
+    pub bootstrap_samples: usize,
+    /// Also train a logistic-regression baseline (no hidden layer) for comparison
+    pub compare_baseline: bool,
+    /// Wall-clock limit for the epoch loop in seconds (None = epochs only)
+    pub max_train_seconds: Option<f64>,
+    /// Rows per forward pass when computing final metrics (None = whole set)
+    pub eval_batch_size: Option<usize>,
+    /// Add the input to the hidden-layer output (through a learned projection
+    /// when the feature count differs from the hidden size)
+    pub skip_connection: bool,
+    /// Normalize each hidden pre-activation over the batch, with a learned scale
+    /// and shift; inference uses the running statistics
+    pub batch_norm: bool,
+    /// Remove zero-variance feature columns before training (otherwise only warn)
+    pub drop_constant_features: bool,
+    /// Standardize each feature to zero mean and unit variance before training
+    pub normalize_features: bool,
+    /// Keep only the last N loss/accuracy points for the live charts (None = all).
+    /// Every epoch is then streamed to result/progress.jsonl for the full record.
+    pub live_history_limit: Option<usize>,
+    /// Finished runs overlaid on the loss chart, the oldest are dropped first (0 = none)
+    pub max_compared_runs: usize,
+    /// Epochs before this one train normally but are not recorded for the charts,
+    /// so a wild warm-up loss doesn't dominate the plot scale
+    pub metrics_start_epoch: usize,
+    /// Probability from which a prediction counts as class 1
+    pub threshold: f64,
+    /// Class predicted for a probability exactly at the threshold (e.g. 0.5) in every metric
+    pub tie_break: TieBreak,
+    /// Stop when the logged loss grows to this many times its best value (None = off)
+    pub divergence_factor: Option<f64>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            epochs: 1000,
+            task_type: TaskType::Classification,
+            activation: Activation::ReLU,
+            init_scheme: InitScheme::StandardNormal,
+            optimizer: Optimizer::Sgd,
+            momentum: 0.0,
+            batch_size: 0,
+            shuffle: true,
+            validation_split: 0.0,
+            stratified: false,
+            restore_best_weights: true,
+            hidden_layers: vec![16],
+            learning_rate: 0.01,
+            lr_schedule: LrSchedule::Constant,
+            l2_lambda: 0.0,
+            grad_clip: None,
+            dropout_rate: 0.0,
+            row_index_file: String::new(),
+            feature_weights_file: String::new(),
+            deterministic: false,
+            parallel: false,
+            seed: None,
+            weights_import_dir: String::new(),
+            export_delimiter: b',',
+            prior_bias_init: false,
+            early_stopping: false,
+            monitor: Metric::Loss,
+            mode: MonitorMode::Min,
+            patience: 50,
+            min_epochs: 100,
+            logit_clamp: None,
+            background_plotting: true,
+            ignore_first_column: false,
+            ignored_columns: Vec::new(),
+            label_column: None,
+            extra_label_columns: Vec::new(),
+            ragged_rows: RaggedRows::Fail,
+            missing_values: MissingValues::Skip,
+            dataset_delimiter: None,
+            log_interval: 100,
+            progress_log: false,
+            bootstrap_samples: 200,
+            compare_baseline: false,
+            max_train_seconds: None,
+            eval_batch_size: None,
+            skip_connection: false,
+            batch_norm: false,
+            drop_constant_features: false,
+            normalize_features: true,
+            live_history_limit: None,
+            max_compared_runs: 5,
+            metrics_start_epoch: 0,
+            threshold: 0.5,
+            tie_break: TieBreak::Positive,
+            divergence_factor: None,
+        }
+    }
+}
+
+impl NetworkConfig {
+    // The default configuration, keeping how the selected dataset's columns are read
+    pub fn defaults_for_dataset(&self) -> NetworkConfig {
+        NetworkConfig {
+            ignore_first_column: self.ignore_first_column,
+            ignored_columns: self.ignored_columns.clone(),
+            label_column: self.label_column,
+            extra_label_columns: self.extra_label_columns.clone(),
+            dataset_delimiter: self.dataset_delimiter,
+            ..NetworkConfig::default()
+        }
+    }
+    
+    // Whether both configs train the same run (export-only settings and the
+    // decision rule, which is re-applied to finished runs, are ignored)
+    pub fn same_run_as(&self, other: &NetworkConfig) -> bool {
+        let mut other = other.clone();
+        other.export_delimiter = self.export_delimiter;
+        other.threshold = self.threshold;
+        other.tie_break = self.tie_break;
+        *self == other
+    }
+    
+    /// Whether 0-based `epoch` of a run ending before `epochs` is a logged one.
+    /// The last epoch always is.
+    pub fn is_logged_epoch(&self, epoch: usize, epochs: usize) -> bool {
+        epoch.is_multiple_of(self.log_interval.max(1)) || epoch + 1 == epochs
+    }
+    
+    pub fn decision_rule(&self) -> DecisionRule {
+        DecisionRule { threshold: self.threshold, tie_break: self.tie_break }
+    }
+    
+    pub fn load_options(&self) -> LoadOptions {
+        let mut ignored_columns = self.ignored_columns.clone();
+        if self.ignore_first_column && !ignored_columns.contains(&0) {
+            ignored_columns.push(0);
+        }
+        let mut extra_label_columns = Vec::new();
+        for &column in &self.extra_label_columns {
+            if !extra_label_columns.contains(&column) {
+                extra_label_columns.push(column);
+            }
+        }
+        LoadOptions {
+            ignored_columns,
+            label_column: self.label_column,
+            extra_label_columns,
+            ragged_rows: self.ragged_rows,
+            missing_values: self.missing_values,
+            delimiter: self.dataset_delimiter,
+        }
+    }
+}
+
+/// Snapshots kept for configuration undo/redo
+const CONFIG_HISTORY_LIMIT: usize = 50;
+/// A config must stay unchanged this long before it becomes an undo step, so
+/// dragging a value records one step instead of one per frame
+const CONFIG_HISTORY_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Bounded undo/redo stack of configuration snapshots (UI-only)
+#[derive(Clone)]
+struct ConfigHistory {
+    undo: Vec<NetworkConfig>,
+    redo: Vec<NetworkConfig>,
+    // Last recorded configuration, the state undo steps back from
+    committed: NetworkConfig,
+    // Config seen in the previous frame and when it last changed
+    last_seen: NetworkConfig,
+    changed_at: std::time::Instant,
+}
+
+impl ConfigHistory {
+    fn new(config: &NetworkConfig) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            committed: config.clone(),
+            last_seen: config.clone(),
+            changed_at: std::time::Instant::now(),
+        }
+    }
+
+    // Called every frame with the live config; records it once it has settled
+    fn observe(&mut self, config: &NetworkConfig) {
+        if *config != self.last_seen {
+            self.last_seen = config.clone();
+            self.changed_at = std::time::Instant::now();
+        } else if *config != self.committed && self.changed_at.elapsed() >= CONFIG_HISTORY_DEBOUNCE {
+            self.undo.push(std::mem::replace(&mut self.committed, config.clone()));
+            if self.undo.len() > CONFIG_HISTORY_LIMIT {
+                self.undo.remove(0);
+            }
+            self.redo.clear();
+        }
+    }
+
+    // The config to restore for Ctrl+Z, if any. Unsettled edits are undone first.
+    fn undo(&mut self, current: &NetworkConfig) -> Option<NetworkConfig> {
+        if *current != self.committed {
+            self.redo.push(current.clone());
+        } else {
+            let previous = self.undo.pop()?;
+            self.redo.push(std::mem::replace(&mut self.committed, previous));
+        }
+        self.last_seen = self.committed.clone();
+        Some(self.committed.clone())
+    }
+
+    // The config to restore for Ctrl+Y, if any
+    fn redo(&mut self) -> Option<NetworkConfig> {
+        let next = self.redo.pop()?;
+        self.undo.push(std::mem::replace(&mut self.committed, next));
+        self.last_seen = self.committed.clone();
+        Some(self.committed.clone())
+    }
+}
+
+/// UI-only display preferences (not used by the training thread)
+#[derive(Clone)]
+pub struct DisplaySettings {
+    /// Accuracy (%) below this value is shown in red
+    pub accuracy_low_threshold: f64,
+    /// Accuracy (%) above this value is shown in green, in between is yellow
+    pub accuracy_high_threshold: f64,
+    /// Which series are shown in the combined metrics chart (missing = shown)
+    pub visible_metrics: HashMap<String, bool>,
+    /// Filter for the per-feature statistics on wide datasets
+    pub feature_search: String,
+    /// Confusion matrix cell whose samples are listed (None = no drill-down)
+    pub selected_cell: Option<ConfusionCell>,
+    /// Stop continuous repainting and keep the charts as they were, training continues
+    pub pause_display: bool,
+    /// Show the effective learning rate per epoch below the metric charts
+    pub show_learning_rate: bool,
+    /// Show the sampled weight distribution of one layer below the metric charts
+    pub show_weight_histogram: bool,
+    /// 0-based layer of that histogram
+    pub histogram_layer: usize,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            accuracy_low_threshold: 60.0,
+            accuracy_high_threshold: 80.0,
+            visible_metrics: HashMap::new(),
+            feature_search: String::new(),
+            selected_cell: None,
+            pause_display: false,
+            show_learning_rate: false,
+            show_weight_histogram: false,
+            histogram_layer: 0,
+        }
+    }
+}
+
+impl DisplaySettings {
+    pub fn accuracy_color(&self, accuracy: f64) -> egui::Color32 {
+        if accuracy < self.accuracy_low_threshold {
+            egui::Color32::from_rgb(230, 60, 60)
+        } else if accuracy <= self.accuracy_high_threshold {
+            egui::Color32::from_rgb(230, 200, 50)
+        } else {
+            egui::Color32::from_rgb(60, 200, 90)
+        }
+    }
+}
+
+// Parse a comma-separated list of layer sizes such as "32,16,8"
+pub fn parse_hidden_layers(text: &str) -> Result<Vec<usize>, String> {
+    let mut layers = Vec::new();
+    for part in text.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            return Err("Empty layer size in list".to_string());
+        }
+        let size = part.parse::<usize>()
+            .map_err(|_| format!("'{}' is not a whole number", part))?;
+        if !(1..=1024).contains(&size) {
+            return Err(format!("Layer size {} must be between 1 and 1024", size));
+        }
+        layers.push(size);
+    }
+    Ok(layers)
+}
+
+// Parse a comma-separated list of column indices such as "0, 3"; empty means none
+pub fn parse_index_list(text: &str) -> Result<Vec<usize>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<usize>().map_err(|_| format!("'{}' is not a column index", part)))
+        .collect()
+}
+
+pub fn format_hidden_layers(layers: &[usize]) -> String {
+    layers.iter().map(|size| size.to_string()).collect::<Vec<_>>().join(",")
+}
+
+// Header name of a loaded feature, or its index when the names are not known
+fn feature_label(names: &[String], feature: usize) -> String {
+    names.get(feature).cloned().unwrap_or_else(|| format!("feature {}", feature))
+}
+
+// State of the "Saved Model Inference" panel: the model file, the loaded model
+// and the last predictions as (dataset file name, probabilities rows x heads)
+#[derive(Clone, Default)]
+struct InferencePanel {
+    model_path: String,
+    model: Option<Result<LoadedModel, String>>,
+    dataset: String,
+    predictions: Option<Result<(String, Array2<f64>), String>>,
+}
+
+/// Metrics of one epoch, sent by the training thread and applied to
+/// `TrainingData` when the channel is drained
+#[derive(Clone, Copy, Debug)]
+pub struct ProgressUpdate {
+    pub epoch: usize,
+    pub loss: f64,
+    /// Accuracy (%) at logged epochs or R² every epoch, None when it should be estimated from the loss
+    pub accuracy: Option<f64>,
+    /// Validation (loss, accuracy) when rows are held out
+    pub validation: Option<(f64, f64)>,
+    pub learning_rate: f64,
+}
+
+#[derive(Clone)]
+pub struct NeuralNetworkApp {
+    training_data: Arc<Mutex<TrainingData>>,
+    network_config: Arc<Mutex<NetworkConfig>>,
+    // Epoch updates from the training thread, drained every frame so the
+    // training loop never waits on the UI
+    progress_tx: mpsc::Sender<ProgressUpdate>,
+    progress_rx: Arc<Mutex<mpsc::Receiver<ProgressUpdate>>>,
+    // Extra receiver of every update, e.g. the headless printer
+    progress_sink: Option<mpsc::Sender<ProgressUpdate>>,
+    // Set once a stop is confirmed, checked by the training loop every epoch
+    stop_flag: Arc<AtomicBool>,
+    // Wakes the training thread when `TrainingData::paused` changes
+    pause_changed: Arc<Condvar>,
+    train_callback: Option<Arc<dyn Fn() + Send + Sync + 'static>>,
+    // Called by the training loop every logged epoch, see `add_epoch_observer`
+    epoch_observers: Vec<EpochObserver>,
+    display_settings: DisplaySettings,
+    // Text buffer for the hidden layers field and the config value it last matched
+    hidden_layers_text: String,
+    hidden_layers_synced: Vec<usize>,
+    // Same for the typed epochs field
+    epochs_text: String,
+    epochs_synced: usize,
+    // Same for the ignored columns field
+    ignored_columns_text: String,
+    ignored_columns_synced: Vec<usize>,
+    // And for the extra label columns field
+    extra_labels_text: String,
+    extra_labels_synced: Vec<usize>,
+    // Saved run history shown in the charts instead of the live series
+    replay: Option<Replay>,
+    // Finished runs overlaid on the charts, shared with the training thread
+    completed_runs: Arc<Mutex<Vec<CompletedRun>>>,
+    config_history: ConfigHistory,
+    // Loss/accuracy series captured when display updates were paused
+    frozen_series: Option<[Vec<f64>; 5]>,
+    // Header field count and lines of ragged rows found when Train was clicked,
+    // shown in a dialog asking whether to drop or pad them
+    ragged_prompt: Option<(usize, Vec<usize>)>,
+    // Pasted feature row for a quick prediction and its result (probabilities or error)
+    prediction_input: String,
+    prediction_result: Option<Result<Vec<f64>, String>>,
+    // One entered value per feature for the what-if prediction, and its outcome
+    what_if_values: Vec<f64>,
+    what_if_result: Option<Result<String, String>>,
+    // Outcome of the last "Save Model" click (message or error)
+    model_save_result: Option<Result<String, String>>,
+    inference: InferencePanel,
+    // Settings of the "Generate Synthetic Dataset" panel
+    synthetic_spec: SyntheticSpec,
+}
+
+impl Default for NeuralNetworkApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NeuralNetworkApp {
+    pub fn new() -> Self {
+        let app = Self::without_saved_comparisons();
+        
+        // Comparisons saved in an earlier session come back on launch
+        if std::path::Path::new(COMPARISONS_PATH).is_file() {
+            app.load_comparisons();
+        }
+        
+        app
+    }
+    
+    // The app as `new` builds it, minus the compared runs of earlier sessions
+    pub fn without_saved_comparisons() -> Self {
+        let (progress_tx, progress_rx) = mpsc::channel();
+        let app = Self {
+            training_data: Arc::new(Mutex::new(TrainingData::new())),
+            network_config: Arc::new(Mutex::new(NetworkConfig::default())),
+            progress_tx,
+            progress_rx: Arc::new(Mutex::new(progress_rx)),
+            progress_sink: None,
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            pause_changed: Arc::new(Condvar::new()),
+            config_history: ConfigHistory::new(&NetworkConfig::default()),
+            frozen_series: None,
+            train_callback: None,
+            epoch_observers: Vec::new(),
+            display_settings: DisplaySettings::default(),
+            hidden_layers_text: String::new(),
+            hidden_layers_synced: Vec::new(),
+            epochs_text: String::new(),
+            epochs_synced: 0,
+            ignored_columns_text: String::new(),
+            ignored_columns_synced: Vec::new(),
+            extra_labels_text: String::new(),
+            extra_labels_synced: Vec::new(),
+            replay: None,
+            completed_runs: Arc::new(Mutex::new(Vec::new())),
+            synthetic_spec: SyntheticSpec::default(),
+            ragged_prompt: None,
+            prediction_input: String::new(),
+            prediction_result: None,
+            what_if_values: Vec::new(),
+            what_if_result: None,
+            model_save_result: None,
+            inference: InferencePanel { model_path: MODEL_PATH.to_string(), ..Default::default() },
+        };
+        
+        // Scan for available datasets on startup
+        app.refresh_datasets();
+        
+        app
+    }
+
+    // Queue an epoch's metrics for the UI without touching the shared data
+    pub fn send_progress(&self, update: ProgressUpdate) {
+        // The receiver lives as long as the app, so sending can't fail
+        let _ = self.progress_tx.send(update);
+        if let Some(sink) = &self.progress_sink {
+            let _ = sink.send(update);
+        }
+    }
+    
+    // Also send every update to `sink`, the charts keep being fed as well
+    pub fn set_progress_sink(&mut self, sink: mpsc::Sender<ProgressUpdate>) {
+        self.progress_sink = Some(sink);
+    }
+    
+    // Apply all queued epoch updates in order
+    pub fn drain_progress(&self) {
+        let rx = self.progress_rx.lock().unwrap();
+        while let Ok(update) = rx.try_recv() {
+            self.update_progress(update);
+        }
+    }
+    
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop_flag.clone()
+    }
+    
+    pub fn pause_signal(&self) -> Arc<Condvar> {
+        self.pause_changed.clone()
+    }
+    
+    pub fn set_paused(&self, paused: bool) {
+        self.training_data.lock().unwrap().set_paused(paused);
+        self.pause_changed.notify_all();
+    }
+    
+    fn update_progress(&self, update: ProgressUpdate) {
+        let ProgressUpdate { epoch, loss, accuracy, validation, learning_rate } = update;
+        let (history_limit, start_epoch) = {
+            let config = self.network_config.lock().unwrap();
+            (config.live_history_limit, config.metrics_start_epoch)
+        };
+        let mut data = self.training_data.lock().unwrap();
+        data.epoch = epoch as u32;
+        data.epochs_run = epoch + 1;
+        data.loss = loss;
+        
+        // Only update accuracy if it's valid
+        match accuracy {
+            Some(accuracy) => data.accuracy = accuracy,
+            // Estimate accuracy from loss
+            None => data.accuracy = self.estimate_accuracy(loss),
+        }
+        
+        // Warm-up epochs are shown as current values but kept out of the charts
+        if epoch < start_epoch {
+            return;
+        }
+        if data.losses.is_empty() {
+            data.first_epoch = epoch;
+        }
+        
+        // Store for plotting - create local copies to avoid borrowing issues
+        let current_accuracy = data.accuracy;
+        data.losses.push(loss);
+        data.accuracies.push(current_accuracy);
+        if let Some(accuracy) = accuracy {
+            data.measured_accuracies.push((epoch, accuracy));
+        }
+        data.learning_rates.push(learning_rate);
+        if let Some((val_loss, val_accuracy)) = validation {
+            data.val_losses.push(val_loss);
+            data.val_accuracies.push(val_accuracy);
+        }
+        
+        // Ring-buffer mode for marathon runs: drop the oldest points beyond the limit
+        if let Some(limit) = history_limit {
+            let excess = data.losses.len().saturating_sub(limit);
+            if excess > 0 {
+                data.losses.drain(..excess);
+                data.accuracies.drain(..excess);
+                data.learning_rates.drain(..excess);
+                let val_excess = excess.min(data.val_losses.len());
+                data.val_losses.drain(..val_excess);
+                data.val_accuracies.drain(..val_excess);
+                data.first_epoch += excess;
+            }
+        }
+    }
+
+    pub fn training_completed(&self, accuracy: f64) {
+        // Epochs still queued belong before the final metrics
+        self.drain_progress();
+        self.stop_flag.store(false, Ordering::Relaxed);  // Reset flag saat pelatihan selesai
+        let mut data = self.training_data.lock().unwrap();
+        data.completed = true;
+        data.training_in_progress = false;
+        data.set_paused(false);
+        data.started_at = None;
+        data.accuracy = accuracy;
+        drop(data);
+        self.record_completed_run();
+    }
+    
+    // Append the finished run to the comparison overlay, keeping at most
+    // `max_compared_runs` of the newest
+    fn record_completed_run(&self) {
+        let config = self.network_config.lock().unwrap().clone();
+        let run = {
+            let data = self.training_data.lock().unwrap();
+            let run_config = data.run_config.as_ref().unwrap_or(&config);
+            CompletedRun {
+                label: format!(
+                    "[{}] lr {}, {} epochs",
+                    format_hidden_layers(&run_config.hidden_layers), run_config.learning_rate, data.epochs_run
+                ),
+                config: run_config.clone(),
+                note: String::new(),
+                first_epoch: data.first_epoch,
+                losses: data.losses.clone(),
+                accuracies: data.accuracies.clone(),
+            }
+        };
+        let mut runs = self.completed_runs.lock().unwrap();
+        runs.push(run);
+        let excess = runs.len().saturating_sub(config.max_compared_runs);
+        runs.drain(..excess);
+    }
+    
+    pub fn save_comparisons(&self) {
+        let runs = self.completed_runs.lock().unwrap().clone();
+        let result = save_comparisons(COMPARISONS_PATH, &runs);
+        self.training_data.lock().unwrap().notice = Some(match result {
+            Ok(()) => format!("{} compared runs saved to {}", runs.len(), COMPARISONS_PATH),
+            Err(e) => format!("Saving the comparisons failed: {}", e),
+        });
+    }
+    
+    // Replace the compared runs with the saved ones, the newest `max_compared_runs` of them
+    pub fn load_comparisons(&self) {
+        let notice = match load_comparisons(COMPARISONS_PATH) {
+            Ok(mut runs) => {
+                let excess = runs.len().saturating_sub(self.network_config.lock().unwrap().max_compared_runs);
+                runs.drain(..excess);
+                let notice = format!("{} compared runs loaded from {}", runs.len(), COMPARISONS_PATH);
+                *self.completed_runs.lock().unwrap() = runs;
+                notice
+            }
+            Err(e) => format!("Loading the comparisons failed: {}", e),
+        };
+        println!("{}", notice);
+        self.training_data.lock().unwrap().notice = Some(notice);
+    }
+    
+    // Names of the loaded dataset's columns, and of the ones the options make features
+    pub fn set_column_names(&self, column_names: Vec<String>, options: &LoadOptions) {
+        let mut data = self.training_data.lock().unwrap();
+        data.feature_names = options.feature_columns(column_names.len()).into_iter()
+            .map(|column| column_names[column].clone())
+            .collect();
+        data.column_names = Some(column_names);
+    }
+    
+    pub fn set_feature_correlations(&self, correlations: Vec<(usize, f64)>) {
+        let mut data = self.training_data.lock().unwrap();
+        data.feature_correlations = correlations;
+    }
+    
+    // Load the selected dataset and compute the model-free statistics
+    pub fn analyze_dataset(&self) {
+        let path = self.training_data.lock().unwrap().dataset_path.clone();
+        let options = self.network_config.lock().unwrap().load_options();
+        match crate::load_data(&path, &options) {
+            Ok((x, y, _, summary, column_names)) => {
+                self.set_load_summary(summary);
+                self.set_column_names(column_names, &options);
+                self.set_feature_correlations(feature_label_correlations(&x, &y));
+            }
+            Err(e) => {
+                let mut data = self.training_data.lock().unwrap();
+                data.notice = Some(format!("Could not analyze {}: {}", path, e));
+            }
+        }
+    }
+    
+    pub fn set_accuracy_interval(&self, interval: Option<AccuracyInterval>) {
+        let mut data = self.training_data.lock().unwrap();
+        data.accuracy_interval = interval;
+    }
+    
+    pub fn set_regression_metrics(&self, metrics: RegressionMetrics) {
+        let mut data = self.training_data.lock().unwrap();
+        data.regression_metrics = Some(metrics);
+    }
+    
+    pub fn set_baseline_accuracy(&self, accuracy: Option<f64>) {
+        let mut data = self.training_data.lock().unwrap();
+        data.baseline_accuracy = accuracy;
+    }
+    
+    pub fn set_best_epoch(&self, best: Option<(usize, f64)>) {
+        let mut data = self.training_data.lock().unwrap();
+        data.best_epoch = best;
+    }
+    
+    pub fn set_confusion_matrix(&self, matrix: ConfusionMatrix) {
+        let mut data = self.training_data.lock().unwrap();
+        data.confusion_matrix = Some(matrix);
+    }
+    
+    pub fn set_class_accuracy(&self, accuracy: ClassAccuracy) {
+        let mut data = self.training_data.lock().unwrap();
+        data.class_accuracy = Some(accuracy);
+    }
+    
+    pub fn set_class_names(&self, names: ClassNames) {
+        let mut data = self.training_data.lock().unwrap();
+        data.class_names = names;
+    }
+    
+    pub fn set_load_summary(&self, summary: LoadSummary) {
+        let mut data = self.training_data.lock().unwrap();
+        data.load_summary = Some(summary);
+    }
+    
+    pub fn set_load_progress(&self, progress: Option<LoadProgress>) {
+        let mut data = self.training_data.lock().unwrap();
+        data.load_progress = progress;
+    }
+    
+    pub fn set_head_metrics(&self, metrics: Vec<(String, ConfusionMatrix)>) {
+        let mut data = self.training_data.lock().unwrap();
+        data.head_metrics = metrics;
+    }
+    
+    // Scores are per network input, numbered like the dataset's features again by
+    // skipping the dropped constant columns
+    pub fn set_feature_importance(&self, scores: Vec<f64>) {
+        let mut data = self.training_data.lock().unwrap();
+        let kept = (0..).filter(|feature| !data.dropped_features.contains(feature));
+        let mut importance: Vec<(usize, f64)> = kept.zip(scores).collect();
+        importance.sort_by(|a, b| b.1.total_cmp(&a.1));
+        data.feature_importance = importance;
+    }
+    
+    pub fn set_dropped_features(&self, features: Vec<usize>) {
+        let mut data = self.training_data.lock().unwrap();
+        data.dropped_features = features;
+    }
+    
+    pub fn set_normalization(&self, normalization: Option<Normalization>) {
+        let mut data = self.training_data.lock().unwrap();
+        data.normalization = normalization;
+    }
+    
+    pub fn set_weight_histograms(&self, epoch: usize, histograms: Vec<Histogram>) {
+        let mut data = self.training_data.lock().unwrap();
+        data.weight_histograms = Some((epoch, histograms));
+    }
+    
+    pub fn set_evaluated_samples(&self, samples: EvaluatedSamples) {
+        let mut data = self.training_data.lock().unwrap();
+        data.calibration = calibration_curve(&samples.probabilities, &samples.labels, CALIBRATION_BINS);
+        data.roc = roc_curve(&samples.probabilities, &samples.labels);
+        data.evaluated_samples = Some(samples);
+    }
+    
+    // Compose the curves, confusion matrix and metrics into result/report.png
+    pub fn generate_report(&self) {
+        let config = self.network_config.lock().unwrap().clone();
+        let mut data = self.training_data.lock().unwrap();
+        let config = data.run_config.clone().unwrap_or(config);
+        let result = crate::report::generate_report_image(crate::report::REPORT_PATH, &data, &config);
+        data.notice = Some(match result {
+            Ok(()) => format!("Report saved to {}", crate::report::REPORT_PATH),
+            Err(e) => format!("Report generation failed: {}", e),
+        });
+    }
+    
+    // Keep the per-epoch curves of the finished run in result/history.json for replay
+    pub fn save_run_history(&self) {
+        let config = self.network_config.lock().unwrap().clone();
+        let history = {
+            let data = self.training_data.lock().unwrap();
+            let run_config = data.run_config.as_ref().unwrap_or(&config);
+            RunHistory {
+                dataset: data.dataset_path.clone(),
+                first_epoch: data.first_epoch,
+                hidden_layers: run_config.hidden_layers.clone(),
+                learning_rate: run_config.learning_rate,
+                final_accuracy: data.accuracy,
+                losses: data.losses.clone(),
+                accuracies: data.accuracies.clone(),
+            }
+        };
+        if let Err(e) = history.save(HISTORY_PATH) {
+            eprintln!("Warning: could not save {}: {}", HISTORY_PATH, e);
+            self.show_toast(&format!("Run history could not be saved: {}", e));
+        }
+    }
+    
+    // Called when the training loop is about to run from `start_epoch` to `epochs`
+    pub fn set_target_epochs(&self, start_epoch: usize, epochs: usize) {
+        let mut data = self.training_data.lock().unwrap();
+        data.target_epochs = epochs;
+        data.started_at = Some((std::time::Instant::now(), start_epoch));
+    }
+    
+    pub fn set_plot_status(&self, status: &str) {
+        let mut data = self.training_data.lock().unwrap();
+        data.plot_status = Some(status.to_string());
+    }
+    
+    pub fn show_toast(&self, message: &str) {
+        let mut data = self.training_data.lock().unwrap();
+        data.toast = Some(message.to_string());
+    }
+    
+    pub fn set_trained_weights(&self, weights: ModelWeights) {
+        let mut data = self.training_data.lock().unwrap();
+        data.trained_weights = Some(weights);
+    }
+    
+    pub fn set_loss_record(&self, losses: Vec<f64>) {
+        let mut data = self.training_data.lock().unwrap();
+        data.loss_record = losses;
+    }
+    
+    // Write a generated dataset to csv/ and select it for the next run
+    pub fn generate_synthetic_dataset(&self, spec: &SyntheticSpec) {
+        let path = format!("csv/{}", spec.file_name());
+        let result = spec.generate().and_then(|(x, y)| write_dataset_csv(&path, &x, &y));
+        match result {
+            Ok(()) => {
+                self.refresh_datasets();
+                let delimiter = self.network_config.lock().unwrap().dataset_delimiter;
+                let mut data = self.training_data.lock().unwrap();
+                data.dataset_path = path.clone();
+                data.dataset_problem = check_dataset_file(&path);
+                data.column_names = crate::dataset_column_names(&path, delimiter).ok();
+                data.notice = Some(format!("Synthetic dataset written to {}", path));
+            }
+            Err(e) => self.show_toast(&format!("Could not generate the dataset: {}", e)),
+        }
+    }
+    
+    // How the last run turned CSV rows into network inputs
+    fn preprocessing(config: &NetworkConfig, data: &TrainingData) -> Result<Preprocessing, Box<dyn std::error::Error>> {
+        let feature_weights = match config.feature_weights_file.trim() {
+            "" => None,
+            file => Some(crate::load_feature_weights(file)?),
+        };
+        let options = config.load_options();
+        Ok(Preprocessing {
+            ignored_columns: options.ignored_columns,
+            label_column: options.label_column,
+            extra_label_columns: options.extra_label_columns,
+            feature_weights,
+            dropped_features: data.dropped_features.clone(),
+            class_names: [data.class_names.negative.clone(), data.class_names.positive.clone()],
+            normalization: data.normalization.clone(),
+        })
+    }
+    
+    // Outcome of the what-if values with the last run's network, or else the loaded model
+    fn predict_what_if(&self, values: &[f64]) -> Result<String, String> {
+        let data = self.training_data.lock().unwrap();
+        let (weights, config, preprocessing) = if let Some(weights) = &data.trained_weights {
+            let config = data.run_config.clone().unwrap_or_else(|| self.network_config.lock().unwrap().clone());
+            let preprocessing = Self::preprocessing(&config, &data).map_err(|e| e.to_string())?;
+            (weights, config, preprocessing)
+        } else if let Some(Ok(model)) = &self.inference.model {
+            (&model.weights, model.config.clone(), model.preprocessing.clone())
+        } else {
+            return Err("No trained or loaded model".to_string());
+        };
+        let output = crate::predict_one(values, weights, &config, &preprocessing).map_err(|e| e.to_string())?;
+        Ok(match config.task_type {
+            TaskType::Classification => {
+                let [negative, positive] = &preprocessing.class_names;
+                let class = if config.decision_rule().predict(output) { positive } else { negative };
+                format!("p({}) = {:.4} → {}", positive, output, class)
+            }
+            TaskType::Regression => format!("Predicted value: {:.4}", output),
+        })
+    }
+    
+    // JSON export with the preprocessing, for loading the network in Python.
+    // Activation, task and clamp come from the finished run, not the edited config.
+    pub fn export_weights_json(&self, path: &str) {
+        let mut data = self.training_data.lock().unwrap();
+        let config = data.run_config.clone().unwrap_or_else(|| self.network_config.lock().unwrap().clone());
+        let result = match &data.trained_weights {
+            Some(weights) => Self::preprocessing(&config, &data)
+                .and_then(|preprocessing| weights.export_json(path, config.activation.id(), config.task_type, config.logit_clamp, &preprocessing)),
+            None => Err("No trained weights available".into()),
+        };
+        data.notice = Some(match result {
+            Ok(()) => format!("Weights exported to {}", path),
+            Err(e) => format!("Weight export failed: {}", e),
+        });
+    }
+    
+    // ONNX graph of the finished run's network, preprocessing included
+    pub fn export_weights_onnx(&self, path: &str) {
+        let mut data = self.training_data.lock().unwrap();
+        let config = data.run_config.clone().unwrap_or_else(|| self.network_config.lock().unwrap().clone());
+        let result = match &data.trained_weights {
+            Some(weights) => Self::preprocessing(&config, &data)
+                .and_then(|preprocessing| export_onnx(path, weights, &config, &preprocessing)),
+            None => Err("No trained weights available".into()),
+        };
+        data.notice = Some(match result {
+            Ok(()) => format!("ONNX model exported to {}", path),
+            Err(e) => format!("ONNX export failed: {}", e),
+        });
+    }
+    
+    // Save the network of the finished run with the config it was trained with
+    pub fn save_model(&mut self, path: &str) {
+        let data = self.training_data.lock().unwrap();
+        let config = data.run_config.clone().unwrap_or_else(|| self.network_config.lock().unwrap().clone());
+        let result = match &data.trained_weights {
+            Some(weights) => Self::preprocessing(&config, &data)
+                .and_then(|preprocessing| save_model(path, weights, &config, &preprocessing)),
+            None => Err("No trained weights available".into()),
+        };
+        self.model_save_result = Some(match result {
+            Ok(()) => Ok(format!("Model saved to {}", path)),
+            Err(e) => Err(format!("Saving the model failed: {}", e)),
+        });
+    }
+    
+    // Save the weights of the running training with its config, for an observer's snapshot
+    pub fn save_snapshot(&self, path: &str, weights: &ModelWeights) -> Result<(), Box<dyn std::error::Error>> {
+        let data = self.training_data.lock().unwrap();
+        let config = data.run_config.clone().unwrap_or_else(|| self.network_config.lock().unwrap().clone());
+        save_model(path, weights, &config, &Self::preprocessing(&config, &data)?)
+    }
+    
+    pub fn export_weights_csv(&self, dir: &str) {
+        let delimiter = self.network_config.lock().unwrap().export_delimiter;
+        let mut data = self.training_data.lock().unwrap();
+        let result = match &data.trained_weights {
+            Some(weights) => weights.export_csv(dir, delimiter),
+            None => Err("No trained weights available".into()),
+        };
+        data.notice = Some(match result {
+            Ok(()) => format!("Weights exported to {}/", dir),
+            Err(e) => format!("Weight export failed: {}", e),
+        });
+    }
+    
+    // Train the selected dataset in f32 and in f64 without blocking the UI
+    pub fn compare_precisions(&self) {
+        let path = self.training_data.lock().unwrap().dataset_path.clone();
+        let config = self.network_config.lock().unwrap().clone();
+        let training_data = self.training_data.clone();
+        training_data.lock().unwrap().comparing_precision = true;
+        std::thread::spawn(move || {
+            let result = crate::precision::compare_precisions(&path, &config).map_err(|e| e.to_string());
+            let mut data = training_data.lock().unwrap();
+            data.precision_comparison = Some(result);
+            data.comparing_precision = false;
+        });
+    }
+    
+    pub fn export_history_csv(&self, path: &str) {
+        let delimiter = self.network_config.lock().unwrap().export_delimiter;
+        let mut data = self.training_data.lock().unwrap();
+        let result = export_history(path, &data, delimiter);
+        data.notice = Some(match result {
+            Ok(()) => format!("History exported to {}", path),
+            Err(e) => format!("History export failed: {}", e),
+        });
+    }
+    
+    pub fn export_predictions_csv(&self, path: &str, probabilities: &Array2<f64>, model: &LoadedModel) {
+        let delimiter = self.network_config.lock().unwrap().export_delimiter;
+        let result = export_predictions(path, probabilities, model.config.decision_rule(), &model.preprocessing.class_names, delimiter);
+        self.training_data.lock().unwrap().notice = Some(match result {
+            Ok(()) => format!("Predictions exported to {}", path),
+            Err(e) => format!("Predictions export failed: {}", e),
+        });
+    }
+    
+    pub fn export_misclassified_csv(&self, path: &str) {
+        let delimiter = self.network_config.lock().unwrap().export_delimiter;
+        let mut data = self.training_data.lock().unwrap();
+        let result = match &data.evaluated_samples {
+            Some(samples) => export_misclassified(path, samples, &data.feature_names, delimiter),
+            None => Err("No evaluated samples available".into()),
+        };
+        data.notice = Some(match result {
+            Ok(()) => format!("Misclassified samples exported to {}", path),
+            Err(e) => format!("Misclassified export failed: {}", e),
+        });
+    }
+    
+    // Write the loss figure of the shown run as SVG, next to the automatic PNG
+    pub fn export_loss_svg(&self) {
+        let mut data = self.training_data.lock().unwrap();
+        let job = crate::PlotJob {
+            losses: data.losses.clone(),
+            first_loss_epoch: data.first_epoch,
+            accuracies: data.measured_accuracies.clone(),
+            epochs: data.target_epochs.max(data.first_epoch + data.losses.len()),
+            task_type: data.run_config.as_ref().map_or(TaskType::Classification, |config| config.task_type),
+        };
+        let path = crate::PlotFormat::Svg.path();
+        let result = crate::plot_loss(&job, crate::PlotFormat::Svg);
+        data.notice = Some(match result {
+            Ok(()) => format!("Loss plot exported to {}", path),
+            Err(e) => format!("SVG export failed: {}", e),
+        });
+    }
+    
+    // Stopped before the first epoch finished, so there is nothing to report
+    pub fn training_canceled(&self) {
+        self.drain_progress();
+        self.stop_flag.store(false, Ordering::Relaxed);
+        let mut data = self.training_data.lock().unwrap();
+        data.completed = false;
+        data.training_in_progress = false;
+        data.set_paused(false);
+        data.started_at = None;
+        data.show_stop_confirm = false;
+    }
+    
+    pub fn training_failed(&self, message: &str) {
+        self.drain_progress();
+        self.stop_flag.store(false, Ordering::Relaxed);
+        let mut data = self.training_data.lock().unwrap();
+        data.completed = false;
+        data.training_in_progress = false;
+        data.set_paused(false);
+        data.started_at = None;
+        data.show_stop_confirm = false;
+        data.error_message = Some(message.to_string());
+    }
+    
+    #[allow(dead_code)]
+    pub fn stop_training(&self) {
+        let data = self.training_data.lock().unwrap();
+        if data.training_in_progress {
+            self.stop_flag.store(true, Ordering::Relaxed);
+            println!("Training stop requested");
+        }
+    }
+    
+    #[allow(dead_code)]
+    pub fn should_stop_training(&self) -> bool {
+        self.stop_flag.load(Ordering::Relaxed)
+    }
+
+    pub fn handle_train_click(&mut self, callback: impl Fn() + Send + Sync + 'static) {
+        self.train_callback = Some(Arc::new(callback));
+    }
+    
+    /// Register a callback for every logged epoch of future runs. It can stop
+    /// training, change the learning rate or snapshot the weights through the
+    /// `EpochControl` handle, so scripts and external controllers can steer runs.
+    pub fn add_epoch_observer(&mut self, observer: impl FnMut(&EpochEvent, &mut EpochControl) + Send + 'static) {
+        self.epoch_observers.push(Arc::new(Mutex::new(observer)));
+    }
+    
+    pub fn epoch_observers(&self) -> Vec<EpochObserver> {
+        self.epoch_observers.clone()
+    }
+    
+    pub fn get_network_config(&self) -> Arc<Mutex<NetworkConfig>> {
+        self.network_config.clone()
+    }
+    
+    pub fn get_training_data(&self) -> Arc<Mutex<TrainingData>> {
+        self.training_data.clone()
+    }
+    
+    // Calculate estimated accuracy based on loss when actual accuracy is not available
+    fn estimate_accuracy(&self, loss: f64) -> f64 {
+        // Simple rough estimate: higher loss usually means lower accuracy
+        // This is not scientific, just for visualization
+        let max_loss = 0.7; // Adjust based on your data
+        let accuracy = (1.0 - (loss / max_loss).min(1.0)) * 100.0;
+        accuracy.max(0.0)
+    }
+
+    // Scan the csv directory for available dataset files
+    pub fn scan_csv_directory(&self) -> Vec<String> {
+        let mut datasets = Vec::new();
+        
+        if let Ok(entries) = std::fs::read_dir("csv") {
+            for entry in entries.flatten() {
+                if let Ok(file_type) = entry.file_type() {
+                    if file_type.is_file() {
+                        if let Some(file_name) = entry.file_name().to_str() {
+                            if is_dataset_file(file_name) {
+                                datasets.push(file_name.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        
+        // If no CSV files found, provide default dataset
+        if datasets.is_empty() {
+            datasets.push("pollution_dataset5k.csv".to_string());
+        }
+        
+        datasets
+    }
+    
+    // Update the available datasets list
+    pub fn refresh_datasets(&self) {
+        let datasets = self.scan_csv_directory();
+        let delimiter = self.network_config.lock().unwrap().dataset_delimiter;
+        let mut data = self.training_data.lock().unwrap();
+        data.available_datasets = datasets;
+        
+        // If current dataset is not in the list, choose the first one
+        if !data.available_datasets.iter().any(|ds| data.dataset_path == format!("csv/{}", ds)) {
+            if let Some(first_dataset) = data.available_datasets.first() {
+                data.dataset_path = format!("csv/{}", first_dataset);
+            }
+        }
+        data.dataset_problem = check_csv_directory("csv")
+            .or_else(|| check_dataset_file(&data.dataset_path));
+        data.column_names = crate::dataset_column_names(&data.dataset_path, delimiter).ok();
+    }
+}
+
+// Extensions offered in the dataset list
+fn is_dataset_file(name: &str) -> bool {
+    name.ends_with(".csv") || name.ends_with(".tsv")
+}
+
+// The dataset list falls back to a default name when csv/ has no dataset files,
+// so say so when the directory does hold files, just with other extensions
+fn check_csv_directory(dir: &str) -> Option<String> {
+    let files: Vec<String> = std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+        .collect();
+    
+    if files.is_empty() || files.iter().any(|name| is_dataset_file(name)) {
+        return None;
+    }
+    Some(format!(
+        "Found {} file{} in {}/ but none are .csv or .tsv datasets",
+        files.len(),
+        if files.len() == 1 { "" } else { "s" },
+        dir
+    ))
+}
+
+// Cheap sanity check of a dataset file: it must exist and have a header plus
+// at least one data row. Full parsing happens when training starts.
+fn check_dataset_file(path: &str) -> Option<String> {
+    let file_path = std::path::Path::new(path);
+    if !file_path.is_file() {
+        return Some(format!("Dataset file not found: {}", path));
+    }
+    
+    let file = match std::fs::File::open(file_path) {
+        Ok(file) => file,
+        Err(e) => return Some(format!("Cannot open {}: {}", path, e)),
+    };
+    let non_empty_lines = std::io::BufRead::lines(std::io::BufReader::new(file))
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .take(2)
+        .count();
+    if non_empty_lines < 2 {
+        return Some(format!("{} has no data rows", path));
+    }
+    
+    None
+}
+
+impl eframe::App for NeuralNetworkApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Set dark mode
+        ctx.set_visuals(egui::Visuals::dark());
+        
+        let network_config = self.network_config.clone();
+        let training_data = self.training_data.clone();
+        let mut display_settings = self.display_settings.clone();
+        self.drain_progress();
+        
+        // Ctrl+Z / Ctrl+Y step through recent configurations, unless a text
+        // field has focus (it has its own undo) or training is running
+        {
+            let training = training_data.lock().unwrap().training_in_progress;
+            let mut config = network_config.lock().unwrap();
+            self.config_history.observe(&config);
+            if !training && !ctx.wants_keyboard_input() {
+                let (undo, redo) = ctx.input(|i| (
+                    i.modifiers.command && i.key_pressed(egui::Key::Z) && !i.modifiers.shift,
+                    i.modifiers.command && (i.key_pressed(egui::Key::Y) || (i.modifiers.shift && i.key_pressed(egui::Key::Z))),
+                ));
+                let restored = if undo {
+                    self.config_history.undo(&config)
+                } else if redo {
+                    self.config_history.redo()
+                } else {
+                    None
+                };
+                if let Some(restored) = restored {
+                    *config = restored;
+                }
+            }
+        }
+        
+        // Escape asks to stop a running training like the Stop button does,
+        // unless the confirmation is already open or a text field has focus
+        if !ctx.wants_keyboard_input() && ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            let mut data = training_data.lock().unwrap();
+            if data.training_in_progress && !data.show_stop_confirm {
+                data.show_stop_confirm = true;
+            }
+        }
+        
+        // Refresh the hidden layers text when the config was changed from elsewhere
+        {
+            let config = network_config.lock().unwrap();
+            if config.hidden_layers != self.hidden_layers_synced {
+                self.hidden_layers_text = format_hidden_layers(&config.hidden_layers);
+                self.hidden_layers_synced = config.hidden_layers.clone();
+            }
+            if config.epochs != self.epochs_synced {
+                self.epochs_text = config.epochs.to_string();
+                self.epochs_synced = config.epochs;
+            }
+            if config.ignored_columns != self.ignored_columns_synced {
+                self.ignored_columns_text = config.ignored_columns.iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                self.ignored_columns_synced = config.ignored_columns.clone();
+            }
+            if config.extra_label_columns != self.extra_labels_synced {
+                self.extra_labels_text = config.extra_label_columns.iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                self.extra_labels_synced = config.extra_label_columns.clone();
+            }
+        }
+        let mut hidden_layers_text = self.hidden_layers_text.clone();
+        let mut epochs_text = self.epochs_text.clone();
+        let mut ignored_columns_text = self.ignored_columns_text.clone();
+        let mut extra_labels_text = self.extra_labels_text.clone();
+        
+        let precision_comparison = training_data.lock().unwrap().precision_comparison.clone();
+        let comparing_precision = training_data.lock().unwrap().comparing_precision;
+        let mut precision_click = false;
+        
+        let mut train_click = false;
+        let mut stop_click = false;
+        let mut pause_click = false;
+        let mut confirm_stop = false;
+        let mut cancel_stop = false;
+        let mut new_dataset_path = None;
+        let mut export_weights_click = false;
+        let mut export_json_click = false;
+        let mut export_onnx_click = false;
+        let mut save_model_click = false;
+        let mut export_history_click = false;
+        let mut export_misclassified_click = false;
+        let mut export_svg_click = false;
+        let mut load_model_click = false;
+        let mut run_inference_click = false;
+        let mut export_predictions_click = false;
+        let mut inference = std::mem::take(&mut self.inference);
+        let model_save_result = self.model_save_result.clone();
+        let mut continue_click = false;
+        let mut analyze_click = false;
+        let mut report_click = false;
+        let mut generate_click = false;
+        let mut synthetic_spec = self.synthetic_spec.clone();
+        let mut prediction_input = self.prediction_input.clone();
+        let mut predict_click = false;
+        let prediction_result = self.prediction_result.clone();
+        // Raw feature count of the network the what-if panel uses, see `predict_what_if`
+        let what_if_inputs = {
+            let data = training_data.lock().unwrap();
+            match (&data.trained_weights, &inference.model) {
+                (Some(weights), _) => Some(weights.n_features() + data.dropped_features.len()),
+                (None, Some(Ok(model))) => Some(model.weights.n_features() + model.preprocessing.dropped_features.len()),
+                _ => None,
+            }
+        };
+        let mut what_if_values = self.what_if_values.clone();
+        what_if_values.resize(what_if_inputs.unwrap_or(0), 0.0);
+        let mut what_if_click = false;
+        let what_if_result = self.what_if_result.clone();
+        let mut delimiter_changed = false;
+        let mut reset_config_click = false;
+        let decision_rule = network_config.lock().unwrap().decision_rule();
+        training_data.lock().unwrap().apply_decision_rule(decision_rule);
+        let feature_correlations = training_data.lock().unwrap().feature_correlations.clone();
+        let dataset_problem = training_data.lock().unwrap().dataset_problem.clone();
+        let column_names = training_data.lock().unwrap().column_names.clone();
+        let feature_names = training_data.lock().unwrap().feature_names.clone();
+        let load_summary = training_data.lock().unwrap().load_summary;
+        let accuracy_interval = training_data.lock().unwrap().accuracy_interval;
+        let regression_metrics = training_data.lock().unwrap().regression_metrics;
+        let has_evaluated_samples = training_data.lock().unwrap().evaluated_samples.is_some();
+        // Labels follow the shown run, or the configured task before the first run
+        let task_type = training_data.lock().unwrap().run_config.as_ref()
+            .map_or_else(|| network_config.lock().unwrap().task_type, |config| config.task_type);
+        let metric_label = task_type.metric_label();
+        let is_regression = task_type == TaskType::Regression;
+        // Top of the accuracy axis, accuracy is a percentage while R² is at most 1
+        let y_max = if is_regression { 1.0 } else { 100.0 };
+        let baseline_accuracy = training_data.lock().unwrap().baseline_accuracy;
+        let best_epoch = training_data.lock().unwrap().best_epoch;
+        let monitor_label = training_data.lock().unwrap().run_config.as_ref()
+            .map_or("loss".to_string(), |config| config.monitor.label().to_lowercase());
+        let first_epoch = training_data.lock().unwrap().first_epoch;
+        let dropped_features = training_data.lock().unwrap().dropped_features.clone();
+        let head_metrics = training_data.lock().unwrap().head_metrics.clone();
+        let class_names = training_data.lock().unwrap().class_names.clone();
+        let config_changed = {
+            let run_config = training_data.lock().unwrap().run_config.clone();
+            run_config.is_some_and(|run| !run.same_run_as(&network_config.lock().unwrap()))
+        };
+        let n_features = {
+            let data = training_data.lock().unwrap();
+            (!data.feature_correlations.is_empty())
+                .then(|| data.feature_correlations.len() - data.dropped_features.len())
+        };
+        let confusion_matrix = training_data.lock().unwrap().confusion_matrix;
+        let class_accuracy = training_data.lock().unwrap().class_accuracy;
+        let load_progress = training_data.lock().unwrap().load_progress;
+        let calibration = training_data.lock().unwrap().calibration.clone();
+        let roc = training_data.lock().unwrap().roc.clone();
+        let weight_histograms = training_data.lock().unwrap().weight_histograms.clone();
+        let feature_importance = training_data.lock().unwrap().feature_importance.clone();
+        let completed_runs = self.completed_runs.lock().unwrap().clone();
+        let mut clear_comparison_click = false;
+        let mut save_comparisons_click = false;
+        let mut load_comparisons_click = false;
+        let mut run_notes: Vec<String> = completed_runs.iter().map(|run| run.note.clone()).collect();
+        
+        // Samples of the selected confusion matrix cell, capped for the table
+        let drilldown = display_settings.selected_cell.and_then(|cell| {
+            let data = training_data.lock().unwrap();
+            let samples = data.evaluated_samples.as_ref()?;
+            let indices = samples.indices_in(cell);
+            let rows: Vec<(usize, f64, f64, Vec<f64>)> = indices.iter()
+                .take(MAX_DRILLDOWN_ROWS)
+                .map(|&i| (i, samples.labels[i], samples.probabilities[i], samples.features.row(i).to_vec()))
+                .collect();
+            Some((cell, indices.len(), rows))
+        });
+        
+        // The big per-epoch series are only cloned while the display is live
+        let series = |data: &TrainingData| [
+            data.losses.clone(),
+            data.accuracies.clone(),
+            data.val_losses.clone(),
+            data.val_accuracies.clone(),
+            data.learning_rates.clone(),
+        ];
+        let [losses, accuracies, val_losses, val_accuracies, learning_rates] = if display_settings.pause_display {
+            self.frozen_series.get_or_insert_with(|| series(&training_data.lock().unwrap())).clone()
+        } else {
+            self.frozen_series = None;
+            series(&training_data.lock().unwrap())
+        };
+        
+        // Clone all the data we need upfront to avoid borrow issues
+        let data_for_ui = {
+            let data = training_data.lock().unwrap();
+            (
+                data.epoch,
+                data.loss,
+                data.accuracy,
+                data.training_in_progress,
+                data.completed,
+                data.dataset_path.clone(),
+                data.available_datasets.clone(),
+                data.show_stop_confirm,
+                data.error_message.clone(),
+                data.notice.clone(),
+                data.trained_weights.is_some(),
+                data.toast.clone(),
+                data.plot_status.clone(),
+                data.target_epochs,
+                data.elapsed_and_eta(),
+                data.paused
+            )
+        };
+        
+        let (
+            epoch,
+            loss,
+            accuracy,
+            training_in_progress,
+            completed,
+            dataset_path,
+            available_datasets,
+            show_stop_confirm,
+            error_message,
+            notice,
+            has_trained_weights,
+            toast,
+            plot_status,
+            target_epochs,
+            elapsed_and_eta,
+            paused
+        ) = data_for_ui;
+        
+        // A loaded run history replaces the live series while it's replayed
+        let mut replay = self.replay.take();
+        if let Some(replay) = replay.as_mut() {
+            replay.advance(ctx.input(|i| i.stable_dt) as f64);
+        }
+        let (losses, accuracies, val_losses, val_accuracies, learning_rates, first_epoch) = match &replay {
+            Some(replay) => (
+                replay.history.losses[..replay.shown()].to_vec(),
+                replay.history.accuracies[..replay.shown()].to_vec(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                replay.history.first_epoch,
+            ),
+            None => (losses, accuracies, val_losses, val_accuracies, learning_rates, first_epoch),
+        };
+        let mut load_history_click = false;
+        let mut close_replay = false;
+        let mut ragged_choice = None;
+        let mut cancel_ragged = false;
+        
+        // Confirmation dialog
+        if show_stop_confirm {
+            egui::Window::new("Confirm Stop Training")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.add_space(10.0);
+                        ui.heading("Are you sure you want to stop training?");
+                        ui.add_space(5.0);
+                        ui.label("Current progress will be saved but training will end.");
+                        ui.add_space(20.0);
+                        
+                        ui.horizontal(|ui| {
+                            if ui.button(egui::RichText::new("Yes, Stop Training")
+                                .size(16.0)
+                                .color(egui::Color32::WHITE))
+                                .clicked() {
+                                confirm_stop = true;
+                            }
+                            
+                            ui.add_space(20.0);
+                            
+                            if ui.button(egui::RichText::new("Continue Training")
+                                .size(16.0)
+                                .color(egui::Color32::WHITE))
+                                .clicked() {
+                                cancel_stop = true;
+                            }
+                        });
+                        ui.add_space(10.0);
+                    });
+                });
+        }
+        
+        // Ragged rows found when starting a run, drop or pad them instead of failing
+        if let Some((n_columns, lines)) = &self.ragged_prompt {
+            egui::Window::new("Ragged Rows")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "{} rows do not have the header's {} fields:", lines.len(), n_columns
+                    ));
+                    egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                        let list = lines.iter().map(|l| format!("line {}", l)).collect::<Vec<_>>().join(", ");
+                        ui.colored_label(egui::Color32::from_rgb(230, 200, 50), list);
+                    });
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Drop Rows").clicked() {
+                            ragged_choice = Some(RaggedRows::Drop);
+                        }
+                        if ui.button("Pad With Zeros").clicked() {
+                            ragged_choice = Some(RaggedRows::Pad);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancel_ragged = true;
+                        }
+                    });
+                });
+        }
+        
+        // Non-fatal warnings (e.g. the loss plot could not be written)
+        if let Some(message) = &toast {
+            let mut dismiss = false;
+            egui::Window::new("Warning")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -40.0])
+                .show(ctx, |ui| {
+                    ui.colored_label(egui::Color32::from_rgb(230, 200, 50), message);
+                    if ui.button("Dismiss").clicked() {
+                        dismiss = true;
+                    }
+                });
+            if dismiss {
+                training_data.lock().unwrap().toast = None;
+            }
+        }
+        
+        if confirm_stop {
+            let mut data = training_data.lock().unwrap();
+            self.stop_flag.store(true, Ordering::Relaxed);
+            // A paused run has to wake up to stop
+            data.set_paused(false);
+            self.pause_changed.notify_all();
+            data.show_stop_confirm = false;
+            println!("Training stop confirmed");
+        }
+        
+        if cancel_stop {
+            let mut data = training_data.lock().unwrap();
+            data.show_stop_confirm = false;
+        }
+        
+        // Continued runs train past the configured epoch count
+        let total_epochs = if target_epochs > 0 {
+            target_epochs
+        } else {
+            network_config.lock().unwrap().epochs
+        };
+        
+        // "Developed by" text at the bottom
+        egui::TopBottomPanel::bottom("footer").show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(
+                    egui::RichText::new("Developed by Group 7")
+                    .size(14.0)
+                    .color(egui::Color32::from_rgb(150, 150, 150))
+                );
+            });
+        });
+        
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // Scrollable so the growing number of panels fits the window
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                // Title & Header
+                ui.vertical_centered(|ui| {
+                    ui.add_space(10.0);
+                    ui.heading(egui::RichText::new("Neural Network Pollution Analysis").size(24.0));
+                    ui.add_space(15.0);
+                });
+            
+                // Progress information if training
+                if training_in_progress || completed {
+                    ui.vertical_centered(|ui| {
+                        // A run shows the dataset load until its first epoch
+                        let loading = load_progress.filter(|_| training_in_progress);
+                        let progress_text = if let Some(progress) = loading {
+                            progress.describe()
+                        } else if training_in_progress {
+                            format!("{}Epoch: {}/{} | Loss: {:.4} | {}: ", 
+                                    if paused { "Paused | " } else { "" }, epoch, total_epochs, loss, metric_label)
+                        } else {
+                            format!("Training completed | Final {}: ", metric_label)
+                        };
+                    
+                        // Accuracy number is color-coded by the configured thresholds
+                        let mut job = egui::text::LayoutJob::default();
+                        job.append(&progress_text, 0.0, egui::TextFormat {
+                            color: egui::Color32::from_rgb(50, 150, 200),
+                            ..Default::default()
+                        });
+                        // Final accuracy with its bootstrap confidence interval, e.g. "87.2% (±1.4)"
+                        // R² has no color thresholds and is followed by the final MSE
+                        let accuracy_text = match (accuracy_interval, regression_metrics) {
+                            _ if is_regression && training_in_progress => format!("{:.4}", accuracy),
+                            (_, Some(metrics)) if is_regression => format!("{:.4} | MSE: {:.6}", metrics.r2, metrics.mse),
+                            (Some(ci), _) if !training_in_progress => format!("{:.1}% (±{:.1})", accuracy, ci.half_width()),
+                            _ => format!("{:.2}%", accuracy),
+                        };
+                        if loading.is_none() {
+                            job.append(&accuracy_text, 0.0, egui::TextFormat {
+                                color: if is_regression { egui::Color32::from_rgb(220, 220, 220) } else { display_settings.accuracy_color(accuracy) },
+                                ..Default::default()
+                            });
+                        }
+                        if let (Some((elapsed, eta)), true) = (elapsed_and_eta, training_in_progress && loading.is_none()) {
+                            let eta_text = eta.map_or("-".to_string(), |eta| format!("{:.0}s", eta));
+                            job.append(&format!(" | Elapsed: {:.0}s, ETA: {}", elapsed, eta_text), 0.0, egui::TextFormat {
+                                color: egui::Color32::from_rgb(50, 150, 200),
+                                ..Default::default()
+                            });
+                        }
+                        ui.label(job);
+                        
+                        if let (Some(baseline), false) = (baseline_accuracy, training_in_progress) {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(180, 180, 200),
+                                format!("Logistic regression baseline: {:.2}% (network {:+.2} points)", baseline, accuracy - baseline)
+                            );
+                        }
+                        
+                        if let (Some((best, value)), false) = (best_epoch, training_in_progress) {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(180, 180, 200),
+                                format!("Best model: epoch {} (validation {} {:.4})", best + 1, monitor_label, value)
+                            );
+                        }
+                        
+                        // Multi-head runs list every head, the primary one drives the numbers above
+                        if head_metrics.len() > 1 && !training_in_progress {
+                            for (name, matrix) in &head_metrics {
+                                ui.colored_label(
+                                    egui::Color32::from_rgb(180, 180, 200),
+                                    format!("Head ({}): accuracy {:.2}%, F1 {:.3}", name, matrix.accuracy(), matrix.f1())
+                                );
+                            }
+                        }
+                
+                        // Show dataset information
+                        let mut dataset_name = dataset_path.clone();
+                        if let Some(file_name) = dataset_name.strip_prefix("csv/") {
+                            dataset_name = file_name.to_string();
+                        }
+                        let dataset_text = match &load_summary {
+                            Some(summary) => format!("Dataset: {} | {}", dataset_name, summary.describe()),
+                            None => format!("Dataset: {}", dataset_name),
+                        };
+                        ui.colored_label(egui::Color32::from_rgb(180, 180, 200), dataset_text);
+                        
+                        if let Some(status) = &plot_status {
+                            ui.colored_label(egui::Color32::from_rgb(180, 180, 200), status);
+                        }
+                    
+                        // Progress bar
+                        if let Some(progress) = loading {
+                            ui.add(egui::ProgressBar::new(progress.overall() as f32)
+                                .text(format!("{} rows", progress.rows))
+                                .animate(true));
+                        } else if training_in_progress {
+                            let progress = (epoch as f64 / total_epochs.max(1) as f64) as f32;
+                            ui.add(egui::ProgressBar::new(progress)
+                                .text(format!("{}/{}", epoch, total_epochs))
+                                .animate(true));
+                        }
+                    });
+                
+                    ui.add_space(10.0);
+                }
+            
+                // Error from the last training attempt
+                if let Some(message) = &error_message {
+                    ui.vertical_centered(|ui| {
+                        ui.colored_label(egui::Color32::from_rgb(230, 60, 60), format!("Training failed: {}", message));
+                    });
+                    ui.add_space(10.0);
+                }
+            
+                // Separator
+                ui.separator();
+            
+                // Network Configuration section with a header
+                ui.vertical(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.heading(egui::RichText::new("Network Configuration").size(18.0));
+                    });
+                    ui.add_space(5.0);
+                
+                    // Only enable configuration when not training
+                    let is_enabled = !training_in_progress;
+                    ui.set_enabled(is_enabled);
+                    
+                    if ui.small_button("Reset Config")
+                        .on_hover_text("Restore the default settings, the dataset and its column choices stay (Ctrl+Z undoes)")
+                        .clicked()
+                    {
+                        reset_config_click = true;
+                    }
+                
+                    // 3 Configuration controls side-by-side
+                    ui.horizontal(|ui| {
+                        // Equal spacing for each control
+                        let available_width = ui.available_width();
+                        let item_width = (available_width - 60.0) / 4.0;
+                    
+                        ui.vertical(|ui| {
+                            ui.set_width(item_width);
+                            ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Epochs:");
+                            let mut config = network_config.lock().unwrap();
+                            ui.add_sized(
+                                [item_width, 30.0],
+                                egui::DragValue::new(&mut config.epochs)
+                                    .speed(10)
+                                    .clamp_range(MIN_EPOCHS..=MAX_EPOCHS)
+                                    .prefix("Epochs: ")
+                            );
+                            // Large counts are easier typed than dragged
+                            let typed = ui.add_sized(
+                                [item_width, 24.0],
+                                egui::TextEdit::singleline(&mut epochs_text)
+                                    .hint_text("or type e.g. 20000")
+                            ).changed();
+                            match epochs_text.trim().parse::<usize>() {
+                                Ok(epochs) if (MIN_EPOCHS..=MAX_EPOCHS).contains(&epochs) => {
+                                    if typed {
+                                        config.epochs = epochs;
+                                    }
+                                }
+                                _ => {
+                                    ui.colored_label(
+                                        egui::Color32::from_rgb(230, 60, 60),
+                                        format!("Enter a whole number from {} to {}", MIN_EPOCHS, MAX_EPOCHS)
+                                    );
+                                }
+                            }
+                        });
+                    
+                        ui.vertical(|ui| {
+                            ui.set_width(item_width);
+                            ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Hidden Layers:");
+                            let mut config = network_config.lock().unwrap();
+                            ui.add_sized(
+                                [item_width, 30.0],
+                                egui::TextEdit::singleline(&mut hidden_layers_text)
+                                    .hint_text("e.g. 32,16,8")
+                            );
+                            
+                            // Echo the parsed architecture (or the parse error) back to the user
+                            match parse_hidden_layers(&hidden_layers_text) {
+                                Ok(layers) => {
+                                    ui.colored_label(
+                                        egui::Color32::from_rgb(180, 180, 200),
+                                        format!("input → {} → 1", layers.iter()
+                                            .map(|size| size.to_string())
+                                            .collect::<Vec<_>>()
+                                            .join(" → "))
+                                    );
+                                    // The feature count is known once the dataset was analyzed or trained on
+                                    match n_features {
+                                        Some(n_features) => {
+                                            let n_heads = config.load_options().extra_label_columns.len() + 1;
+                                            let count = parameter_count(n_features, &layers, n_heads, config.skip_connection, config.batch_norm);
+                                            ui.colored_label(
+                                                egui::Color32::from_rgb(180, 180, 200),
+                                                format!("{} trainable parameters", count)
+                                            );
+                                        }
+                                        None => {
+                                            ui.colored_label(
+                                                egui::Color32::from_rgb(180, 180, 200),
+                                                "Analyze the dataset to count parameters"
+                                            );
+                                        }
+                                    }
+                                    config.hidden_layers = layers;
+                                }
+                                Err(e) => {
+                                    ui.colored_label(egui::Color32::from_rgb(230, 60, 60), e);
+                                }
+                            }
+                        });
+                    
+                        ui.vertical(|ui| {
+                            ui.set_width(item_width);
+                            ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Learning Rate:");
+                            let mut config = network_config.lock().unwrap();
+                            ui.add_sized(
+                                [item_width, 30.0],
+                                egui::DragValue::new(&mut config.learning_rate)
+                                    .speed(0.001)
+                                    .clamp_range(0.0001..=0.1)
+                                    .fixed_decimals(4)
+                                    .prefix("Rate: ")
+                            );
+                        });
+                    
+                        ui.vertical(|ui| {
+                            ui.set_width(item_width);
+                            ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Activation:");
+                            let mut config = network_config.lock().unwrap();
+                            egui::ComboBox::from_id_source("activation")
+                                .selected_text(config.activation.label())
+                                .width(item_width)
+                                .show_ui(ui, |ui| {
+                                    for activation in Activation::ALL {
+                                        ui.selectable_value(&mut config.activation, activation, activation.label());
+                                    }
+                                });
+                        });
+                    });
+                
+                    // Task type (regression drops the output sigmoid and trains on MSE) and weight init
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        let mut config = network_config.lock().unwrap();
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Task:");
+                        egui::ComboBox::from_id_source("task_type")
+                            .selected_text(config.task_type.label())
+                            .show_ui(ui, |ui| {
+                                for task_type in TaskType::ALL {
+                                    ui.selectable_value(&mut config.task_type, task_type, task_type.label());
+                                }
+                            });
+                        if config.task_type == TaskType::Regression {
+                            ui.colored_label(egui::Color32::from_rgb(180, 180, 180), "Label column is used as a continuous target");
+                        }
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Weight Init:");
+                        egui::ComboBox::from_id_source("init_scheme")
+                            .selected_text(config.init_scheme.label())
+                            .show_ui(ui, |ui| {
+                                for scheme in InitScheme::ALL {
+                                    ui.selectable_value(&mut config.init_scheme, scheme, scheme.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("He suits ReLU layers, Xavier tanh and sigmoid layers");
+                    });
+                
+                    // Learning rate schedule with its decay parameters
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        let mut config = network_config.lock().unwrap();
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "LR Schedule:");
+                        egui::ComboBox::from_id_source("lr_schedule")
+                            .selected_text(config.lr_schedule.label())
+                            .show_ui(ui, |ui| {
+                                for schedule in LrSchedule::ALL {
+                                    let selected = config.lr_schedule.label() == schedule.label();
+                                    if ui.selectable_label(selected, schedule.label()).clicked() && !selected {
+                                        config.lr_schedule = schedule;
+                                    }
+                                }
+                            });
+                        match &mut config.lr_schedule {
+                            LrSchedule::Constant => {}
+                            LrSchedule::StepDecay { step_size, gamma } => {
+                                ui.add(
+                                    egui::DragValue::new(step_size)
+                                        .speed(10)
+                                        .clamp_range(1..=1_000_000)
+                                        .prefix("Every: ")
+                                        .suffix(" epochs")
+                                );
+                                ui.add(
+                                    egui::DragValue::new(gamma)
+                                        .speed(0.01)
+                                        .clamp_range(0.0..=1.0)
+                                        .fixed_decimals(3)
+                                        .prefix("γ: ")
+                                ).on_hover_text("Factor applied to the rate at every step");
+                            }
+                            LrSchedule::ExponentialDecay { gamma } => {
+                                ui.add(
+                                    egui::DragValue::new(gamma)
+                                        .speed(0.001)
+                                        .clamp_range(0.0..=1.0)
+                                        .fixed_decimals(4)
+                                        .prefix("γ: ")
+                                ).on_hover_text("Factor applied to the rate every epoch");
+                            }
+                        }
+                    });
+                
+                    // Optimizer, SGD exposes its momentum and Adam its moment decay rates
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        let mut config = network_config.lock().unwrap();
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Optimizer:");
+                        egui::ComboBox::from_id_source("optimizer")
+                            .selected_text(config.optimizer.label())
+                            .show_ui(ui, |ui| {
+                                for optimizer in [Optimizer::Sgd, Optimizer::ADAM_DEFAULT] {
+                                    let selected = config.optimizer.label() == optimizer.label();
+                                    if ui.selectable_label(selected, optimizer.label()).clicked() && !selected {
+                                        config.optimizer = optimizer;
+                                    }
+                                }
+                            });
+                        if config.optimizer == Optimizer::Sgd {
+                            ui.add(
+                                egui::DragValue::new(&mut config.momentum)
+                                    .speed(0.01)
+                                    .clamp_range(0.0..=0.99)
+                                    .fixed_decimals(2)
+                                    .prefix("Momentum: ")
+                            ).on_hover_text("Fraction of the previous step added to each update (0 = plain SGD)");
+                        }
+                        if let Optimizer::Adam { beta1, beta2, .. } = &mut config.optimizer {
+                            ui.add(
+                                egui::DragValue::new(beta1)
+                                    .speed(0.001)
+                                    .clamp_range(0.0..=0.9999)
+                                    .fixed_decimals(4)
+                                    .prefix("β1: ")
+                            ).on_hover_text("Decay rate of the gradient average (momentum)");
+                            ui.add(
+                                egui::DragValue::new(beta2)
+                                    .speed(0.0001)
+                                    .clamp_range(0.0..=0.99999)
+                                    .fixed_decimals(5)
+                                    .prefix("β2: ")
+                            ).on_hover_text("Decay rate of the squared gradient average");
+                        }
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Batch Size:");
+                        ui.add(
+                            egui::DragValue::new(&mut config.batch_size)
+                                .speed(1)
+                                .clamp_range(0..=1_000_000)
+                        ).on_hover_text("Rows per weight update (0 = full batch)");
+                        ui.checkbox(&mut config.shuffle, "Shuffle")
+                            .on_hover_text("Reorder the rows every epoch, reproducible with a seed");
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "L2:");
+                        ui.add(
+                            egui::DragValue::new(&mut config.l2_lambda)
+                                .speed(0.0001)
+                                .clamp_range(0.0..=1.0)
+                                .fixed_decimals(4)
+                        ).on_hover_text("Weight decay strength, penalizes large weights (0 = off)");
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Dropout:");
+                        ui.add(
+                            egui::DragValue::new(&mut config.dropout_rate)
+                                .speed(0.01)
+                                .clamp_range(0.0..=0.9)
+                                .fixed_decimals(2)
+                        ).on_hover_text("Share of hidden units dropped at random in every training step (0 = off)");
+                        let mut clip_enabled = config.grad_clip.is_some();
+                        let mut clip_value = config.grad_clip.unwrap_or(1.0);
+                        ui.checkbox(&mut clip_enabled, "Grad Clip")
+                            .on_hover_text("Scale every gradient matrix down to at most this L2 norm, keeps high learning rates from diverging");
+                        ui.add_enabled(
+                            clip_enabled,
+                            egui::DragValue::new(&mut clip_value)
+                                .speed(0.1)
+                                .clamp_range(0.001..=1000.0)
+                                .fixed_decimals(3)
+                        );
+                        config.grad_clip = clip_enabled.then_some(clip_value);
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Validation Split:");
+                        ui.add(
+                            egui::DragValue::new(&mut config.validation_split)
+                                .speed(0.01)
+                                .clamp_range(0.0..=0.9)
+                                .fixed_decimals(2)
+                        ).on_hover_text("Share of the rows held out to track validation loss and accuracy (0 = none)");
+                        ui.add_enabled(
+                            config.validation_split > 0.0,
+                            egui::Checkbox::new(&mut config.restore_best_weights, "Restore Best")
+                        ).on_hover_text("Finish with the weights of the epoch with the best validation value of the monitored metric");
+                        let can_stratify = config.validation_split > 0.0 && config.task_type == TaskType::Classification;
+                        ui.add_enabled(can_stratify, egui::Checkbox::new(&mut config.stratified, "Stratified"))
+                            .on_hover_text("Hold out the same share of every class, so the validation rows keep the class ratio");
+                    });
+                    
+                    // Early stopping controls
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        let mut config = network_config.lock().unwrap();
+                        ui.checkbox(&mut config.early_stopping, "Early Stopping");
+                        ui.add_enabled(
+                            config.early_stopping,
+                            egui::DragValue::new(&mut config.patience)
+                                .speed(1)
+                                .clamp_range(1..=1000)
+                                .prefix("Patience: ")
+                        );
+                        ui.add_enabled(
+                            config.early_stopping,
+                            egui::DragValue::new(&mut config.min_epochs)
+                                .speed(10)
+                                .clamp_range(0..=5000)
+                                .prefix("Min Epochs: ")
+                        );
+                        
+                        // The monitor also picks the restored best weights
+                        let uses_monitor = config.early_stopping || (config.restore_best_weights && config.validation_split > 0.0);
+                        ui.add_enabled_ui(uses_monitor, |ui| {
+                            let mut monitor = config.monitor;
+                            egui::ComboBox::from_id_source("monitor_metric")
+                                .selected_text(format!("Monitor: {}", monitor.label()))
+                                .show_ui(ui, |ui| {
+                                    for metric in Metric::ALL {
+                                        ui.selectable_value(&mut monitor, metric, metric.label());
+                                    }
+                                });
+                            // Picking a metric resets the direction to its natural one
+                            if monitor != config.monitor {
+                                config.monitor = monitor;
+                                config.mode = monitor.default_mode();
+                            }
+                            ui.selectable_value(&mut config.mode, MonitorMode::Min, "Min");
+                            ui.selectable_value(&mut config.mode, MonitorMode::Max, "Max");
+                        });
+                        
+                        ui.add_space(20.0);
+                        
+                        // Logit clamp against sigmoid saturation
+                        let mut clamp_enabled = config.logit_clamp.is_some();
+                        let mut clamp_value = config.logit_clamp.unwrap_or(30.0);
+                        ui.checkbox(&mut clamp_enabled, "Clamp Logits");
+                        ui.add_enabled(
+                            clamp_enabled,
+                            egui::DragValue::new(&mut clamp_value)
+                                .speed(0.5)
+                                .clamp_range(1.0..=100.0)
+                                .prefix("±")
+                        );
+                        config.logit_clamp = clamp_enabled.then_some(clamp_value);
+                        
+                        ui.add_space(20.0);
+                        
+                        // Wall-clock budget for the run
+                        let mut limit_enabled = config.max_train_seconds.is_some();
+                        let mut limit_value = config.max_train_seconds.unwrap_or(60.0);
+                        ui.checkbox(&mut limit_enabled, "Time Limit");
+                        ui.add_enabled(
+                            limit_enabled,
+                            egui::DragValue::new(&mut limit_value)
+                                .speed(1.0)
+                                .clamp_range(1.0..=86400.0)
+                                .suffix(" s")
+                        );
+                        config.max_train_seconds = limit_enabled.then_some(limit_value);
+                    });
+                
+                    // Dataset Selection
+                    ui.add_space(10.0);
+                    ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Select Dataset:");
+                
+                    // Extract just the filename from the path
+                    let mut current_dataset_name = dataset_path.clone();
+                    if let Some(file_name) = current_dataset_name.strip_prefix("csv/") {
+                        current_dataset_name = file_name.to_string();
+                    }
+                
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source("dataset_selector")
+                            .selected_text(current_dataset_name.clone())
+                            .width(200.0)
+                            .show_ui(ui, |ui| {
+                                for dataset in &available_datasets {
+                                    if ui.selectable_label(current_dataset_name == *dataset, dataset.clone()).clicked() {
+                                        new_dataset_path = Some(format!("csv/{}", dataset));
+                                    }
+                                }
+                            });
+                    
+                        ui.add_space(10.0);
+                    
+                        if ui.button("Refresh Datasets").clicked() {
+                            // We'll handle this outside the UI closure
+                            new_dataset_path = Some("REFRESH".to_string());
+                        }
+                    });
+                    
+                    // Generated datasets for demos and reproducible test runs
+                    ui.collapsing("Generate Synthetic Dataset", |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label("Samples:");
+                            ui.add(egui::DragValue::new(&mut synthetic_spec.samples).clamp_range(10..=1_000_000).speed(10));
+                            ui.label("Features:");
+                            ui.add(egui::DragValue::new(&mut synthetic_spec.features).clamp_range(1..=1000));
+                            ui.label("Noise:");
+                            ui.add(egui::DragValue::new(&mut synthetic_spec.noise).clamp_range(0.0..=0.5).speed(0.01))
+                                .on_hover_text("Share of labels flipped at random");
+                            ui.label("Class 1 Share:");
+                            ui.add(egui::DragValue::new(&mut synthetic_spec.positive_ratio).clamp_range(0.0..=1.0).speed(0.01));
+                        });
+                        ui.horizontal(|ui| {
+                            egui::ComboBox::from_id_source("synthetic_separability")
+                                .selected_text(synthetic_spec.separability.label())
+                                .show_ui(ui, |ui| {
+                                    for separability in Separability::ALL {
+                                        ui.selectable_value(&mut synthetic_spec.separability, separability, separability.label());
+                                    }
+                                });
+                            ui.label("Seed:");
+                            ui.add(egui::DragValue::new(&mut synthetic_spec.seed));
+                            if ui.add_enabled(!training_in_progress, egui::Button::new("Generate")).clicked() {
+                                generate_click = true;
+                            }
+                        });
+                    });
+                
+                    // Optional subset of rows, e.g. to re-run on specific failing samples
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Row Index File:");
+                        let mut config = network_config.lock().unwrap();
+                        ui.add(
+                            egui::TextEdit::singleline(&mut config.row_index_file)
+                                .hint_text("optional, e.g. result/rows.txt")
+                                .desired_width(250.0)
+                        );
+                    });
+                    
+                    // Optional per-feature multipliers to emphasize or mute features
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Feature Weights File:");
+                        let mut config = network_config.lock().unwrap();
+                        ui.add(
+                            egui::TextEdit::singleline(&mut config.feature_weights_file)
+                                .hint_text("optional, e.g. csv/weights.txt")
+                                .desired_width(250.0)
+                        ).on_hover_text("One multiplier per feature column (after ignored columns), comma or line separated");
+                    });
+                    
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Ragged Rows:");
+                        let mut config = network_config.lock().unwrap();
+                        egui::ComboBox::from_id_source("ragged_rows")
+                            .selected_text(config.ragged_rows.label())
+                            .show_ui(ui, |ui| {
+                                for policy in RaggedRows::ALL {
+                                    ui.selectable_value(&mut config.ragged_rows, policy, policy.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("Rows with more or fewer fields than the header: stop and ask, skip them, or pad missing values with 0");
+                        
+                        ui.add_space(20.0);
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Missing Values:");
+                        egui::ComboBox::from_id_source("missing_values")
+                            .selected_text(config.missing_values.label())
+                            .show_ui(ui, |ui| {
+                                for policy in MissingValues::ALL {
+                                    ui.selectable_value(&mut config.missing_values, policy, policy.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("Empty or non-numeric cells: skip the row, fill features with the column mean, or stop with an error");
+                    });
+                    
+                    // Run options
+                    ui.horizontal_wrapped(|ui| {
+                        let mut config = network_config.lock().unwrap();
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Options:");
+                        ui.checkbox(&mut config.deterministic, "Deterministic")
+                            .on_hover_text("Fixed-seed, single-threaded training so runs reproduce exactly (slower)");
+                        
+                        let mut seed_enabled = config.seed.is_some();
+                        let mut seed_value = config.seed.unwrap_or(42);
+                        ui.checkbox(&mut seed_enabled, "Seed")
+                            .on_hover_text("Repeat the same weight init, shuffles and validation split every run");
+                        ui.add_enabled(seed_enabled, egui::DragValue::new(&mut seed_value));
+                        config.seed = seed_enabled.then_some(seed_value);
+                        
+                        let deterministic = config.deterministic;
+                        ui.add_enabled(!deterministic, egui::Checkbox::new(&mut config.parallel, "Parallel"))
+                            .on_hover_text("Spread the matrix products and activations over all cores, with the same results as the serial path")
+                            .on_disabled_hover_text("Deterministic mode always trains on one thread");
+                        
+                        ui.checkbox(&mut config.background_plotting, "Background Plotting")
+                            .on_hover_text("Write the loss figure on a separate thread so completion shows immediately");
+                        ui.add(
+                            egui::DragValue::new(&mut config.log_interval)
+                                .speed(1)
+                                .clamp_range(1..=100_000)
+                                .prefix("Log Every: ")
+                                .suffix(" epochs")
+                        ).on_hover_text("How often the accuracy is measured and the weight histograms are sampled");
+                        ui.checkbox(&mut config.progress_log, "Progress Log")
+                            .on_hover_text("Append one JSON line per logged epoch to result/progress.jsonl, after a line with the run's start time and config");
+                        ui.add(
+                            egui::DragValue::new(&mut config.bootstrap_samples)
+                                .speed(10)
+                                .clamp_range(0..=10000)
+                                .prefix("Bootstrap: ")
+                        ).on_hover_text("Resamples for the final accuracy confidence interval (0 = off)");
+                        ui.checkbox(&mut config.compare_baseline, "Compare Baseline")
+                            .on_hover_text("Also train logistic regression (no hidden layer) on the same data");
+                        ui.checkbox(&mut config.prior_bias_init, "Prior Bias Init")
+                            .on_hover_text("Start the output bias at the label's base rate instead of zero (faster on imbalanced data)");
+                        ui.checkbox(&mut config.drop_constant_features, "Drop Constant Features")
+                            .on_hover_text("Remove feature columns with a single repeated value before training");
+                        ui.checkbox(&mut config.normalize_features, "Normalize Features")
+                            .on_hover_text("Z-score each feature with the training rows' mean and standard deviation");
+                        ui.checkbox(&mut config.skip_connection, "Skip Connection")
+                            .on_hover_text("Residual path from the input to the hidden output (identity, or a learned projection when sizes differ)");
+                        ui.checkbox(&mut config.batch_norm, "Batch Norm")
+                            .on_hover_text("Normalize each hidden layer's pre-activation over the batch, with a learned scale and shift");
+                        
+                        let mut divergence_enabled = config.divergence_factor.is_some();
+                        let mut divergence_value = config.divergence_factor.unwrap_or(10.0);
+                        ui.checkbox(&mut divergence_enabled, "Abort On Divergence")
+                            .on_hover_text("Stop when the loss is NaN or has grown to this many times its best value");
+                        ui.add_enabled(
+                            divergence_enabled,
+                            egui::DragValue::new(&mut divergence_value)
+                                .speed(0.5)
+                                .clamp_range(1.5..=1000.0)
+                                .suffix("x")
+                        );
+                        config.divergence_factor = divergence_enabled.then_some(divergence_value);
+                        
+                        let mut batch_enabled = config.eval_batch_size.is_some();
+                        let mut batch_value = config.eval_batch_size.unwrap_or(10000);
+                        ui.checkbox(&mut batch_enabled, "Eval Batch")
+                            .on_hover_text("Compute final metrics in chunks of rows to bound memory (off = whole set)");
+                        ui.add_enabled(
+                            batch_enabled,
+                            egui::DragValue::new(&mut batch_value)
+                                .speed(100)
+                                .clamp_range(1..=1_000_000)
+                                .suffix(" rows")
+                        );
+                        config.eval_batch_size = batch_enabled.then_some(batch_value);
+                        
+                        let mut limit_enabled = config.live_history_limit.is_some();
+                        let mut limit_value = config.live_history_limit.unwrap_or(5000);
+                        ui.checkbox(&mut limit_enabled, "Live History Limit")
+                            .on_hover_text("Keep only the last N points in the live charts; every epoch is streamed to result/progress.jsonl");
+                        ui.add_enabled(
+                            limit_enabled,
+                            egui::DragValue::new(&mut limit_value)
+                                .speed(100)
+                                .clamp_range(100..=1_000_000)
+                                .suffix(" points")
+                        );
+                        config.live_history_limit = limit_enabled.then_some(limit_value);
+                        
+                        ui.add(
+                            egui::DragValue::new(&mut config.metrics_start_epoch)
+                                .speed(10)
+                                .clamp_range(0..=100_000)
+                                .prefix("Metrics From Epoch: ")
+                        ).on_hover_text("Leave the first epochs out of the charts (training still starts at epoch 0)");
+                        
+                        ui.add(
+                            egui::DragValue::new(&mut config.threshold)
+                                .speed(0.01)
+                                .clamp_range(0.0..=1.0)
+                                .fixed_decimals(2)
+                                .prefix("Threshold: ")
+                        ).on_hover_text("Probability from which a prediction counts as class 1");
+                        egui::ComboBox::from_id_source("tie_break")
+                            .selected_text(config.tie_break.label())
+                            .show_ui(ui, |ui| {
+                                for tie_break in TieBreak::ALL {
+                                    ui.selectable_value(&mut config.tie_break, tie_break, tie_break.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("Class predicted when the probability equals the threshold");
+                    });
+                
+                    // Columns excluded from the features (IDs, timestamps, ...)
+                    ui.horizontal(|ui| {
+                        let mut config = network_config.lock().unwrap();
+                        ui.checkbox(&mut config.ignore_first_column, "Ignore first column (ID)");
+                        ui.add_space(20.0);
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Ignore Columns:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut ignored_columns_text)
+                                .hint_text("e.g. 0,3")
+                                .desired_width(120.0)
+                        );
+                        match parse_index_list(&ignored_columns_text) {
+                            Ok(columns) => config.ignored_columns = columns,
+                            Err(e) => {
+                                ui.colored_label(egui::Color32::from_rgb(230, 60, 60), e);
+                            }
+                        }
+                    });
+                    
+                    // Field separator, for files the detection gets wrong
+                    ui.horizontal(|ui| {
+                        let mut config = network_config.lock().unwrap();
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Dataset Delimiter:");
+                        let selected = DATASET_DELIMITERS.iter()
+                            .find(|(delimiter, _)| Some(*delimiter) == config.dataset_delimiter)
+                            .map_or("Auto", |(_, name)| name);
+                        let before = config.dataset_delimiter;
+                        egui::ComboBox::from_id_source("dataset_delimiter")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut config.dataset_delimiter, None, "Auto")
+                                    .on_hover_text("Semicolon, tab or pipe when the first line has any (the most frequent), otherwise comma");
+                                for (delimiter, name) in DATASET_DELIMITERS {
+                                    ui.selectable_value(&mut config.dataset_delimiter, Some(delimiter), name);
+                                }
+                            });
+                        delimiter_changed = config.dataset_delimiter != before;
+                    });
+                    
+                    // Target column, the last one unless picked here
+                    ui.horizontal(|ui| {
+                        let mut config = network_config.lock().unwrap();
+                        let dataset_columns = column_names.as_ref().map(|names| names.len());
+                        let last = dataset_columns.unwrap_or(1).saturating_sub(1);
+                        let mut custom = config.label_column.is_some();
+                        let mut column = config.label_column.unwrap_or(last);
+                        ui.checkbox(&mut custom, "Label Column:")
+                            .on_hover_text("Unchecked, the last column is the label");
+                        let mut drag = egui::DragValue::new(&mut column);
+                        if dataset_columns.is_some() {
+                            drag = drag.clamp_range(0..=last);
+                        }
+                        ui.add_enabled(custom, drag);
+                        config.label_column = custom.then_some(column);
+                        match dataset_columns {
+                            Some(n_columns) if column >= n_columns => {
+                                ui.colored_label(
+                                    egui::Color32::from_rgb(230, 60, 60),
+                                    format!("The dataset has only {} columns", n_columns)
+                                );
+                            }
+                            Some(n_columns) => {
+                                let names = column_names.as_deref().unwrap_or_default();
+                                let list = names.iter().enumerate()
+                                    .map(|(i, name)| format!("{}: {}", i, name))
+                                    .collect::<Vec<_>>().join("\n");
+                                ui.label(format!("\"{}\" of {} columns (0-{})", names[column], n_columns, last))
+                                    .on_hover_text(list);
+                            }
+                            None => {}
+                        }
+                    });
+                    
+                    // Extra binary targets, each trained as its own head on the shared hidden layer
+                    ui.horizontal(|ui| {
+                        let mut config = network_config.lock().unwrap();
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Extra Label Columns:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut extra_labels_text)
+                                .hint_text("e.g. 4 (one output head each)")
+                                .desired_width(180.0)
+                        ).on_hover_text("The label column is always the primary head that drives accuracy, charts and reports");
+                        match parse_index_list(&extra_labels_text) {
+                            Ok(columns) => config.extra_label_columns = columns,
+                            Err(e) => {
+                                ui.colored_label(egui::Color32::from_rgb(230, 60, 60), e);
+                            }
+                        }
+                    });
+                    
+                    // Initial weights from CSV files (w1.csv, b1.csv, ... one pair per layer)
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Import Weights From:");
+                        let mut config = network_config.lock().unwrap();
+                        ui.add(
+                            egui::TextEdit::singleline(&mut config.weights_import_dir)
+                                .hint_text("optional directory, e.g. result/weights")
+                                .desired_width(250.0)
+                        );
+                        
+                        ui.add_space(20.0);
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Export Separator:");
+                        let selected = EXPORT_DELIMITERS.iter()
+                            .find(|(delimiter, _)| *delimiter == config.export_delimiter)
+                            .map_or("Comma", |(_, name)| name);
+                        egui::ComboBox::from_id_source("export_delimiter")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                for (delimiter, name) in EXPORT_DELIMITERS {
+                                    ui.selectable_value(&mut config.export_delimiter, delimiter, name);
+                                }
+                            });
+                    });
+                
+                    // Accuracy color thresholds
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Accuracy Colors:");
+                        ui.add(
+                            egui::DragValue::new(&mut display_settings.accuracy_low_threshold)
+                                .speed(0.5)
+                                .clamp_range(0.0..=100.0)
+                                .prefix("Red below: ")
+                                .suffix("%")
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut display_settings.accuracy_high_threshold)
+                                .speed(0.5)
+                                .clamp_range(0.0..=100.0)
+                                .prefix("Green above: ")
+                                .suffix("%")
+                        );
+                    });
+                });
+            
+                ui.add_space(10.0);
+                ui.separator();
+            
+                // Training Charts section
+                ui.vertical(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.heading(egui::RichText::new("Training Charts").size(18.0));
+                        if config_changed && !training_in_progress && replay.is_none() {
+                            ui.colored_label(egui::Color32::from_rgb(180, 180, 200), "config changed since last run")
+                                .on_hover_text("The results below were produced by a different configuration");
+                        }
+                    });
+                    ui.add_space(5.0);
+                    
+                    // Replay a saved run without training it again
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(!training_in_progress, egui::Button::new("Load Run History"))
+                            .on_hover_text(format!("Replay the charts of the run saved in {}", HISTORY_PATH))
+                            .clicked()
+                        {
+                            load_history_click = true;
+                        }
+                        
+                        if ui.add_enabled(!completed_runs.is_empty(), egui::Button::new("Clear Comparison"))
+                            .on_hover_text("Remove the finished runs overlaid on the loss chart")
+                            .clicked()
+                        {
+                            clear_comparison_click = true;
+                        }
+                        if ui.add_enabled(!completed_runs.is_empty(), egui::Button::new("Save Comparisons"))
+                            .on_hover_text(format!("Write the compared runs with their configs and notes to {}, loaded again on launch", COMPARISONS_PATH))
+                            .clicked()
+                        {
+                            save_comparisons_click = true;
+                        }
+                        if ui.add_enabled(!training_in_progress, egui::Button::new("Load Comparisons"))
+                            .on_hover_text(format!("Replace the compared runs with the ones saved in {}", COMPARISONS_PATH))
+                            .clicked()
+                        {
+                            load_comparisons_click = true;
+                        }
+                        {
+                            let mut config = network_config.lock().unwrap();
+                            ui.add(
+                                egui::DragValue::new(&mut config.max_compared_runs)
+                                    .speed(1)
+                                    .clamp_range(0..=COMPARISON_COLORS.len() * 2)
+                                    .prefix("Compare: ")
+                            ).on_hover_text("Number of finished runs overlaid on the loss chart (0 = none)");
+                        }
+                        
+                        ui.checkbox(&mut display_settings.pause_display, "Pause Display Updates")
+                            .on_hover_text("Freeze the charts and stop continuous repainting to save CPU; training keeps running");
+                        
+                        if let Some(replay) = replay.as_mut() {
+                            let total = replay.len();
+                            if ui.button(if replay.playing { "Pause" } else { "Play" }).clicked() {
+                                // Play from the start again once the end was reached
+                                if replay.shown() == total {
+                                    replay.position = 0.0;
+                                }
+                                replay.playing = !replay.playing;
+                            }
+                            ui.add(egui::Slider::new(&mut replay.position, 0.0..=total as f64).show_value(false));
+                            ui.label(format!(
+                                "Replaying {}: epoch {}/{}",
+                                replay.history.dataset, replay.shown(), total
+                            ));
+                            if ui.button("Close Replay").clicked() {
+                                close_replay = true;
+                            }
+                        }
+                    });
+                    
+                    // Overlaid runs with their notes, saved along with them
+                    if !completed_runs.is_empty() {
+                        egui::CollapsingHeader::new(format!("Compared Runs ({})", completed_runs.len()))
+                            .id_source("compared_runs")
+                            .show(ui, |ui| {
+                                for ((run, note), color) in completed_runs.iter().zip(run_notes.iter_mut()).zip(COMPARISON_COLORS.iter().cycle()) {
+                                    ui.horizontal(|ui| {
+                                        ui.colored_label(*color, &run.label);
+                                        ui.add(
+                                            egui::TextEdit::singleline(note)
+                                                .hint_text("note")
+                                                .desired_width(300.0)
+                                        );
+                                    });
+                                }
+                            });
+                    }
+                    ui.add_space(5.0);
+                
+                    // Two charts side by side
+                    ui.horizontal(|ui| {
+                        // Loss chart
+                        let plot = Plot::new("loss_plot")
+                            .height(200.0)
+                            .width(ui.available_width() * 0.48)
+                            .view_aspect(2.0)
+                            .allow_zoom(false)
+                            .allow_drag(false)
+                            .show_axes([true, true])
+                            // BCE is never below 0, same y-range start as the saved PNG
+                            .include_y(0.0)
+                            .legend(egui_plot::Legend::default());
+                    
+                        plot.show(ui, |plot_ui| {
+                            // Earlier runs first, so the live curve is drawn on top
+                            for (run, color) in completed_runs.iter().zip(COMPARISON_COLORS.iter().cycle()) {
+                                let points: PlotPoints = run.losses.iter()
+                                    .enumerate()
+                                    .map(|(i, &loss)| [(run.first_epoch + i) as f64, loss])
+                                    .collect();
+                                plot_ui.line(Line::new(points).name(&run.label).width(1.0).color(*color));
+                            }
+                            if !losses.is_empty() {
+                                let points: PlotPoints = losses.iter()
+                                    .enumerate()
+                                    .map(|(i, &loss)| [(first_epoch + i) as f64, loss])
+                                    .collect();
+                            
+                                plot_ui.line(Line::new(points).name("Loss").width(2.0).color(egui::Color32::RED));
+                            }
+                            if !val_losses.is_empty() {
+                                let points: PlotPoints = val_losses.iter()
+                                    .enumerate()
+                                    .map(|(i, &loss)| [(first_epoch + i) as f64, loss])
+                                    .collect();
+                            
+                                plot_ui.line(Line::new(points).name("Validation Loss").width(2.0).color(egui::Color32::from_rgb(255, 165, 0)));
+                            }
+                        
+                            plot_ui.text(egui_plot::Text::new(
+                                egui_plot::PlotPoint::new(first_epoch as f64 + losses.len().max(1) as f64 * 0.5, 0.01), 
+                                "Loss over Epochs"
+                            ).color(egui::Color32::WHITE));
+                        });
+                    
+                        ui.add_space(10.0);
+                    
+                        // Accuracy chart, R² for regression runs
+                        let plot = Plot::new("accuracy_plot")
+                            .height(200.0)
+                            .width(ui.available_width())
+                            .view_aspect(2.0)
+                            .allow_zoom(false)
+                            .allow_drag(false)
+                            .show_axes([true, true])
+                            .include_y(0.0)
+                            .include_y(y_max)
+                            .legend(egui_plot::Legend::default());
+                    
+                        plot.show(ui, |plot_ui| {
+                            for (run, color) in completed_runs.iter().zip(COMPARISON_COLORS.iter().cycle()) {
+                                let points: PlotPoints = run.accuracies.iter()
+                                    .enumerate()
+                                    .map(|(i, &acc)| [(run.first_epoch + i) as f64, acc])
+                                    .collect();
+                                plot_ui.line(Line::new(points).name(&run.label).width(1.0).color(*color));
+                            }
+                            if !accuracies.is_empty() {
+                                let points: PlotPoints = accuracies.iter()
+                                    .enumerate()
+                                    .map(|(i, &acc)| [(first_epoch + i) as f64, acc])
+                                    .collect();
+                            
+                                plot_ui.line(Line::new(points).name(metric_label).width(2.0).color(egui::Color32::BLUE));
+                            }
+                            if !val_accuracies.is_empty() {
+                                let points: PlotPoints = val_accuracies.iter()
+                                    .enumerate()
+                                    .map(|(i, &acc)| [(first_epoch + i) as f64, acc])
+                                    .collect();
+                            
+                                plot_ui.line(Line::new(points).name(format!("Validation {}", metric_label)).width(2.0).color(egui::Color32::from_rgb(0, 200, 200)));
+                            }
+                        
+                            plot_ui.text(egui_plot::Text::new(
+                                egui_plot::PlotPoint::new(first_epoch as f64 + accuracies.len().max(1) as f64 * 0.5, 0.8 * y_max), 
+                                if is_regression { "R² over Epochs" } else { "Accuracy (%) over Epochs" }
+                            ).color(egui::Color32::WHITE));
+                        });
+                    });
+                    
+                    // Combined chart with toggleable series sharing the epoch axis
+                    ui.add_space(10.0);
+                    let mut metric_series: Vec<(&str, egui::Color32, Vec<[f64; 2]>)> = vec![
+                        ("Loss", egui::Color32::RED,
+                         losses.iter().enumerate().map(|(i, &v)| [(first_epoch + i) as f64, v]).collect()),
+                        (if is_regression { "R²" } else { "Accuracy (/100)" }, egui::Color32::BLUE,
+                         accuracies.iter().enumerate().map(|(i, &v)| [(first_epoch + i) as f64, v / y_max]).collect()),
+                    ];
+                    if !val_losses.is_empty() {
+                        metric_series.push(("Validation Loss", egui::Color32::from_rgb(255, 165, 0),
+                            val_losses.iter().enumerate().map(|(i, &v)| [(first_epoch + i) as f64, v]).collect()));
+                        metric_series.push((if is_regression { "Validation R²" } else { "Validation Accuracy (/100)" }, egui::Color32::from_rgb(0, 200, 200),
+                            val_accuracies.iter().enumerate().map(|(i, &v)| [(first_epoch + i) as f64, v / y_max]).collect()));
+                    }
+                    
+                    ui.horizontal(|ui| {
+                        ui.label("Show:");
+                        for (name, color, _) in &metric_series {
+                            let visible = display_settings.visible_metrics
+                                .entry(name.to_string())
+                                .or_insert(true);
+                            ui.checkbox(visible, egui::RichText::new(*name).color(*color));
+                        }
+                    });
+                    
+                    Plot::new("metrics_plot")
+                        .height(200.0)
+                        .allow_zoom(false)
+                        .allow_drag(false)
+                        .show_axes([true, true])
+                        .include_y(0.0)
+                        .legend(egui_plot::Legend::default())
+                        .show(ui, |plot_ui| {
+                            for (name, color, points) in metric_series {
+                                if display_settings.visible_metrics.get(name).copied().unwrap_or(true)
+                                    && !points.is_empty()
+                                {
+                                    plot_ui.line(Line::new(PlotPoints::from(points)).name(name).width(2.0).color(color));
+                                }
+                            }
+                        });
+                    
+                    // Effective learning rate, to check the schedule is applied
+                    ui.checkbox(&mut display_settings.show_learning_rate, "Show Learning Rate");
+                    if display_settings.show_learning_rate {
+                        Plot::new("learning_rate_plot")
+                            .height(150.0)
+                            .allow_zoom(false)
+                            .allow_drag(false)
+                            .show_axes([true, true])
+                            .include_y(0.0)
+                            .legend(egui_plot::Legend::default())
+                            .show(ui, |plot_ui| {
+                                if !learning_rates.is_empty() {
+                                    let points: PlotPoints = learning_rates.iter()
+                                        .enumerate()
+                                        .map(|(i, &rate)| [(first_epoch + i) as f64, rate])
+                                        .collect();
+                                    plot_ui.line(Line::new(points).name("Learning Rate").width(2.0).color(egui::Color32::from_rgb(180, 120, 255)));
+                                }
+                            });
+                    }
+                    
+                    // Weight values of one layer, sampled at the log interval, to spot
+                    // dead units (a spike at 0) or weights blowing up (a widening range)
+                    ui.checkbox(&mut display_settings.show_weight_histogram, "Show Weight Histogram");
+                    if display_settings.show_weight_histogram {
+                        match &weight_histograms {
+                            Some((sampled_epoch, histograms)) => {
+                                let last_layer = histograms.len().saturating_sub(1);
+                                display_settings.histogram_layer = display_settings.histogram_layer.min(last_layer);
+                                ui.horizontal(|ui| {
+                                    ui.add(
+                                        egui::DragValue::new(&mut display_settings.histogram_layer)
+                                            .clamp_range(0..=last_layer)
+                                            .custom_formatter(|layer, _| format!("w{}", layer as usize + 1))
+                                            .prefix("Layer: ")
+                                    ).on_hover_text("The last layer is the output layer");
+                                    ui.label(format!("sampled at epoch {}", sampled_epoch + 1));
+                                });
+                                let histogram = &histograms[display_settings.histogram_layer];
+                                let bars: Vec<egui_plot::Bar> = histogram.counts.iter()
+                                    .enumerate()
+                                    .map(|(bin, &count)| {
+                                        egui_plot::Bar::new(histogram.bin_center(bin), count as f64)
+                                            .width(histogram.bin_width)
+                                            .fill(egui::Color32::from_rgb(180, 120, 255))
+                                    })
+                                    .collect();
+                                Plot::new("weight_histogram_plot")
+                                    .height(150.0)
+                                    .allow_zoom(false)
+                                    .allow_drag(false)
+                                    .show_axes([true, true])
+                                    .include_y(0.0)
+                                    .show(ui, |plot_ui| {
+                                        plot_ui.bar_chart(egui_plot::BarChart::new(bars).name("Weights"));
+                                    });
+                            }
+                            None => {
+                                let interval = network_config.lock().unwrap().log_interval.max(1);
+                                ui.label(format!("The histogram is sampled every {} epochs during training.", interval));
+                            }
+                        }
+                    }
+                });
+                
+                ui.add_space(10.0);
+                ui.separator();
+                
+                // Confusion matrix of the last run, click a cell to list its samples
+                if let Some(matrix) = confusion_matrix {
+                    ui.vertical(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.heading(egui::RichText::new("Confusion Matrix").size(18.0));
+                        });
+                        ui.add_space(5.0);
+                        
+                        egui::Grid::new("confusion_grid")
+                            .spacing([20.0, 8.0])
+                            .show(ui, |ui| {
+                                ui.label("");
+                                ui.label(format!("predicted {}", class_names.negative));
+                                ui.label(format!("predicted {}", class_names.positive));
+                                ui.end_row();
+                                
+                                let rows = [
+                                    (&class_names.negative, [ConfusionCell::TrueNegative, ConfusionCell::FalsePositive]),
+                                    (&class_names.positive, [ConfusionCell::FalseNegative, ConfusionCell::TruePositive]),
+                                ];
+                                for (actual, cells) in rows {
+                                    ui.label(format!("actual {}", actual));
+                                    for cell in cells {
+                                        let selected = display_settings.selected_cell == Some(cell);
+                                        let text = format!("{}: {}", cell.label(), matrix.count(cell));
+                                        if ui.selectable_label(selected, text).clicked() {
+                                            display_settings.selected_cell = if selected { None } else { Some(cell) };
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        ui.label(format!(
+                            "Precision: {:.3} | Recall: {:.3} | F1: {:.3}",
+                            matrix.precision(), matrix.recall(), matrix.f1()
+                        ));
+                        // Recall of each class, the minority class is easy to miss in the overall accuracy
+                        if let Some(class_accuracy) = class_accuracy {
+                            let format_class = |class: usize| class_accuracy.accuracy(class).map_or("-".to_string(), |accuracy| format!("{:.1}%", accuracy));
+                            ui.label(format!(
+                                "Accuracy on actual {}: {} | on actual {}: {}",
+                                class_names.negative, format_class(0), class_names.positive, format_class(1)
+                            ));
+                        }
+                        
+                        if let Some((cell, total, rows)) = &drilldown {
+                            ui.add_space(5.0);
+                            let n_features = rows.first().map_or(0, |(_, _, _, features)| features.len());
+                            let shown_features = n_features.min(MAX_DRILLDOWN_FEATURES);
+                            ui.label(format!(
+                                "{}: showing {} of {} samples, {} of {} features (row = position in the training data)",
+                                cell.label(), rows.len(), total, shown_features, n_features
+                            ));
+                            
+                            egui::ScrollArea::both()
+                                .id_source("drilldown_table")
+                                .max_height(200.0)
+                                .show(ui, |ui| {
+                                    egui::Grid::new("drilldown_grid")
+                                        .striped(true)
+                                        .show(ui, |ui| {
+                                            ui.strong("row");
+                                            ui.strong("label");
+                                            ui.strong(format!("p({})", class_names.positive));
+                                            // The table holds the features kept after dropping constant ones
+                                            let kept = (0..).filter(|feature| !dropped_features.contains(feature));
+                                            for feature in kept.take(shown_features) {
+                                                ui.strong(feature_label(&feature_names, feature));
+                                            }
+                                            ui.end_row();
+                                            
+                                            for (row, label, probability, features) in rows {
+                                                ui.label(row.to_string());
+                                                ui.label(class_names.name(*label));
+                                                ui.label(format!("{:.3}", probability));
+                                                for value in features.iter().take(shown_features) {
+                                                    ui.label(format!("{:.3}", value));
+                                                }
+                                                ui.end_row();
+                                            }
+                                        });
+                                });
+                        }
+                    });
+                    
+                    ui.add_space(10.0);
+                    ui.separator();
+                }
+                
+                // Reliability diagram: are the predicted probabilities trustworthy?
+                if let Some(calibration) = &calibration {
+                    ui.vertical(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.heading(egui::RichText::new("Calibration").size(18.0));
+                        });
+                        ui.add_space(5.0);
+                        ui.label(format!("Expected Calibration Error: {:.4}", calibration.expected_error))
+                            .on_hover_text("Sample-weighted mean gap between predicted probability and observed positive fraction per bin");
+                        
+                        let curve: Vec<[f64; 2]> = calibration.bins.iter()
+                            .map(|bin| [bin.mean_predicted, bin.observed_positive])
+                            .collect();
+                        Plot::new("calibration_plot")
+                            .height(220.0)
+                            .view_aspect(1.0)
+                            .allow_zoom(false)
+                            .allow_drag(false)
+                            .include_x(0.0)
+                            .include_x(1.0)
+                            .include_y(0.0)
+                            .include_y(1.0)
+                            .legend(egui_plot::Legend::default())
+                            .show(ui, |plot_ui| {
+                                plot_ui.line(
+                                    Line::new(PlotPoints::from(vec![[0.0, 0.0], [1.0, 1.0]]))
+                                        .name("Perfect calibration")
+                                        .color(egui::Color32::GRAY)
+                                        .style(egui_plot::LineStyle::dashed_loose())
+                                );
+                                plot_ui.line(
+                                    Line::new(PlotPoints::from(curve.clone()))
+                                        .name("Observed positive fraction")
+                                        .color(egui::Color32::from_rgb(100, 150, 255))
+                                        .width(2.0)
+                                );
+                                plot_ui.points(
+                                    egui_plot::Points::new(PlotPoints::from(curve))
+                                        .radius(3.0)
+                                        .color(egui::Color32::from_rgb(100, 150, 255))
+                                );
+                            });
+                    });
+                    
+                    ui.add_space(10.0);
+                    ui.separator();
+                }
+                
+                // ROC curve: how well the probabilities rank positives above negatives
+                if let Some(roc) = &roc {
+                    ui.vertical(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.heading(egui::RichText::new("ROC Curve").size(18.0));
+                        });
+                        ui.add_space(5.0);
+                        ui.label(format!("AUC: {:.4}", roc.auc))
+                            .on_hover_text("Probability that a random positive sample gets a higher probability than a random negative one");
+                        
+                        Plot::new("roc_plot")
+                            .height(220.0)
+                            .view_aspect(1.0)
+                            .allow_zoom(false)
+                            .allow_drag(false)
+                            .include_x(0.0)
+                            .include_x(1.0)
+                            .include_y(0.0)
+                            .include_y(1.0)
+                            .x_axis_label("False positive rate")
+                            .y_axis_label("True positive rate")
+                            .legend(egui_plot::Legend::default())
+                            .show(ui, |plot_ui| {
+                                plot_ui.line(
+                                    Line::new(PlotPoints::from(vec![[0.0, 0.0], [1.0, 1.0]]))
+                                        .name("Chance")
+                                        .color(egui::Color32::GRAY)
+                                        .style(egui_plot::LineStyle::dashed_loose())
+                                );
+                                plot_ui.line(
+                                    Line::new(PlotPoints::from(roc.points.clone()))
+                                        .name("ROC")
+                                        .color(egui::Color32::from_rgb(255, 140, 60))
+                                        .width(2.0)
+                                );
+                            });
+                    });
+                    
+                    ui.add_space(10.0);
+                    ui.separator();
+                }
+                
+                // Saliency: how strongly the output reacts to each input feature
+                if !feature_importance.is_empty() {
+                    ui.vertical(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.heading(egui::RichText::new("Feature Importance").size(18.0));
+                        });
+                        ui.add_space(5.0);
+                        ui.label("Mean absolute gradient of the output with respect to each (normalized) input feature, strongest first")
+                            .on_hover_text("A local sensitivity measure over the training rows, not a causal effect");
+                        
+                        let charted = &feature_importance[..feature_importance.len().min(MAX_CHARTED_FEATURES)];
+                        let n_features = charted.len();
+                        let bars: Vec<egui_plot::Bar> = charted.iter()
+                            .enumerate()
+                            .map(|(rank, &(feature, score))| {
+                                egui_plot::Bar::new((n_features - rank) as f64, score)
+                                    .name(feature_label(&feature_names, feature))
+                                    .fill(egui::Color32::from_rgb(100, 150, 255))
+                            })
+                            .collect();
+                        Plot::new("importance_plot")
+                            .height(180.0)
+                            .allow_zoom(false)
+                            .allow_drag(false)
+                            .include_x(0.0)
+                            .show(ui, |plot_ui| {
+                                plot_ui.bar_chart(egui_plot::BarChart::new(bars).horizontal().width(0.7));
+                            });
+                    });
+                    
+                    ui.add_space(10.0);
+                    ui.separator();
+                }
+                
+                // Quick prediction on one pasted row, with the retained weights
+                if has_trained_weights {
+                    ui.vertical(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.heading(egui::RichText::new("Quick Prediction").size(18.0));
+                        });
+                        ui.add_space(5.0);
+                        ui.horizontal(|ui| {
+                            ui.label("Feature values:");
+                            let response = ui.add(
+                                egui::TextEdit::singleline(&mut prediction_input)
+                                    .hint_text("e.g. 12.5,0.3,41")
+                                    .desired_width(300.0)
+                            ).on_hover_text("One row as in the dataset, without ignored and label columns");
+                            let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                            if ui.button("Predict").clicked() || enter {
+                                predict_click = true;
+                            }
+                        });
+                        match &prediction_result {
+                            Some(Ok(probabilities)) => {
+                                for (h, &p) in probabilities.iter().enumerate() {
+                                    let predicted = if decision_rule.predict(p) { 1.0 } else { 0.0 };
+                                    let class = if h == 0 { class_names.name(predicted).to_string() } else { predicted.to_string() };
+                                    let prefix = if probabilities.len() > 1 { format!("Head {}: ", h) } else { String::new() };
+                                    ui.label(format!("{}p(1) = {:.4} → {}", prefix, p, class));
+                                }
+                            }
+                            Some(Err(e)) => {
+                                ui.colored_label(egui::Color32::from_rgb(230, 60, 60), e);
+                            }
+                            None => {}
+                        }
+                    });
+                    
+                    ui.add_space(10.0);
+                    ui.separator();
+                }
+                
+                // One input per feature, for trying out values by hand
+                if let Some(n_inputs) = what_if_inputs {
+                    ui.vertical(|ui| {
+                        ui.vertical_centered(|ui| {
+                            ui.heading(egui::RichText::new("What-If Prediction").size(18.0));
+                        });
+                        ui.add_space(5.0);
+                        ui.label(if has_trained_weights {
+                            "Feature values in dataset units, predicted with the last run's network"
+                        } else {
+                            "Feature values in dataset units, predicted with the loaded model"
+                        });
+                        // Header names only when they belong to this network's inputs
+                        let names: &[String] = if feature_names.len() == n_inputs { &feature_names } else { &[] };
+                        egui::ScrollArea::vertical()
+                            .id_source("what_if_inputs")
+                            .max_height(200.0)
+                            .show(ui, |ui| {
+                                egui::Grid::new("what_if_grid")
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for (feature, value) in what_if_values.iter_mut().enumerate() {
+                                            ui.label(feature_label(names, feature));
+                                            ui.add(egui::DragValue::new(value).speed(0.1));
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+                        ui.horizontal(|ui| {
+                            if ui.button("Predict").clicked() {
+                                what_if_click = true;
+                            }
+                            match &what_if_result {
+                                Some(Ok(outcome)) => {
+                                    ui.label(outcome);
+                                }
+                                Some(Err(e)) => {
+                                    ui.colored_label(egui::Color32::from_rgb(230, 60, 60), e);
+                                }
+                                None => {}
+                            }
+                        });
+                    });
+                    
+                    ui.add_space(10.0);
+                    ui.separator();
+                }
+                
+                // Inference with a model saved earlier, on any dataset in csv/
+                ui.vertical(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.heading(egui::RichText::new("Saved Model Inference").size(18.0));
+                    });
+                    ui.add_space(5.0);
+                    ui.horizontal(|ui| {
+                        ui.label("Model File:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut inference.model_path)
+                                .hint_text(MODEL_PATH)
+                                .desired_width(250.0)
+                        );
+                        if ui.button("Load Model").clicked() {
+                            load_model_click = true;
+                        }
+                    });
+                    match &inference.model {
+                        Some(Ok(model)) => {
+                            ui.label(format!(
+                                "{} inputs, hidden [{}], {}, {} parameters",
+                                model.weights.n_features(),
+                                format_hidden_layers(&model.config.hidden_layers),
+                                model.config.activation.label(),
+                                model.weights.parameter_count()
+                            ));
+                            ui.horizontal(|ui| {
+                                ui.label("Dataset:");
+                                egui::ComboBox::from_id_source("inference_dataset")
+                                    .selected_text(inference.dataset.clone())
+                                    .width(200.0)
+                                    .show_ui(ui, |ui| {
+                                        for dataset in &available_datasets {
+                                            ui.selectable_value(&mut inference.dataset, dataset.clone(), dataset);
+                                        }
+                                    });
+                                if ui.add_enabled(!inference.dataset.is_empty(), egui::Button::new("Run Inference")).clicked() {
+                                    run_inference_click = true;
+                                }
+                            });
+                            
+                            match &inference.predictions {
+                                Some(Ok((dataset, probabilities))) => {
+                                    let rule = model.config.decision_rule();
+                                    let [negative, positive] = &model.preprocessing.class_names;
+                                    let n_positive = probabilities.column(0).iter().filter(|&&p| rule.predict(p)).count();
+                                    ui.horizontal(|ui| {
+                                        ui.label(format!(
+                                            "{}: {} rows, {} predicted {} (first {} shown)",
+                                            dataset, probabilities.nrows(), n_positive, positive,
+                                            probabilities.nrows().min(MAX_DRILLDOWN_ROWS)
+                                        ));
+                                        if ui.button("Export Predictions")
+                                            .on_hover_text(format!("All rows as CSV in {}", PREDICTIONS_CSV_PATH))
+                                            .clicked() {
+                                            export_predictions_click = true;
+                                        }
+                                    });
+                                    egui::ScrollArea::vertical()
+                                        .id_source("inference_table")
+                                        .max_height(200.0)
+                                        .show(ui, |ui| {
+                                            egui::Grid::new("inference_grid")
+                                                .striped(true)
+                                                .show(ui, |ui| {
+                                                    ui.strong("row");
+                                                    ui.strong(format!("p({})", positive));
+                                                    ui.strong("class");
+                                                    ui.end_row();
+                                                    for (i, &p) in probabilities.column(0).iter().take(MAX_DRILLDOWN_ROWS).enumerate() {
+                                                        ui.label(i.to_string());
+                                                        ui.label(format!("{:.4}", p));
+                                                        ui.label(if rule.predict(p) { positive } else { negative });
+                                                        ui.end_row();
+                                                    }
+                                                });
+                                        });
+                                }
+                                Some(Err(e)) => {
+                                    ui.colored_label(egui::Color32::from_rgb(230, 60, 60), e);
+                                }
+                                None => {}
+                            }
+                        }
+                        Some(Err(e)) => {
+                            ui.colored_label(egui::Color32::from_rgb(230, 60, 60), e);
+                        }
+                        None => {}
+                    }
+                });
+                
+                ui.add_space(10.0);
+                ui.separator();
+                
+                // Dataset Statistics section
+                ui.vertical(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.heading(egui::RichText::new("Dataset Statistics").size(18.0));
+                    });
+                    ui.add_space(5.0);
+                    
+                    if ui.add_enabled(!training_in_progress, egui::Button::new("Analyze Dataset")).clicked() {
+                        analyze_click = true;
+                    }
+                    
+                    if !dropped_features.is_empty() {
+                        let list = dropped_features.iter().map(|&i| feature_label(&feature_names, i)).collect::<Vec<_>>().join(", ");
+                        ui.colored_label(
+                            egui::Color32::from_rgb(230, 200, 50),
+                            format!("Constant features dropped from the last run: {}", list)
+                        );
+                    }
+                    
+                    if feature_correlations.is_empty() {
+                        ui.label("Analyze the dataset (or train) to see each feature's correlation with the label.");
+                    } else {
+                        ui.horizontal(|ui| {
+                            ui.label(format!(
+                                "Pearson correlation with the label for {} features, strongest first. Search:",
+                                feature_correlations.len()
+                            ));
+                            ui.add(
+                                egui::TextEdit::singleline(&mut display_settings.feature_search)
+                                    .hint_text("feature name or index")
+                                    .desired_width(150.0)
+                            );
+                        });
+                        
+                        let search = display_settings.feature_search.trim().to_lowercase();
+                        let matching: Vec<(usize, f64)> = feature_correlations.iter()
+                            .filter(|(feature, _)| {
+                                search.is_empty() || format!("feature {}", feature).contains(&search)
+                                    || feature_label(&feature_names, *feature).to_lowercase().contains(&search)
+                            })
+                            .copied()
+                            .collect();
+                        
+                        // Wide datasets: chart only the strongest matches, the full list scrolls below
+                        let charted = &matching[..matching.len().min(MAX_CHARTED_FEATURES)];
+                        let n_features = charted.len();
+                        let bars: Vec<egui_plot::Bar> = charted.iter()
+                            .enumerate()
+                            .map(|(rank, &(feature, r))| {
+                                egui_plot::Bar::new((n_features - rank) as f64, r)
+                                    .name(feature_label(&feature_names, feature))
+                                    .fill(if r >= 0.0 {
+                                        egui::Color32::from_rgb(60, 200, 90)
+                                    } else {
+                                        egui::Color32::from_rgb(230, 60, 60)
+                                    })
+                            })
+                            .collect();
+                        
+                        Plot::new("correlation_plot")
+                            .height(180.0)
+                            .allow_zoom(false)
+                            .allow_drag(false)
+                            .include_x(-1.0)
+                            .include_x(1.0)
+                            .show(ui, |plot_ui| {
+                                plot_ui.bar_chart(egui_plot::BarChart::new(bars).horizontal().width(0.7));
+                            });
+                        
+                        if matching.len() > MAX_CHARTED_FEATURES {
+                            ui.label(format!("Showing the top {} of {} matching features in the chart:", MAX_CHARTED_FEATURES, matching.len()));
+                            egui::ScrollArea::vertical()
+                                .id_source("correlation_list")
+                                .max_height(150.0)
+                                .show(ui, |ui| {
+                                    for (feature, r) in &matching {
+                                        ui.label(format!("{}: {:+.3}", feature_label(&feature_names, *feature), r));
+                                    }
+                                });
+                        }
+                    }
+                });
+            
+                ui.add_space(10.0);
+                ui.separator();
+                
+                // The same seeded config trained in both precisions, side by side
+                ui.vertical(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.heading(egui::RichText::new("Precision Comparison").size(18.0));
+                    });
+                    ui.add_space(5.0);
+                    
+                    ui.horizontal(|ui| {
+                        let unsupported = crate::precision::unsupported_options(&network_config.lock().unwrap());
+                        let can_compare = !training_in_progress && !comparing_precision && unsupported.is_empty();
+                        if ui.add_enabled(can_compare, egui::Button::new("Precision Comparison"))
+                            .on_hover_text("Train the current config with its seed in f32 and in f64 (plain gradient descent) and compare speed and accuracy")
+                            .on_disabled_hover_text(if unsupported.is_empty() {
+                                "Not while training or comparing".to_string()
+                            } else {
+                                format!("Only plain gradient descent is compared, turn off: {}", unsupported.join(", "))
+                            })
+                            .clicked()
+                        {
+                            precision_click = true;
+                        }
+                        if comparing_precision {
+                            ui.spinner();
+                            ui.label("Training in f32 and f64...");
+                        }
+                    });
+                    
+                    match &precision_comparison {
+                        Some(Ok(comparison)) => {
+                            egui::Grid::new("precision_grid")
+                                .spacing([20.0, 4.0])
+                                .show(ui, |ui| {
+                                    ui.label("Precision");
+                                    ui.label("Time");
+                                    ui.label("Final loss");
+                                    ui.label(if comparison.on_validation { "Validation accuracy" } else { "Training accuracy" });
+                                    ui.end_row();
+                                    for (name, run) in [("f32", comparison.single), ("f64", comparison.double)] {
+                                        ui.label(name);
+                                        ui.label(format!("{:.3}s", run.seconds));
+                                        ui.label(format!("{:.6}", run.loss));
+                                        ui.label(format!("{:.2}%", run.accuracy));
+                                        ui.end_row();
+                                    }
+                                });
+                            ui.label(format!(
+                                "{} epochs, accuracy on {} rows: f32 is {:.2}x as fast with an accuracy gap of {:+.2} points",
+                                comparison.epochs, comparison.evaluated_rows, comparison.speedup(), comparison.accuracy_gap()
+                            ));
+                        }
+                        Some(Err(e)) => {
+                            ui.colored_label(egui::Color32::from_rgb(230, 60, 60), e);
+                        }
+                        None => {}
+                    }
+                });
+            
+                ui.add_space(15.0);
+                ui.separator();
+            
+                // Control buttons - centered buttons
+                ui.vertical_centered(|ui| {
+                    ui.add_space(15.0);
+                
+                    ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                        // Create button styles
+                        let button_height = 40.0;
+                        let button_width = 150.0;
+                    
+                        // Train button
+                        let can_train = !training_in_progress && dataset_problem.is_none();
+                        let btn_text = if training_in_progress {
+                            "Training..."
+                        } else if completed {
+                            "Restart Training"
+                        } else {
+                            "Start Training"
+                        };
+                    
+                        if ui.add_sized(
+                            [button_width, button_height],
+                            egui::Button::new(
+                                egui::RichText::new(btn_text)
+                                .size(16.0)
+                                .color(egui::Color32::WHITE)
+                            )
+                            .fill(if can_train { egui::Color32::from_rgb(0, 255, 8) } else { egui::Color32::DARK_GRAY })
+                        ).clicked() && can_train {
+                            train_click = true;
+                        }
+                        
+                        if let Some(problem) = &dataset_problem {
+                            ui.colored_label(egui::Color32::from_rgb(230, 60, 60), problem);
+                        }
+                    
+                        ui.add_space(20.0);
+                    
+                        // Stop button (hanya aktif saat training berjalan)
+                        if ui.add_sized(
+                            [button_width, button_height],
+                            egui::Button::new(
+                                egui::RichText::new("⏹ Stop Training")
+                                .size(16.0)
+                                .color(egui::Color32::WHITE)
+                            )
+                            .fill(if training_in_progress { egui::Color32::from_rgb(255, 0, 0) } else { egui::Color32::DARK_GRAY })
+                        ).on_hover_text("Shortcut: Escape").clicked() && training_in_progress {
+                            stop_click = true;
+                        }
+                    
+                        ui.add_space(10.0);
+                        
+                        // Hold the epoch loop without ending the run
+                        if ui.add_enabled(
+                            training_in_progress,
+                            egui::Button::new(if paused { "▶ Resume" } else { "⏸ Pause" })
+                        ).clicked() {
+                            pause_click = true;
+                        }
+                        
+                        ui.add_space(10.0);
+                        
+                        // Extend a finished run from its retained weights
+                        let can_continue = completed && has_trained_weights;
+                        if ui.add_enabled(
+                            can_continue,
+                            egui::Button::new(format!("+{} Epochs", CONTINUE_EPOCHS))
+                        ).clicked() {
+                            continue_click = true;
+                        }
+                        
+                        ui.add_space(10.0);
+                    
+                        // Export button (only after a run produced weights)
+                        let can_export = completed && has_trained_weights;
+                        if ui.add_enabled(can_export, egui::Button::new("Export Weights CSV")).clicked() {
+                            export_weights_click = true;
+                        }
+                        if ui.add_enabled(can_export, egui::Button::new("Export Weights JSON"))
+                            .on_hover_text("One JSON file with weights, activations and preprocessing, e.g. for NumPy")
+                            .clicked() {
+                            export_json_click = true;
+                        }
+                        if ui.add_enabled(can_export, egui::Button::new("Export ONNX"))
+                            .on_hover_text(format!("ONNX graph taking raw feature values, in {}", ONNX_PATH))
+                            .clicked() {
+                            export_onnx_click = true;
+                        }
+                        if ui.add_enabled(can_export, egui::Button::new("Save Model"))
+                            .on_hover_text(format!("Weights, layer shapes, config and preprocessing in {}", MODEL_PATH))
+                            .clicked() {
+                            save_model_click = true;
+                        }
+                        
+                        if ui.add_enabled(completed, egui::Button::new("Generate Report Image")).clicked() {
+                            report_click = true;
+                        }
+                        if ui.add_enabled(completed, egui::Button::new("Export History"))
+                            .on_hover_text(format!("Per-epoch loss and accuracy as CSV in {}", HISTORY_CSV_PATH))
+                            .clicked() {
+                            export_history_click = true;
+                        }
+                        if ui.add_enabled(completed && has_evaluated_samples, egui::Button::new("Export Misclassified"))
+                            .on_hover_text(format!("Wrongly classified samples with their features as CSV in {}", MISCLASSIFIED_CSV_PATH))
+                            .clicked() {
+                            export_misclassified_click = true;
+                        }
+                        if ui.add_enabled(completed, egui::Button::new("Export SVG"))
+                            .on_hover_text(format!("Loss figure as a vector image in {}", crate::PlotFormat::Svg.path()))
+                            .clicked() {
+                            export_svg_click = true;
+                        }
+                    
+                        if let Some(message) = &notice {
+                            ui.colored_label(egui::Color32::from_rgb(180, 180, 200), message);
+                        }
+                        match &model_save_result {
+                            Some(Ok(message)) => {
+                                ui.colored_label(egui::Color32::from_rgb(180, 180, 200), message);
+                            }
+                            Some(Err(e)) => {
+                                ui.colored_label(egui::Color32::from_rgb(230, 60, 60), e);
+                            }
+                            None => {}
+                        }
+                    });
+                });
+            });  // End of ScrollArea
+        });  // End of CentralPanel
+        
+        // Keep thresholds ordered so the yellow band never inverts
+        if display_settings.accuracy_high_threshold < display_settings.accuracy_low_threshold {
+            display_settings.accuracy_high_threshold = display_settings.accuracy_low_threshold;
+        }
+        self.display_settings = display_settings;
+        self.hidden_layers_text = hidden_layers_text;
+        self.hidden_layers_synced = network_config.lock().unwrap().hidden_layers.clone();
+        self.epochs_text = epochs_text;
+        self.epochs_synced = network_config.lock().unwrap().epochs;
+        self.ignored_columns_text = ignored_columns_text;
+        self.ignored_columns_synced = network_config.lock().unwrap().ignored_columns.clone();
+        self.extra_labels_text = extra_labels_text;
+        self.extra_labels_synced = network_config.lock().unwrap().extra_label_columns.clone();
+        
+        // After the text buffers were stored, so they pick up the defaults next frame
+        if reset_config_click && !training_data.lock().unwrap().training_in_progress {
+            let mut config = network_config.lock().unwrap();
+            *config = config.defaults_for_dataset();
+        }
+        
+        // The column names depend on how the fields are split
+        if delimiter_changed {
+            let delimiter = network_config.lock().unwrap().dataset_delimiter;
+            let mut data = training_data.lock().unwrap();
+            data.column_names = crate::dataset_column_names(&data.dataset_path, delimiter).ok();
+        }
+        
+        // Handle dataset changes 
+        if let Some(path) = new_dataset_path {
+            if path == "REFRESH" {
+                // Just refresh the datasets list
+                self.refresh_datasets();
+            } else {
+                // Update the selected dataset
+                let delimiter = network_config.lock().unwrap().dataset_delimiter;
+                let mut data = training_data.lock().unwrap();
+                data.dataset_path = path.clone();
+                data.dataset_problem = check_dataset_file(&path);
+                data.column_names = crate::dataset_column_names(&path, delimiter).ok();
+                println!("Dataset changed to: {}", path);
+            }
+        }
+        
+        if precision_click {
+            self.compare_precisions();
+        }
+        
+        // Handle training button click outside of the panel to avoid borrowing issues
+        if let Some(policy) = ragged_choice {
+            network_config.lock().unwrap().ragged_rows = policy;
+            self.ragged_prompt = None;
+            train_click = true;
+        }
+        if cancel_ragged {
+            self.ragged_prompt = None;
+        }
+        
+        // Check for ragged rows first unless the config already says how to handle them
+        if train_click && network_config.lock().unwrap().ragged_rows == RaggedRows::Fail {
+            let path = training_data.lock().unwrap().dataset_path.clone();
+            let delimiter = network_config.lock().unwrap().dataset_delimiter;
+            if let Ok((n_columns, lines)) = crate::find_ragged_rows(&path, delimiter) {
+                if !lines.is_empty() {
+                    self.ragged_prompt = Some((n_columns, lines));
+                    train_click = false;
+                }
+            }
+        }
+        
+        if train_click {
+            let mut data = self.training_data.lock().unwrap();
+            if !data.training_in_progress && data.dataset_problem.is_none() {
+                // Reset data for new training session
+                if data.completed {
+                    data.reset();
+                }
+                data.training_in_progress = true;
+                data.completed = false;
+                data.epoch = 0;
+                data.error_message = None;
+                self.model_save_result = None;
+                
+                // Trigger training callback outside of the lock
+                drop(data); // Drop the lock here to avoid deadlocks
+                if let Some(callback) = &self.train_callback {
+                    callback();
+                }
+            }
+        }
+        
+        if export_weights_click {
+            self.export_weights_csv("result/weights");
+        }
+        if export_json_click {
+            self.export_weights_json("result/weights.json");
+        }
+        if export_onnx_click {
+            self.export_weights_onnx(ONNX_PATH);
+        }
+        if save_model_click {
+            self.save_model(MODEL_PATH);
+        }
+        if export_history_click {
+            self.export_history_csv(HISTORY_CSV_PATH);
+        }
+        if export_misclassified_click {
+            self.export_misclassified_csv(MISCLASSIFIED_CSV_PATH);
+        }
+        if export_svg_click {
+            self.export_loss_svg();
+        }
+        
+        if load_model_click {
+            inference.model = Some(load_model(inference.model_path.trim()).map_err(|e| e.to_string()));
+            inference.predictions = None;
+        }
+        if run_inference_click {
+            if let Some(Ok(model)) = &inference.model {
+                let path = format!("csv/{}", inference.dataset);
+                inference.predictions = Some(
+                    crate::predict_csv(&path, model)
+                        .map(|probabilities| (inference.dataset.clone(), probabilities))
+                        .map_err(|e| e.to_string())
+                );
+            }
+        }
+        if export_predictions_click {
+            if let (Some(Ok(model)), Some(Ok((_, probabilities)))) = (&inference.model, &inference.predictions) {
+                self.export_predictions_csv(PREDICTIONS_CSV_PATH, probabilities, model);
+            }
+        }
+        self.inference = inference;
+        self.what_if_values = what_if_values;
+        if what_if_click {
+            self.what_if_result = Some(self.predict_what_if(&self.what_if_values));
+        }
+        
+        if report_click {
+            self.generate_report();
+        }
+        
+        if analyze_click {
+            self.analyze_dataset();
+        }
+        
+        self.synthetic_spec = synthetic_spec;
+        self.prediction_input = prediction_input;
+        if predict_click {
+            let config = network_config.lock().unwrap().clone();
+            let data = training_data.lock().unwrap();
+            self.prediction_result = Some(match &data.trained_weights {
+                Some(weights) => crate::predict_line(&self.prediction_input, weights, &config, &data.dropped_features, data.normalization.as_ref())
+                    .map_err(|e| e.to_string()),
+                None => Err("No trained weights available".to_string()),
+            });
+        }
+        if generate_click {
+            self.generate_synthetic_dataset(&self.synthetic_spec);
+        }
+        
+        // Notes typed this frame, unless a run finished and shifted the list meanwhile
+        {
+            let mut runs = self.completed_runs.lock().unwrap();
+            if runs.len() == run_notes.len() {
+                for (run, note) in runs.iter_mut().zip(run_notes) {
+                    run.note = note;
+                }
+            }
+        }
+        if clear_comparison_click {
+            self.completed_runs.lock().unwrap().clear();
+        }
+        if save_comparisons_click {
+            self.save_comparisons();
+        }
+        if load_comparisons_click {
+            self.load_comparisons();
+        }
+        self.replay = if close_replay { None } else { replay };
+        if load_history_click {
+            match RunHistory::load(HISTORY_PATH) {
+                Ok(history) => self.replay = Some(Replay::new(history)),
+                Err(e) => self.show_toast(&format!("Could not load the run history: {}", e)),
+            }
+        }
+        
+        // Continue the finished run without resetting the loss history
+        if continue_click {
+            let mut data = self.training_data.lock().unwrap();
+            if data.completed && data.trained_weights.is_some() {
+                data.resume_epochs = Some(CONTINUE_EPOCHS);
+                data.training_in_progress = true;
+                data.completed = false;
+                data.error_message = None;
+                
+                drop(data); // Drop the lock here to avoid deadlocks
+                if let Some(callback) = &self.train_callback {
+                    callback();
+                }
+            }
+        }
+        
+        if pause_click && training_in_progress {
+            self.set_paused(!paused);
+        }
+        
+        // Handle stop button click
+        if stop_click {
+            let mut data = self.training_data.lock().unwrap();
+            if data.training_in_progress {
+                // Show confirmation dialog instead of immediately stopping
+                data.show_stop_confirm = true;
+            }
+        }
+        
+        // Request continuous repainting, unless display updates are paused
+        if !self.display_settings.pause_display {
+            ctx.request_repaint();
+        }
+    }
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yes_no_labels_round_trip_to_predictions() {
+        let path = std::env::temp_dir().join(format!("neural_network_test_{}_yes_no.csv", std::process::id()));
+        std::fs::write(&path, "smoke,label\n0.1,no\n0.9,yes\n0.2,no\n").unwrap();
+        let (_, y, class_names, _, _) = crate::load_data(&path.to_string_lossy(), &crate::LoadOptions::default()).unwrap();
+        assert_eq!(y.column(0).to_vec(), vec![0.0, 1.0, 0.0]);
+
+        // p = sigmoid(relu(10 * smoke - 5) - 2), above 0.5 for smoke 0.9 and below for 0.1
+        let app = NeuralNetworkApp::without_saved_comparisons();
+        {
+            let mut data = app.training_data.lock().unwrap();
+            data.class_names = class_names[0].clone();
+            data.trained_weights = Some(ModelWeights {
+                weights: vec![Array2::from_elem((1, 1), 10.0), Array2::from_elem((1, 1), 1.0)],
+                biases: vec![Array2::from_elem((1, 1), -5.0), Array2::from_elem((1, 1), -2.0)],
+                skip: None,
+                batch_norm: None,
+            });
+            data.run_config = Some(NetworkConfig { hidden_layers: vec![1], activation: Activation::ReLU, ..NetworkConfig::default() });
+        }
+        assert_eq!(app.predict_what_if(&[0.9]).unwrap(), "p(yes) = 0.8808 → yes");
+        assert_eq!(app.predict_what_if(&[0.1]).unwrap(), "p(yes) = 0.1192 → no");
+    }
+
+    #[test]
+    fn csv_directory_without_datasets_is_explained() {
+        let dir = std::env::temp_dir().join(format!("neural_network_test_{}_csv_dir", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir_name = dir.to_string_lossy().into_owned();
+        assert_eq!(check_csv_directory(&dir_name), None);
+
+        for name in ["notes.txt", "data.xlsx", "readme.md"] {
+            std::fs::write(dir.join(name), "x").unwrap();
+        }
+        assert_eq!(
+            check_csv_directory(&dir_name),
+            Some(format!("Found 3 files in {}/ but none are .csv or .tsv datasets", dir_name))
+        );
+
+        std::fs::write(dir.join("data.csv"), "a,label\n1,0\n").unwrap();
+        assert_eq!(check_csv_directory(&dir_name), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,260 @@
+use csv::WriterBuilder;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+use crate::frontend_new::{CompletedRun, TrainingData};
+use crate::stats::{ConfusionCell, DecisionRule, EvaluatedSamples};
+
+pub const HISTORY_PATH: &str = "result/history.json";
+pub const HISTORY_CSV_PATH: &str = "result/history.csv";
+pub const COMPARISONS_PATH: &str = "result/comparisons.json";
+pub const PREDICTIONS_CSV_PATH: &str = "result/predictions.csv";
+pub const MISCLASSIFIED_CSV_PATH: &str = "result/misclassified.csv";
+
+/// Per-epoch record of a finished run, saved so the training charts can be
+/// replayed later without training again
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunHistory {
+    pub dataset: String,
+    /// Epoch of the first recorded point (older ones dropped by the live history limit)
+    #[serde(default)]
+    pub first_epoch: usize,
+    pub hidden_layers: Vec<usize>,
+    pub learning_rate: f64,
+    pub final_accuracy: f64,
+    pub losses: Vec<f64>,
+    pub accuracies: Vec<f64>,
+}
+
+impl RunHistory {
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        println!("Run history saved to {}", path);
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        let history: Self = serde_json::from_str(&text).map_err(|e| format!("Invalid run history {}: {}", path, e))?;
+        if history.losses.len() != history.accuracies.len() {
+            return Err(format!(
+                "Run history {} has {} losses but {} accuracies",
+                path, history.losses.len(), history.accuracies.len()
+            ).into());
+        }
+        Ok(history)
+    }
+}
+
+// Write the compared runs with their configs, series and notes
+pub fn save_comparisons(path: &str, runs: &[CompletedRun]) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(runs)?)?;
+    println!("Compared runs saved to {}", path);
+    Ok(())
+}
+
+pub fn load_comparisons(path: &str) -> Result<Vec<CompletedRun>, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let runs: Vec<CompletedRun> = serde_json::from_str(&text).map_err(|e| format!("Invalid comparisons file {}: {}", path, e))?;
+    if let Some(run) = runs.iter().find(|run| run.losses.len() != run.accuracies.len()) {
+        return Err(format!(
+            "Compared run \"{}\" in {} has {} losses but {} accuracies",
+            run.label, path, run.losses.len(), run.accuracies.len()
+        ).into());
+    }
+    Ok(runs)
+}
+
+// Write the charted per-epoch series as CSV for pandas or spreadsheets: epoch,
+// loss, accuracy, plus validation columns when the run held out rows
+pub fn export_history(path: &str, data: &TrainingData, delimiter: u8) -> Result<(), Box<dyn Error>> {
+    if data.losses.is_empty() {
+        return Err("No training history to export".into());
+    }
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let has_validation = !data.val_losses.is_empty();
+    let mut wtr = WriterBuilder::new().delimiter(delimiter).from_path(path)?;
+    let mut header = vec!["epoch", "loss", "accuracy"];
+    if has_validation {
+        header.extend(["val_loss", "val_accuracy"]);
+    }
+    wtr.write_record(&header)?;
+    for (i, (loss, accuracy)) in data.losses.iter().zip(&data.accuracies).enumerate() {
+        let mut record = vec![(data.first_epoch + i).to_string(), loss.to_string(), accuracy.to_string()];
+        if has_validation {
+            record.push(data.val_losses.get(i).map_or(String::new(), |v| v.to_string()));
+            record.push(data.val_accuracies.get(i).map_or(String::new(), |v| v.to_string()));
+        }
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+
+    println!("Training history exported to {}", path);
+    Ok(())
+}
+
+// Write the probabilities of a saved model's inference, one column per head,
+// with the class the first head predicts under `rule`
+pub fn export_predictions(
+    path: &str,
+    probabilities: &Array2<f64>,
+    rule: DecisionRule,
+    class_names: &[String; 2],
+    delimiter: u8,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut wtr = WriterBuilder::new().delimiter(delimiter).from_path(path)?;
+    let mut header = vec!["row".to_string()];
+    match probabilities.ncols() {
+        1 => header.push("probability".to_string()),
+        n => header.extend((0..n).map(|head| format!("probability_{}", head))),
+    }
+    header.push("class".to_string());
+    wtr.write_record(&header)?;
+    for (i, row) in probabilities.rows().into_iter().enumerate() {
+        let mut record = vec![i.to_string()];
+        record.extend(row.iter().map(|p| p.to_string()));
+        record.push(class_names[rule.predict(row[0]) as usize].clone());
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+
+    println!("Predictions exported to {}", path);
+    Ok(())
+}
+
+// Write the wrongly classified samples of the final evaluation: row, feature
+// values, true label and predicted probability
+pub fn export_misclassified(path: &str, samples: &EvaluatedSamples, feature_names: &[String], delimiter: u8) -> Result<(), Box<dyn Error>> {
+    let mut rows: Vec<usize> = [ConfusionCell::FalsePositive, ConfusionCell::FalseNegative]
+        .into_iter()
+        .flat_map(|cell| samples.indices_in(cell))
+        .collect();
+    rows.sort_unstable();
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let n_features = samples.features.ncols();
+    let mut wtr = WriterBuilder::new().delimiter(delimiter).from_path(path)?;
+    let mut header = vec!["row".to_string()];
+    if feature_names.len() == n_features {
+        header.extend(feature_names.iter().cloned());
+    } else {
+        header.extend((0..n_features).map(|feature| format!("feature_{}", feature)));
+    }
+    header.extend(["label".to_string(), "probability".to_string()]);
+    wtr.write_record(&header)?;
+    for &i in &rows {
+        let mut record = vec![i.to_string()];
+        record.extend(samples.features.row(i).iter().map(|v| v.to_string()));
+        record.push(samples.labels[i].to_string());
+        record.push(samples.probabilities[i].to_string());
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+
+    println!("{} misclassified samples exported to {}", rows.len(), path);
+    Ok(())
+}
+
+/// Playback of a loaded history: the charts show the first `position` epochs,
+/// which advances while playing and can be scrubbed by hand
+#[derive(Clone)]
+pub struct Replay {
+    pub history: RunHistory,
+    pub position: f64,
+    pub playing: bool,
+    pub epochs_per_second: f64,
+}
+
+impl Replay {
+    // Starts paused at epoch 0, paced so a full playback takes about 10 seconds
+    pub fn new(history: RunHistory) -> Self {
+        let epochs_per_second = (history.losses.len() as f64 / 10.0).max(1.0);
+        Self { history, position: 0.0, playing: false, epochs_per_second }
+    }
+
+    pub fn len(&self) -> usize {
+        self.history.losses.len()
+    }
+
+    pub fn shown(&self) -> usize {
+        (self.position as usize).min(self.len())
+    }
+
+    pub fn advance(&mut self, seconds: f64) {
+        if !self.playing {
+            return;
+        }
+        self.position = (self.position + seconds * self.epochs_per_second).min(self.len() as f64);
+        if self.shown() == self.len() {
+            self.playing = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend_new::NetworkConfig;
+    use crate::stats::TieBreak;
+    use ndarray::array;
+
+    #[test]
+    fn comparisons_round_trip_with_configs_and_notes() {
+        let path = std::env::temp_dir().join(format!("neural_network_test_{}_comparisons.json", std::process::id()));
+        let path = path.to_string_lossy();
+        let runs = vec![CompletedRun {
+            label: "[8] lr 0.01, 3 epochs".to_string(),
+            config: NetworkConfig { hidden_layers: vec![8], learning_rate: 0.01, ..NetworkConfig::default() },
+            note: "baseline".to_string(),
+            first_epoch: 1,
+            losses: vec![0.7, 0.5, 0.4],
+            accuracies: vec![60.0, 75.0, 80.0],
+        }];
+        save_comparisons(&path, &runs).unwrap();
+        let loaded = load_comparisons(&path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].note, "baseline");
+        assert_eq!(loaded[0].first_epoch, 1);
+        assert_eq!(loaded[0].losses, runs[0].losses);
+        assert!(loaded[0].config == runs[0].config);
+    }
+
+    #[test]
+    fn csv_exports_use_the_chosen_separator() {
+        let rule = DecisionRule { threshold: 0.5, tie_break: TieBreak::Positive };
+        let path = std::env::temp_dir().join(format!("neural_network_test_{}_misclassified.csv", std::process::id()));
+        let path = path.to_string_lossy();
+        let samples = EvaluatedSamples {
+            features: array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]],
+            labels: vec![1.0, 0.0, 0.0],
+            probabilities: vec![0.9, 0.7, 0.2],
+            rule,
+        };
+        export_misclassified(&path, &samples, &["age".to_string(), "income".to_string()], b';').unwrap();
+        let text = std::fs::read_to_string(&*path).unwrap();
+        assert_eq!(text, "row;age;income;label;probability\n1;3;4;0;0.7\n");
+
+        let path = std::env::temp_dir().join(format!("neural_network_test_{}_predictions.csv", std::process::id()));
+        let path = path.to_string_lossy();
+        let class_names = ["no".to_string(), "yes".to_string()];
+        export_predictions(&path, &array![[0.25], [0.5]], rule, &class_names, b'\t').unwrap();
+        let text = std::fs::read_to_string(&*path).unwrap();
+        assert_eq!(text, "row\tprobability\tclass\n0\t0.25\tno\n1\t0.5\tyes\n");
+    }
+}
@@ -1,30 +1,404 @@
-use ndarray::{Array2, Axis};
+use ndarray::{s, Array1, Array2, ArrayView2, Axis};
 use ndarray_rand::RandomExt;
-use rand_distr::StandardNormal;
-use rand::thread_rng;
+use rand_distr::{StandardNormal, Uniform};
+use rand::{thread_rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
 use csv::ReaderBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::error::Error;
+use std::io::Write;
 use plotters::prelude::*;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
 use std::thread;
-use eframe;
 
+mod cli;
 mod frontend_new;
-use frontend_new::NeuralNetworkApp;
+mod history;
+mod model;
+mod observer;
+mod onnx;
+mod parallel;
+mod precision;
+mod report;
+mod stats;
+mod synthetic;
+use frontend_new::{Activation, InitScheme, Metric, Optimizer, MonitorMode, NetworkConfig, NeuralNetworkApp, ProgressUpdate, TaskType, TrainingData};
+use model::{BatchNorm, LoadedModel, ModelWeights, Normalization, Preprocessing, BATCH_NORM_EPSILON, BATCH_NORM_MOMENTUM};
+use stats::{ClassAccuracy, ConfusionMatrix, DecisionRule, EvaluatedSamples, RegressionMetrics};
 
 // Default values moved to NetworkConfig in frontend.rs
-const LOG_INTERVAL: usize = 100; // How often to log progress
+const WEIGHT_HISTOGRAM_BINS: usize = 30; // Bins of the weight histograms sent at each logged epoch
+const DETERMINISTIC_SEED: u64 = 0; // Seed used in deterministic mode when no seed is configured
+const PROGRESS_LOG_PATH: &str = "result/progress.jsonl";
+const LOAD_PROGRESS_ROWS: usize = 10_000; // Records between two dataset loading progress reports
 
-fn relu(x: &Array2<f64>) -> Array2<f64> {
-    x.mapv(|v| v.max(0.0))
+fn relu(x: &Array2<f64>, parallel: bool) -> Array2<f64> {
+    parallel::mapv(x, |v| v.max(0.0), parallel)
 }
 
-fn relu_deriv(x: &Array2<f64>) -> Array2<f64> {
-    x.mapv(|v| if v > 0.0 { 1.0 } else { 0.0 })
+fn relu_deriv(x: &Array2<f64>, parallel: bool) -> Array2<f64> {
+    parallel::mapv(x, |v| if v > 0.0 { 1.0 } else { 0.0 }, parallel)
 }
 
-fn sigmoid(x: &Array2<f64>) -> Array2<f64> {
-    x.mapv(|v| 1.0 / (1.0 + (-v).exp()))
+const LEAKY_RELU_SLOPE: f64 = 0.01;
+
+fn leaky_relu(x: &Array2<f64>, parallel: bool) -> Array2<f64> {
+    parallel::mapv(x, |v| if v > 0.0 { v } else { LEAKY_RELU_SLOPE * v }, parallel)
+}
+
+fn leaky_relu_deriv(x: &Array2<f64>, parallel: bool) -> Array2<f64> {
+    parallel::mapv(x, |v| if v > 0.0 { 1.0 } else { LEAKY_RELU_SLOPE }, parallel)
+}
+
+// Hidden-layer activation chosen in the config, element-wise on the rayon pool when `parallel`
+fn activate(activation: Activation, z: &Array2<f64>, parallel: bool) -> Array2<f64> {
+    match activation {
+        Activation::ReLU => relu(z, parallel),
+        Activation::LeakyReLU => leaky_relu(z, parallel),
+        Activation::Tanh => parallel::mapv(z, f64::tanh, parallel),
+        Activation::Sigmoid => sigmoid(z, parallel),
+    }
+}
+
+// Derivative of the activation at `z`. Tanh and sigmoid use the cached
+// activation output `a` (1 - a^2 and a(1 - a)) instead of recomputing from `z`.
+fn activation_deriv(activation: Activation, z: &Array2<f64>, a: &Array2<f64>, parallel: bool) -> Array2<f64> {
+    match activation {
+        Activation::ReLU => relu_deriv(z, parallel),
+        Activation::LeakyReLU => leaky_relu_deriv(z, parallel),
+        Activation::Tanh => parallel::mapv(a, |v| 1.0 - v * v, parallel),
+        Activation::Sigmoid => parallel::mapv(a, |v| v * (1.0 - v), parallel),
+    }
+}
+
+// Logistic function that only ever exponentiates a non-positive number, so
+// exp never overflows for large-magnitude inputs and very negative ones keep
+// their precision instead of going through 1 / (1 + huge)
+fn stable_sigmoid(v: f64) -> f64 {
+    if v >= 0.0 {
+        1.0 / (1.0 + (-v).exp())
+    } else {
+        let e = v.exp();
+        e / (1.0 + e)
+    }
+}
+
+fn sigmoid(x: &Array2<f64>, parallel: bool) -> Array2<f64> {
+    parallel::mapv(x, stable_sigmoid, parallel)
+}
+
+// Optionally limit the output logits to [-limit, limit] before the sigmoid.
+// Predictions then stay within [sigmoid(-limit), sigmoid(limit)], so a confidently
+// wrong sample costs at most about `limit` in BCE instead of a huge spike. The
+// backward pass keeps using `y_pred - y_true`, so the output gradient never
+// vanishes even when the raw logit is far beyond the clamp.
+fn clamp_logits(z: Array2<f64>, limit: Option<f64>) -> Array2<f64> {
+    match limit {
+        Some(limit) => z.mapv(|v| v.clamp(-limit, limit)),
+        None => z,
+    }
+}
+
+// Output layer of the task: clamped logits through the sigmoid for
+// classification, the linear output as it is for regression
+fn output_activation(z: Array2<f64>, config: &NetworkConfig, parallel: bool) -> Array2<f64> {
+    match config.task_type {
+        TaskType::Classification => sigmoid(&clamp_logits(z, config.logit_clamp), parallel),
+        TaskType::Regression => z,
+    }
+}
+
+// Output probabilities (regression: predicted values) of the trained network, one column per head
+fn forward(x: ArrayView2<f64>, weights: &ModelWeights, config: &NetworkConfig) -> Array2<f64> {
+    let n_hidden = weights.weights.len() - 1;
+    let mut a = x.to_owned();
+    for layer in 0..n_hidden {
+        let mut z = a.dot(&weights.weights[layer]) + &weights.biases[layer];
+        if let Some(batch_norm) = &weights.batch_norm {
+            z = batch_norm[layer].apply(&z);
+        }
+        a = activate(config.activation, &z, false);
+        if let Some(skip) = weights.skip.as_ref().filter(|_| layer == 0) {
+            a += &x.dot(skip);
+        }
+    }
+    output_activation(a.dot(&weights.weights[n_hidden]) + &weights.biases[n_hidden], config, false)
+}
+
+// Saliency of each input: mean |d output / d input| of the primary head over the
+// rows of `x`. The output itself (not the loss) is backpropagated through the
+// trained network, like `train_step` does, but on down to the inputs.
+fn input_saliency(x: &Array2<f64>, weights: &ModelWeights, config: &NetworkConfig) -> Vec<f64> {
+    let n_hidden = weights.weights.len() - 1;
+    let mut pre_activations = Vec::with_capacity(n_hidden);
+    let mut outputs = Vec::with_capacity(n_hidden);
+    let mut a = x.clone();
+    for layer in 0..n_hidden {
+        let mut z = a.dot(&weights.weights[layer]) + &weights.biases[layer];
+        if let Some(batch_norm) = &weights.batch_norm {
+            z = batch_norm[layer].apply(&z);
+        }
+        let h = activate(config.activation, &z, false);
+        a = h.clone();
+        if let Some(skip) = weights.skip.as_ref().filter(|_| layer == 0) {
+            a += &x.dot(skip);
+        }
+        pre_activations.push(z);
+        outputs.push(h);
+    }
+    let w_out = weights.weights[n_hidden].slice(s![.., 0..1]);
+    let y = output_activation(a.dot(&w_out) + weights.biases[n_hidden].slice(s![.., 0..1]), config, false);
+    let dz_out = match config.task_type {
+        TaskType::Classification => y.mapv(|p| p * (1.0 - p)),
+        TaskType::Regression => Array2::ones(y.raw_dim()),
+    };
+
+    let mut da = dz_out.dot(&w_out.t());
+    let mut dx = Array2::<f64>::zeros(x.raw_dim());
+    for layer in (0..n_hidden).rev() {
+        if let Some(skip) = weights.skip.as_ref().filter(|_| layer == 0) {
+            dx += &da.dot(&skip.t());
+        }
+        let mut dz = &da * &activation_deriv(config.activation, &pre_activations[layer], &outputs[layer], false);
+        // At inference batch norm is a fixed per-unit affine map
+        if let Some(batch_norm) = &weights.batch_norm {
+            dz *= &batch_norm[layer].inference_scale();
+        }
+        da = dz.dot(&weights.weights[layer].t());
+    }
+    dx += &da;
+    dx.mapv(f64::abs).mean_axis(Axis(0)).map_or_else(Vec::new, |mean| mean.to_vec())
+}
+
+/// Probabilities (one per head) for a single pasted row of feature values, split
+/// like a dataset line. The row holds the features as in the CSV, without ignored
+/// and label columns; feature weights and dropped constant columns are applied
+/// the same way as in training.
+pub fn predict_line(
+    line: &str,
+    weights: &ModelWeights,
+    config: &NetworkConfig,
+    dropped_features: &[usize],
+    normalization: Option<&Normalization>,
+) -> Result<Vec<f64>, Box<dyn Error>> {
+    let line = line.trim();
+    let delimiter = detect_delimiter(line);
+    let mut values = Vec::new();
+    for (i, field) in line.split(delimiter as char).enumerate() {
+        let value = parse_number(field.trim(), delimiter)
+            .ok_or_else(|| format!("Value {} ('{}') is not a number", i + 1, field.trim()))?;
+        values.push(value);
+    }
+
+    let feature_weights = match config.feature_weights_file.trim() {
+        "" => None,
+        file => Some(load_feature_weights(file)?),
+    };
+    predict_row(&values, weights, config, feature_weights.as_deref(), dropped_features, normalization)
+}
+
+/// Output of the primary head (the probability, or the value for regression)
+/// for one row of feature values laid out as in `predict_line`, preprocessed
+/// like the rows the network was trained on
+pub fn predict_one(values: &[f64], weights: &ModelWeights, config: &NetworkConfig, preprocessing: &Preprocessing) -> Result<f64, Box<dyn Error>> {
+    let outputs = predict_row(
+        values, weights, config,
+        preprocessing.feature_weights.as_deref(), &preprocessing.dropped_features, preprocessing.normalization.as_ref(),
+    )?;
+    Ok(outputs[0])
+}
+
+// Outputs of every head for one row of raw feature values
+fn predict_row(
+    values: &[f64],
+    weights: &ModelWeights,
+    config: &NetworkConfig,
+    feature_weights: Option<&[f64]>,
+    dropped_features: &[usize],
+    normalization: Option<&Normalization>,
+) -> Result<Vec<f64>, Box<dyn Error>> {
+    let expected = weights.n_features() + dropped_features.len();
+    if values.len() != expected {
+        return Err(format!("Expected {} feature values, got {}", expected, values.len()).into());
+    }
+
+    let x = Array2::from_shape_vec((1, values.len()), values.to_vec())?;
+    let x = prepare_inputs(x, feature_weights, dropped_features, normalization)?;
+    Ok(forward(x.view(), weights, config).row(0).to_vec())
+}
+
+// Turn raw feature columns into network inputs the way training did: the
+// multipliers, then without the dropped constant columns, then the z-score
+fn prepare_inputs(
+    mut x: Array2<f64>,
+    feature_weights: Option<&[f64]>,
+    dropped_features: &[usize],
+    normalization: Option<&Normalization>,
+) -> Result<Array2<f64>, Box<dyn Error>> {
+    if let Some(feature_weights) = feature_weights {
+        apply_feature_weights(&mut x, feature_weights)?;
+    }
+    let keep: Vec<usize> = (0..x.ncols()).filter(|i| !dropped_features.contains(i)).collect();
+    let mut x = x.select(Axis(1), &keep);
+    if let Some(normalization) = normalization {
+        normalization.apply(&mut x);
+    }
+    Ok(x)
+}
+
+// Probabilities (rows x heads) of a saved model for every row of a CSV laid out
+// like its training data, with or without the label columns
+pub fn predict_csv(path: &str, model: &LoadedModel) -> Result<Array2<f64>, Box<dyn Error>> {
+    let preprocessing = &model.preprocessing;
+    let n_inputs = model.weights.n_features() + preprocessing.dropped_features.len();
+    let DatasetCsv { reader: mut rdr, delimiter, column_names, .. } = open_dataset_csv(path, None)?;
+    let n_columns = column_names.len();
+
+    let unlabeled: Vec<usize> = (0..n_columns)
+        .filter(|c| !preprocessing.ignored_columns.contains(c))
+        .collect();
+    let label_column = preprocessing.label_column.unwrap_or(n_columns.saturating_sub(1));
+    let labeled: Vec<usize> = (0..n_columns)
+        .filter(|c| !preprocessing.ignored_columns.contains(c) && !preprocessing.extra_label_columns.contains(c))
+        .filter(|&c| c != label_column)
+        .collect();
+    let feature_columns = if unlabeled.len() == n_inputs {
+        unlabeled
+    } else if labeled.len() == n_inputs {
+        labeled
+    } else {
+        return Err(format!(
+            "The model expects {} feature columns, {} has {} besides the ignored columns ({} with label columns)",
+            n_inputs, path, unlabeled.len(), labeled.len()
+        ).into());
+    };
+
+    let mut values = Vec::new();
+    for (i, result) in rdr.records().enumerate() {
+        let record = result?;
+        for &column in &feature_columns {
+            let field = record.get(column)
+                .ok_or_else(|| format!("Line {} has only {} fields", i + 2, record.len()))?
+                .trim();
+            let value = parse_number(field, delimiter)
+                .ok_or_else(|| format!("Line {}: '{}' is not a number", i + 2, field))?;
+            values.push(value);
+        }
+    }
+    let n_rows = values.len() / feature_columns.len().max(1);
+    if n_rows == 0 {
+        return Err(format!("{} has no data rows", path).into());
+    }
+
+    let x = Array2::from_shape_vec((n_rows, feature_columns.len()), values)?;
+    let x = prepare_inputs(x, preprocessing.feature_weights.as_deref(), &preprocessing.dropped_features, preprocessing.normalization.as_ref())?;
+    Ok(forward(x.view(), &model.weights, &model.config))
+}
+
+// Forward pass of the trained network over `x` in chunks of `eval_batch_size` rows
+// (None = the whole set at once), so metrics on large sets keep peak memory bounded.
+// One confusion matrix per output head is accumulated chunk by chunk alongside the probabilities.
+fn evaluate_batched(
+    x: &Array2<f64>,
+    y_true: &Array2<f64>,
+    weights: &ModelWeights,
+    config: &NetworkConfig,
+) -> (Array2<f64>, Vec<ConfusionMatrix>) {
+    let batch_size = config.eval_batch_size.unwrap_or(x.nrows()).max(1);
+    let rule = config.decision_rule();
+    let mut y_pred = Array2::zeros(y_true.raw_dim());
+    let mut matrices = vec![ConfusionMatrix::default(); y_true.ncols()];
+    for ((batch, labels), mut out) in x
+        .axis_chunks_iter(Axis(0), batch_size)
+        .zip(y_true.axis_chunks_iter(Axis(0), batch_size))
+        .zip(y_pred.axis_chunks_iter_mut(Axis(0), batch_size))
+    {
+        out.assign(&forward(batch, weights, config));
+        for (h, matrix) in matrices.iter_mut().enumerate() {
+            matrix.accumulate(out.slice(s![.., h..h + 1]), labels.slice(s![.., h..h + 1]), rule);
+        }
+    }
+    (y_pred, matrices)
+}
+
+// Evaluate the finished network on the training rows and pass the metrics to
+// the UI: the confusion matrix and everything derived from it for
+// classification, MSE and R² for regression. Returns the predictions and the
+// primary metric (accuracy in % or R²).
+fn report_final_metrics(
+    app: &Arc<Mutex<NeuralNetworkApp>>,
+    config: &NetworkConfig,
+    x: &Array2<f64>,
+    y_true: &Array2<f64>,
+    weights: &ModelWeights,
+    rng: &mut StdRng,
+) -> (Array2<f64>, f64) {
+    let (y_pred, matrices) = evaluate_batched(x, y_true, weights, config);
+    app.lock().unwrap().set_feature_importance(input_saliency(x, weights, config));
+    if config.task_type == TaskType::Regression {
+        let metrics = RegressionMetrics::from_predictions(head(&y_pred, 0), head(y_true, 0));
+        println!("Regression fit: MSE {:.6}, R² {:.4}", metrics.mse, metrics.r2);
+        app.lock().unwrap().set_regression_metrics(metrics);
+        return (y_pred, metrics.r2);
+    }
+
+    let accuracy = matrices[0].accuracy();
+    report_accuracy_interval(app, head(&y_pred, 0), head(y_true, 0), config.bootstrap_samples, config.decision_rule(), rng);
+    app.lock().unwrap().set_confusion_matrix(matrices[0]);
+    report_head_metrics(app, config, &matrices);
+    app.lock().unwrap().set_evaluated_samples(EvaluatedSamples {
+        features: x.clone(),
+        labels: y_true.column(0).to_vec(),
+        probabilities: y_pred.column(0).to_vec(),
+        rule: config.decision_rule(),
+    });
+    (y_pred, accuracy)
+}
+
+// Name each output head and pass its metrics to the UI (head 0 drives the main metrics)
+fn report_head_metrics(app: &Arc<Mutex<NeuralNetworkApp>>, config: &NetworkConfig, matrices: &[ConfusionMatrix]) {
+    let primary = match config.label_column {
+        Some(column) => format!("column {}", column),
+        None => "last column".to_string(),
+    };
+    let names = std::iter::once(primary)
+        .chain(config.load_options().extra_label_columns.into_iter().map(|column| format!("column {}", column)));
+    let metrics: Vec<(String, ConfusionMatrix)> = names.zip(matrices.iter().copied()).collect();
+    if metrics.len() > 1 {
+        for (name, matrix) in &metrics {
+            println!("Head ({}): accuracy {:.2}%, F1 {:.3}", name, matrix.accuracy(), matrix.f1());
+        }
+    }
+    app.lock().unwrap().set_head_metrics(metrics);
+}
+
+// Column `h` of a prediction or label matrix, i.e. one output head
+fn head(y: &Array2<f64>, h: usize) -> ArrayView2<'_, f64> {
+    y.slice(s![.., h..h + 1])
+}
+
+// Loss of the task: cross-entropy for classification, mean squared error for regression
+fn task_loss(config: &NetworkConfig, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> f64 {
+    match config.task_type {
+        TaskType::Classification => binary_cross_entropy(y_pred, y_true),
+        TaskType::Regression => mean_squared_error(y_pred, y_true),
+    }
+}
+
+// Per-epoch metric of the primary head: accuracy in % or R²
+fn task_metric(config: &NetworkConfig, y_pred: &Array2<f64>, y_true: &Array2<f64>) -> f64 {
+    match config.task_type {
+        TaskType::Classification => ConfusionMatrix::from_predictions(head(y_pred, 0), head(y_true, 0), config.decision_rule()).accuracy(),
+        TaskType::Regression => RegressionMetrics::from_predictions(head(y_pred, 0), head(y_true, 0)).r2,
+    }
+}
+
+// The output gradient stays `y_pred - y_true`, the gradient of half this loss
+fn mean_squared_error(y_pred: &Array2<f64>, y_true: &Array2<f64>) -> f64 {
+    (y_pred - y_true).mapv(|d| d * d).mean().unwrap_or(0.0)
 }
 
 fn binary_cross_entropy(y_pred: &Array2<f64>, y_true: &Array2<f64>) -> f64 {
@@ -35,236 +409,1719 @@ fn binary_cross_entropy(y_pred: &Array2<f64>, y_true: &Array2<f64>) -> f64 {
     -loss.mean().unwrap()
 }
 
-fn load_data(path: &str) -> Result<(Array2<f64>, Array2<f64>), Box<dyn Error>> {
-    // Check if file exists
-    if !std::path::Path::new(path).exists() {
-        return Err(format!("File not found: {}", path).into());
-    }
-    
-    // Baca beberapa baris pertama untuk mendeteksi delimiter
-    let file = std::fs::File::open(path)?;
-    let mut buf_reader = std::io::BufReader::new(file);
-    let mut first_line = String::new();
-    std::io::BufRead::read_line(&mut buf_reader, &mut first_line)?;
-    
-    // Deteksi delimiter: jika ada titik koma, gunakan titik koma, jika tidak gunakan koma
-    let delimiter = if first_line.contains(';') { b';' } else { b',' };
-    println!("Detected delimiter: '{}'", char::from(delimiter));
+/// Stops training once the monitored metric hasn't improved for `patience` epochs.
+/// The stop is never triggered before `min_epochs` epochs have run, so noisy
+/// early values can't end a run prematurely.
+struct EarlyStopping {
+    patience: usize,
+    min_epochs: usize,
+    mode: MonitorMode,
+    best_value: f64,
+    epochs_without_improvement: usize,
+}
+
+impl EarlyStopping {
+    fn new(patience: usize, min_epochs: usize, mode: MonitorMode) -> Self {
+        Self {
+            patience,
+            min_epochs,
+            mode,
+            best_value: match mode {
+                MonitorMode::Min => f64::INFINITY,
+                MonitorMode::Max => f64::NEG_INFINITY,
+            },
+            epochs_without_improvement: 0,
+        }
+    }
+
+    // Record the monitored value of `epoch` (0-based) and return true if training should stop
+    fn should_stop(&mut self, epoch: usize, value: f64) -> bool {
+        if self.mode.improves(value, self.best_value) {
+            self.best_value = value;
+            self.epochs_without_improvement = 0;
+        } else {
+            self.epochs_without_improvement += 1;
+        }
+
+        epoch + 1 >= self.min_epochs && self.epochs_without_improvement >= self.patience
+    }
+}
+
+/// Copy of the weights from the epoch with the best validation value of the
+/// monitored metric so far
+struct BestWeights {
+    epoch: usize,
+    value: f64,
+    weights: ModelWeights,
+}
+
+// Snapshot `model` when `value` improves on the best one in the direction of `mode`
+fn record_best_weights(best: &mut Option<BestWeights>, epoch: usize, value: f64, mode: MonitorMode, model: &ModelWeights) {
+    if best.as_ref().is_none_or(|best| mode.improves(value, best.value)) {
+        *best = Some(BestWeights { epoch, value, weights: model.clone() });
+    }
+}
+
+// The weights the run ends with: the best snapshot if there is one, else the last epoch's
+fn restore_best_weights(app: &Arc<Mutex<NeuralNetworkApp>>, config: &NetworkConfig, best: Option<BestWeights>, model: ModelWeights) -> ModelWeights {
+    let Some(best) = best else {
+        return model;
+    };
+    println!("Restoring the best weights from epoch {} (validation {} {:.4})", best.epoch + 1, config.monitor.label(), best.value);
+    app.lock().unwrap().set_best_epoch(Some((best.epoch, best.value)));
+    best.weights
+}
+
+// Value of `metric` for this epoch's predictions (accuracy in %). Regression
+// runs have neither accuracy, F1 nor AUC and monitor R² for all three. The AUC
+// is that of chance (0.5) while the labels hold a single class.
+fn monitored_value(metric: Metric, loss: f64, y_pred: &Array2<f64>, y_true: &Array2<f64>, config: &NetworkConfig) -> f64 {
+    match metric {
+        Metric::Loss => loss,
+        _ if config.task_type == TaskType::Regression => task_metric(config, y_pred, y_true),
+        Metric::Accuracy => task_metric(config, y_pred, y_true),
+        Metric::F1 => ConfusionMatrix::from_predictions(head(y_pred, 0), head(y_true, 0), config.decision_rule()).f1(),
+        Metric::Auc => {
+            let probabilities: Vec<f64> = head(y_pred, 0).iter().copied().collect();
+            let labels: Vec<f64> = head(y_true, 0).iter().copied().collect();
+            stats::roc_curve(&probabilities, &labels).map_or(0.5, |roc| roc.auc)
+        }
+    }
+}
+
+/// What `load_data` does with rows whose field count differs from the header
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum RaggedRows {
+    /// Stop with an error listing the offending lines
+    #[default]
+    Fail,
+    /// Skip the rows
+    Drop,
+    /// Fill missing trailing fields with 0 and cut extra ones, every field keeps its column
+    Pad,
+}
+
+/// What `load_data` does with empty or non-numeric cells of an otherwise complete row
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum MissingValues {
+    /// Leave the row out and count it
+    #[default]
+    Skip,
+    /// Replace feature cells with the mean of their column (rows with a bad label are still skipped)
+    FillMean,
+    /// Stop with an error naming the first bad cell
+    Error,
+}
+
+impl MissingValues {
+    pub const ALL: [MissingValues; 3] = [MissingValues::Skip, MissingValues::FillMean, MissingValues::Error];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MissingValues::Skip => "Skip Row",
+            MissingValues::FillMean => "Fill Mean",
+            MissingValues::Error => "Error",
+        }
+    }
+}
+
+impl RaggedRows {
+    pub const ALL: [RaggedRows; 3] = [RaggedRows::Fail, RaggedRows::Drop, RaggedRows::Pad];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RaggedRows::Fail => "Fail",
+            RaggedRows::Drop => "Drop",
+            RaggedRows::Pad => "Pad",
+        }
+    }
+}
+
+/// Options controlling how `load_data` turns CSV columns into features and labels
+#[derive(Clone, Default)]
+pub struct LoadOptions {
+    /// 0-based CSV column indices that are never used as features (e.g. ID columns)
+    pub ignored_columns: Vec<usize>,
+    /// 0-based CSV column of the primary label (None = the last column)
+    pub label_column: Option<usize>,
+    /// 0-based CSV columns used as additional binary targets, one output head each.
+    /// The label column is always the first (primary) head.
+    pub extra_label_columns: Vec<usize>,
+    pub ragged_rows: RaggedRows,
+    pub missing_values: MissingValues,
+    /// Field separator of the file (None = detected from the first line)
+    pub delimiter: Option<u8>,
+}
+
+impl LoadOptions {
+    /// CSV columns that become features of a file with `n_columns` columns, in feature order
+    pub fn feature_columns(&self, n_columns: usize) -> Vec<usize> {
+        let primary = self.label_column.unwrap_or(n_columns.saturating_sub(1));
+        (0..n_columns)
+            .filter(|c| !self.ignored_columns.contains(c) && !self.extra_label_columns.contains(c))
+            .filter(|&c| c != primary)
+            .collect()
+    }
+}
+
+// Gradients of the sigmoid + BCE output layer fed by `inputs` (the hidden
+// activations, or the raw features for the logistic-regression baseline).
+// Returns (dz, dW, db).
+fn output_gradients(
+    inputs: &Array2<f64>,
+    y_pred: &Array2<f64>,
+    y_true: &Array2<f64>,
+) -> (Array2<f64>, Array2<f64>, Array1<f64>) {
+    let n_samples = inputs.nrows() as f64;
+    let dz = y_pred - y_true;
+    let dw = inputs.t().dot(&dz) / n_samples;
+    let db = dz.sum_axis(Axis(0)) / n_samples;
+    (dz, dw, db)
+}
+
+// Parameter updates of the configured optimizer. SGD with momentum keeps a
+// velocity and Adam its moment estimates per matrix in numbered slots, which
+// persist across epochs.
+struct OptimizerState {
+    optimizer: Optimizer,
+    momentum: f64,
+    velocities: Vec<Array2<f64>>,
+    first_moments: Vec<Array2<f64>>,
+    second_moments: Vec<Array2<f64>>,
+    step: i32,
+}
+
+impl OptimizerState {
+    fn new(optimizer: Optimizer, momentum: f64) -> Self {
+        Self {
+            optimizer,
+            momentum,
+            velocities: Vec::new(),
+            first_moments: Vec::new(),
+            second_moments: Vec::new(),
+            step: 0,
+        }
+    }
+
+    // Start a new update step (Adam's bias correction counts these)
+    fn next_step(&mut self) {
+        self.step += 1;
+    }
+
+    fn update(&mut self, slot: usize, param: &mut Array2<f64>, grad: &Array2<f64>, learning_rate: f64) {
+        match self.optimizer {
+            Optimizer::Sgd if self.momentum == 0.0 => *param -= &(grad * learning_rate),
+            Optimizer::Sgd => {
+                while self.velocities.len() <= slot {
+                    self.velocities.push(Array2::zeros(param.raw_dim()));
+                }
+                let v = &mut self.velocities[slot];
+                *v = &*v * self.momentum - grad * learning_rate;
+                *param += &*v;
+            }
+            Optimizer::Adam { beta1, beta2, epsilon } => {
+                while self.first_moments.len() <= slot {
+                    self.first_moments.push(Array2::zeros(param.raw_dim()));
+                    self.second_moments.push(Array2::zeros(param.raw_dim()));
+                }
+                let m = &mut self.first_moments[slot];
+                let v = &mut self.second_moments[slot];
+                *m = &*m * beta1 + grad * (1.0 - beta1);
+                *v = &*v * beta2 + &grad.mapv(|g| g * g) * (1.0 - beta2);
+                let m_hat_scale = 1.0 / (1.0 - beta1.powi(self.step));
+                let v_hat_scale = 1.0 / (1.0 - beta2.powi(self.step));
+                ndarray::Zip::from(param).and(&*m).and(&*v).for_each(|p, &m, &v| {
+                    *p -= learning_rate * m * m_hat_scale / ((v * v_hat_scale).sqrt() + epsilon);
+                });
+            }
+        }
+    }
+}
+
+// Inverted dropout mask: 0 with probability `rate`, otherwise 1 / (1 - rate)
+// so the expected activation stays the same
+fn dropout_mask(shape: (usize, usize), rate: f64, rng: &mut StdRng) -> Array2<f64> {
+    let keep_scale = 1.0 / (1.0 - rate);
+    Array2::random_using(shape, Uniform::new(0.0, 1.0), rng)
+        .mapv(|u| if u < rate { 0.0 } else { keep_scale })
+}
+
+// What the backward pass needs of a batch-normalized layer: the normalized
+// pre-activations and 1 / std of the batch
+struct BatchNormCache {
+    normalized: Array2<f64>,
+    inv_std: Array2<f64>,
+}
+
+// Normalize pre-activations with the batch's own mean and variance, fold those
+// into the running statistics used at inference, then scale and shift
+fn batch_norm_forward(layer: &mut BatchNorm, z: &Array2<f64>) -> (Array2<f64>, BatchNormCache) {
+    let mean = z.mean_axis(Axis(0)).expect("batches have rows").insert_axis(Axis(0));
+    let centered = z - &mean;
+    let var = centered.mapv(|c| c * c).mean_axis(Axis(0)).expect("batches have rows").insert_axis(Axis(0));
+    let inv_std = var.mapv(|v| 1.0 / (v + BATCH_NORM_EPSILON).sqrt());
+    let normalized = &centered * &inv_std;
+    layer.running_mean = &layer.running_mean * BATCH_NORM_MOMENTUM + &mean * (1.0 - BATCH_NORM_MOMENTUM);
+    layer.running_var = &layer.running_var * BATCH_NORM_MOMENTUM + &var * (1.0 - BATCH_NORM_MOMENTUM);
+    (&normalized * &layer.gamma + &layer.beta, BatchNormCache { normalized, inv_std })
+}
+
+// Gradient with respect to the pre-activations given the one with respect to the
+// normalized values, through the batch mean and variance both depend on
+fn batch_norm_backward(d_normalized: &Array2<f64>, cache: &BatchNormCache) -> Array2<f64> {
+    let mean_d = d_normalized.mean_axis(Axis(0)).expect("batches have rows");
+    let mean_d_dot = (d_normalized * &cache.normalized).mean_axis(Axis(0)).expect("batches have rows");
+    (d_normalized - &mean_d - &(&cache.normalized * &mean_d_dot)) * &cache.inv_std
+}
+
+// One forward and backward pass over `x` followed by a parameter update.
+// Returns the predictions made before the update.
+fn train_step(
+    model: &mut ModelWeights,
+    optimizer: &mut OptimizerState,
+    x: &Array2<f64>,
+    y_true: &Array2<f64>,
+    config: &NetworkConfig,
+    learning_rate: f64,
+    rng: &mut StdRng,
+) -> Array2<f64> {
+    let n_samples = x.nrows();
+    let n_hidden = model.weights.len() - 1;
+    let skip_is_projection = x.ncols() != model.weights[0].ncols();
+    // Deterministic runs stay on one thread even when parallel is set
+    let par = config.parallel && !config.deterministic;
+
+    // Forward pass, keeping each hidden layer's input, pre-activation (after
+    // batch norm), activation output (without dropout and shortcut), dropout
+    // mask and batch norm cache for the backward pass. No mask is drawn at
+    // rate 0, so the rng is untouched.
+    let mut inputs = Vec::with_capacity(n_hidden);
+    let mut pre_activations = Vec::with_capacity(n_hidden);
+    let mut outputs = Vec::with_capacity(n_hidden);
+    let mut masks = Vec::with_capacity(n_hidden);
+    let mut norm_caches = Vec::with_capacity(n_hidden);
+    let mut a = x.clone();
+    for layer in 0..n_hidden {
+        let mut z = parallel::dot(a.view(), model.weights[layer].view(), par) + &model.biases[layer];
+        norm_caches.push(model.batch_norm.as_mut().map(|batch_norm| {
+            let (normalized, cache) = batch_norm_forward(&mut batch_norm[layer], &z);
+            z = normalized;
+            cache
+        }));
+        let h = activate(config.activation, &z, par);
+        let mask = (config.dropout_rate > 0.0).then(|| dropout_mask(h.dim(), config.dropout_rate, rng));
+        let mut next = match &mask {
+            Some(mask) => &h * mask,
+            None => h.clone(),
+        };
+        if let Some(skip) = model.skip.as_ref().filter(|_| layer == 0) {
+            next += &parallel::dot(x.view(), skip.view(), par);
+        }
+        inputs.push(std::mem::replace(&mut a, next));
+        pre_activations.push(z);
+        outputs.push(h);
+        masks.push(mask);
+    }
+    let z_out = parallel::dot(a.view(), model.weights[n_hidden].view(), par) + &model.biases[n_hidden];
+    let y_pred = output_activation(z_out, config, par);
+
+    // Backward pass from the output layer down, all gradients use the
+    // weights of this step and are applied afterwards
+    let (dz_out, dw_out, db_out) = output_gradients(&a, &y_pred, y_true);
+    let mut da = parallel::dot(dz_out.view(), model.weights[n_hidden].t(), par);
+    let mut gradients = vec![(dw_out, db_out.insert_axis(Axis(0)))];
+    let mut dskip = None;
+    let mut norm_gradients = Vec::new();
+    for layer in (0..n_hidden).rev() {
+        // The identity shortcut has no parameters, only the projection is learned
+        if layer == 0 && skip_is_projection && model.skip.is_some() {
+            dskip = Some(x.t().dot(&da) / n_samples as f64);
+        }
+        // Dropped units pass no gradient, kept ones carry the same scale as forward
+        if let Some(mask) = &masks[layer] {
+            da *= mask;
+        }
+        let mut dz = &da * &activation_deriv(config.activation, &pre_activations[layer], &outputs[layer], par);
+        if let (Some(cache), Some(batch_norm)) = (&norm_caches[layer], &model.batch_norm) {
+            norm_gradients.push((
+                ((&dz * &cache.normalized).sum_axis(Axis(0)) / n_samples as f64).insert_axis(Axis(0)),
+                (dz.sum_axis(Axis(0)) / n_samples as f64).insert_axis(Axis(0)),
+            ));
+            dz = batch_norm_backward(&(&dz * &batch_norm[layer].gamma), cache);
+        }
+        gradients.push((
+            inputs[layer].t().dot(&dz) / n_samples as f64,
+            (dz.sum_axis(Axis(0)) / n_samples as f64).insert_axis(Axis(0)),
+        ));
+        if layer > 0 {
+            da = parallel::dot(dz.view(), model.weights[layer].t(), par);
+        }
+    }
+    gradients.reverse();
+    norm_gradients.reverse();
+
+    // Slots: 2i for layer i's weights, 2i + 1 for its bias, then the skip
+    // projection, then the batch norm scale and shift of each hidden layer
+    optimizer.next_step();
+    for (layer, ((w, b), (mut dw, mut db))) in model.weights.iter_mut().zip(model.biases.iter_mut()).zip(gradients).enumerate() {
+        // Weight decay, the gradient of l2_penalty (biases are not decayed)
+        if config.l2_lambda > 0.0 {
+            dw.scaled_add(config.l2_lambda, w);
+        }
+        clip_gradient(&mut dw, config.grad_clip);
+        clip_gradient(&mut db, config.grad_clip);
+        optimizer.update(2 * layer, w, &dw, learning_rate);
+        optimizer.update(2 * layer + 1, b, &db, learning_rate);
+    }
+    if let (Some(skip), Some(mut dskip)) = (model.skip.as_mut(), dskip) {
+        clip_gradient(&mut dskip, config.grad_clip);
+        optimizer.update(2 * (n_hidden + 1), skip, &dskip, learning_rate);
+    }
+    if let Some(batch_norm) = model.batch_norm.as_mut() {
+        let first_slot = 2 * (n_hidden + 1) + 1;
+        for (layer, (norm, (mut dgamma, mut dbeta))) in batch_norm.iter_mut().zip(norm_gradients).enumerate() {
+            clip_gradient(&mut dgamma, config.grad_clip);
+            clip_gradient(&mut dbeta, config.grad_clip);
+            optimizer.update(first_slot + 2 * layer, &mut norm.gamma, &dgamma, learning_rate);
+            optimizer.update(first_slot + 2 * layer + 1, &mut norm.beta, &dbeta, learning_rate);
+        }
+    }
+    y_pred
+}
+
+// Scale `gradient` down to an L2 (Frobenius) norm of at most `max_norm`, keeping
+// its direction. A NaN norm is left alone, clipping can't repair it.
+fn clip_gradient(gradient: &mut Array2<f64>, max_norm: Option<f64>) {
+    let Some(max_norm) = max_norm else {
+        return;
+    };
+    let norm = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
+    if norm > max_norm {
+        *gradient *= max_norm / norm;
+    }
+}
+
+// L2 regularization term of the loss, lambda / 2 times the squared layer
+// weights (biases and the skip projection are not penalized)
+fn l2_penalty(model: &ModelWeights, l2_lambda: f64) -> f64 {
+    if l2_lambda == 0.0 {
+        return 0.0;
+    }
+    let squared: f64 = model.weights.iter().map(|w| w.iter().map(|v| v * v).sum::<f64>()).sum();
+    0.5 * l2_lambda * squared
+}
+
+// Weight matrices n_features -> hidden layers -> n_heads, drawn from N(0, 1)
+// and scaled for each layer's fan-in by the init scheme
+fn initial_weights(n_features: usize, hidden_layers: &[usize], n_heads: usize, scheme: InitScheme, rng: &mut StdRng) -> Vec<Array2<f64>> {
+    let sizes: Vec<usize> = std::iter::once(n_features)
+        .chain(hidden_layers.iter().copied())
+        .chain(std::iter::once(n_heads))
+        .collect();
+    sizes.windows(2)
+        .map(|pair| Array2::random_using((pair[0], pair[1]), StandardNormal, rng) * scheme.scale(pair[0]))
+        .collect()
+}
+
+// Baseline without hidden layer: the same output layer and update rule as
+// the network, trained directly on the features for the same epochs and
+// learning rate. Returns its accuracy (%) on the training rows.
+fn train_logistic_regression(x: &Array2<f64>, y_true: &Array2<f64>, config: &NetworkConfig) -> f64 {
+    let mut w = Array2::<f64>::zeros((x.ncols(), 1));
+    let mut b = Array2::<f64>::zeros((1, 1));
+
+    let mut y_pred = Array2::zeros(y_true.raw_dim());
+    for _ in 0..config.epochs {
+        y_pred = sigmoid(&clamp_logits(x.dot(&w) + &b, config.logit_clamp), false);
+        let (_, dw, db) = output_gradients(x, &y_pred, y_true);
+        w -= &(dw * config.learning_rate);
+        b -= &(db * config.learning_rate);
+    }
+
+    ConfusionMatrix::from_predictions(y_pred.view(), y_true.view(), config.decision_rule()).accuracy()
+}
+
+/// Display names of a label column's 0/1 codes, e.g. "no"/"yes" for a column of
+/// yes/no values, so outputs show the original labels instead of the codes
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassNames {
+    pub negative: String,
+    pub positive: String,
+}
+
+impl Default for ClassNames {
+    fn default() -> Self {
+        Self { negative: "0".to_string(), positive: "1".to_string() }
+    }
+}
+
+impl ClassNames {
+    pub fn name(&self, code: f64) -> &str {
+        if code >= 0.5 { &self.positive } else { &self.negative }
+    }
+}
+
+/// Row counts of a loaded dataset, so discarded or filled-in data is visible
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoadSummary {
+    /// Rows that made it into the dataset
+    pub rows: usize,
+    /// Rows left out for a bad field count, feature or label
+    pub skipped_rows: usize,
+    /// Feature cells replaced by their column mean
+    pub imputed_values: usize,
+}
+
+impl LoadSummary {
+    pub fn describe(&self) -> String {
+        let mut text = format!("Loaded {} rows, skipped {}", self.rows, self.skipped_rows);
+        if self.imputed_values > 0 {
+            text.push_str(&format!(", {} values filled with the column mean", self.imputed_values));
+        }
+        text
+    }
+}
+
+/// How far a dataset load has got, reported every `LOAD_PROGRESS_ROWS` records
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LoadProgress {
+    /// Records read so far in the current pass
+    pub rows: usize,
+    /// Share of the file's bytes the current pass has read
+    pub fraction: f64,
+    /// False in the first pass, which only checks the records
+    pub filling: bool,
+}
+
+impl LoadProgress {
+    pub fn describe(&self) -> String {
+        let pass = if self.filling { "reading" } else { "checking" };
+        format!("Loading: {} rows ({})", self.rows, pass)
+    }
+
+    // Share of the whole load done, the two passes counting half each
+    pub fn overall(&self) -> f64 {
+        (self.fraction + if self.filling { 1.0 } else { 0.0 }) / 2.0
+    }
+}
+
+// Features, one label column per output head, each head's class names, the row
+// counts and the names of all columns
+type Dataset = (Array2<f64>, Array2<f64>, Vec<ClassNames>, LoadSummary, Vec<String>);
+
+// Values of a label column seen in the first loading pass: whether any is a
+// number, and until then its distinct texts
+#[derive(Clone, Debug, Default)]
+struct LabelValues {
+    numeric: bool,
+    distinct: BTreeSet<String>,
+}
+
+impl LabelValues {
+    fn add(&mut self, value: &str, delimiter: u8) {
+        if self.numeric {
+            return;
+        }
+        if parse_number(value, delimiter).is_some() {
+            self.numeric = true;
+            self.distinct.clear();
+        } else if !self.distinct.contains(value) {
+            self.distinct.insert(value.to_string());
+        }
+    }
+}
+
+/// How the text of a label column becomes its value
+enum LabelCodec {
+    /// Parsed as a number, unparseable rows have no label
+    Numeric,
+    /// Class names, this one is 1 and the other 0
+    Classes(String),
+}
+
+impl LabelCodec {
+    fn code(&self, value: &str, delimiter: u8) -> Option<f64> {
+        match self {
+            LabelCodec::Numeric => parse_number(value, delimiter),
+            LabelCodec::Classes(positive) => Some(if value == positive { 1.0 } else { 0.0 }),
+        }
+    }
+}
+
+// Decide how one label column is decoded: numeric columns are used as they are
+// (unparseable rows are skipped), a column without any numeric value must hold
+// exactly two distinct values, mapped to 0/1 in sorted order (no = 0, yes = 1)
+fn decode_label_column(column: usize, values: LabelValues) -> Result<(LabelCodec, ClassNames), Box<dyn Error>> {
+    if values.numeric {
+        return Ok((LabelCodec::Numeric, ClassNames::default()));
+    }
+
+    if values.distinct.len() != 2 {
+        return Err(format!(
+            "Label column {} has {} distinct non-numeric values, only two classes are supported",
+            column, values.distinct.len()
+        ).into());
+    }
+    let mut classes = values.distinct.into_iter();
+    let (negative, positive) = (classes.next().unwrap_or_default(), classes.next().unwrap_or_default());
+    println!("Label column {}: '{}' = 0, '{}' = 1", column, negative, positive);
+    Ok((LabelCodec::Classes(positive.clone()), ClassNames { negative, positive }))
+}
+
+// Delimiter of a dataset line: the most frequent of semicolon, tab and pipe,
+// or a comma when none of them occurs. Commas lose even when more frequent, a
+// semicolon file may use them as decimal separators.
+fn detect_delimiter(line: &str) -> u8 {
+    [b';', b'\t', b'|'].into_iter()
+        .map(|delimiter| (line.bytes().filter(|&b| b == delimiter).count(), delimiter))
+        .filter(|&(count, _)| count > 0)
+        .max_by_key(|&(count, _)| count)
+        .map_or(b',', |(_, delimiter)| delimiter)
+}
+
+// Parse a numeric field. Semicolon-separated files come from locales that write
+// the decimal separator as a comma (3,14), so there a comma is read as a dot.
+fn parse_number(field: &str, delimiter: u8) -> Option<f64> {
+    if delimiter == b';' && field.contains(',') {
+        field.replace(',', ".").parse::<f64>().ok()
+    } else {
+        field.parse::<f64>().ok()
+    }
+}
+
+/// A dataset CSV opened for reading its records
+struct DatasetCsv {
+    reader: csv::Reader<std::fs::File>,
+    /// Detected delimiter, needed for parsing the fields
+    delimiter: u8,
+    /// Header names, or feature_0, feature_1, ... when the first row is already data
+    column_names: Vec<String>,
+    /// Where the first data record starts, for reading the records again
+    data_start: csv::Position,
+}
+
+// Open a dataset CSV with the given delimiter, or the detected one when None. A
+// first row of numbers only is data, not a header. Records may have any length,
+// callers compare them against the column count themselves.
+fn open_dataset_csv(path: &str, delimiter: Option<u8>) -> Result<DatasetCsv, Box<dyn Error>> {
+    // Check if file exists
+    if !std::path::Path::new(path).exists() {
+        return Err(format!("File not found: {}", path).into());
+    }
+    
+    // Baca beberapa baris pertama untuk mendeteksi delimiter
+    let file = std::fs::File::open(path)?;
+    let mut buf_reader = std::io::BufReader::new(file);
+    let mut first_line = String::new();
+    std::io::BufRead::read_line(&mut buf_reader, &mut first_line)?;
+    
+    let delimiter = match delimiter {
+        Some(delimiter) => delimiter,
+        None => {
+            let detected = detect_delimiter(&first_line);
+            println!("Detected delimiter: '{}'", char::from(detected).escape_default());
+            detected
+        }
+    };
+    let has_header = !first_line.trim().split(delimiter as char)
+        .all(|field| parse_number(field.trim(), delimiter).is_some());
+
+    let mut reader = ReaderBuilder::new()
+        .has_headers(has_header)
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_path(path)?;
+    // Without a header this is the first record, which stays in `records()`
+    let first_row = reader.headers()?;
+    let column_names = if has_header {
+        first_row.iter().map(|name| name.trim().to_string()).collect()
+    } else {
+        println!("No header row in {}, naming the columns feature_0 to feature_{}", path, first_row.len().saturating_sub(1));
+        (0..first_row.len()).map(|i| format!("feature_{}", i)).collect()
+    };
+    let data_start = if has_header { reader.position().clone() } else { csv::Position::new() };
+    Ok(DatasetCsv { reader, delimiter, column_names, data_start })
+}
+
+// Column names of a dataset, for naming and validating column indices in the UI
+pub fn dataset_column_names(path: &str, delimiter: Option<u8>) -> Result<Vec<String>, Box<dyn Error>> {
+    Ok(open_dataset_csv(path, delimiter)?.column_names)
+}
+
+/// File line numbers (the header is line 1) of rows whose field count differs
+/// from the header's, together with the header's field count
+pub fn find_ragged_rows(path: &str, delimiter: Option<u8>) -> Result<(usize, Vec<usize>), Box<dyn Error>> {
+    let DatasetCsv { reader: mut rdr, column_names, .. } = open_dataset_csv(path, delimiter)?;
+    let n_columns = column_names.len();
+    let mut lines = Vec::new();
+    for (i, result) in rdr.records().enumerate() {
+        let record = result?;
+        if record.len() != n_columns {
+            lines.push(record.position().map_or(i + 2, |p| p.line() as usize));
+        }
+    }
+    Ok((n_columns, lines))
+}
+
+// What became of one CSV record on its way into the dataset
+enum RecordOutcome {
+    /// Wrong field count under `RaggedRows::Fail`, at this file line
+    Ragged(usize),
+    /// Wrong field count under `RaggedRows::Drop`
+    Dropped,
+    /// A feature that isn't a number under `MissingValues::Skip`
+    Skipped,
+    /// No feature columns at all
+    Empty,
+    /// A dataset row, features that aren't a number are None
+    Row { line: usize, record: csv::StringRecord, features: Vec<Option<f64>> },
+}
+
+// Bring a record to the header's field count as `options.ragged_rows` says and
+// parse its feature columns, identically in both passes of `load_data`
+fn read_record(
+    index: usize,
+    mut record: csv::StringRecord,
+    n_columns: usize,
+    feature_columns: &[usize],
+    delimiter: u8,
+    options: &LoadOptions,
+) -> Result<RecordOutcome, Box<dyn Error>> {
+    if record.len() != n_columns {
+        match options.ragged_rows {
+            RaggedRows::Fail => {
+                return Ok(RecordOutcome::Ragged(record.position().map_or(index + 2, |p| p.line() as usize)));
+            }
+            RaggedRows::Drop => return Ok(RecordOutcome::Dropped),
+            RaggedRows::Pad => {
+                // The label may be any column, so fields are never shifted
+                let position = record.position().cloned();
+                let mut padded: csv::StringRecord = record.iter()
+                    .chain(std::iter::repeat("0"))
+                    .take(n_columns)
+                    .collect();
+                padded.set_position(position);
+                record = padded;
+            }
+        }
+    }
+
+    // Ignored columns are never parsed, so non-numeric IDs don't drop the row.
+    // Labels stay text here, they may be class names.
+    let line = record.position().map_or(index + 2, |p| p.line() as usize);
+    let features: Vec<Option<f64>> = feature_columns.iter()
+        .map(|&c| parse_number(record[c].trim(), delimiter))
+        .collect();
+    if features.is_empty() {
+        return Ok(RecordOutcome::Empty);
+    }
+    if let Some(k) = features.iter().position(Option::is_none) {
+        match options.missing_values {
+            MissingValues::Skip => return Ok(RecordOutcome::Skipped),
+            MissingValues::Error => {
+                let column = feature_columns[k];
+                return Err(format!(
+                    "Line {}: '{}' in column {} is not a number", line, record[column].trim(), column
+                ).into());
+            }
+            MissingValues::FillMean => {}
+        }
+    }
+    Ok(RecordOutcome::Row { line, record, features })
+}
+
+fn load_data(path: &str, options: &LoadOptions) -> Result<Dataset, Box<dyn Error>> {
+    load_data_with_progress(path, options, |_| true)?.ok_or_else(|| "Loading was canceled".into())
+}
+
+// Read the dataset in two passes so large files are never held as rows of
+// vectors: the first checks every record, counts the usable rows and collects
+// what decoding the labels needs, the second parses the rows again straight
+// into preallocated feature and label arrays. `on_progress` is called every
+// `LOAD_PROGRESS_ROWS` records and cancels the load (Ok(None)) by returning false.
+fn load_data_with_progress(
+    path: &str,
+    options: &LoadOptions,
+    mut on_progress: impl FnMut(LoadProgress) -> bool,
+) -> Result<Option<Dataset>, Box<dyn Error>> {
+    let DatasetCsv { reader: mut rdr, delimiter, column_names, data_start } = open_dataset_csv(path, options.delimiter)?;
+    let n_columns = column_names.len();
+
+    // The label column is always the primary label, extra heads add their own columns
+    let primary = options.label_column.unwrap_or(n_columns.saturating_sub(1));
+    if primary >= n_columns {
+        return Err(format!("Label column {} does not exist ({} columns)", primary, n_columns).into());
+    }
+    let mut label_columns = vec![primary];
+    for &column in &options.extra_label_columns {
+        if column >= n_columns {
+            return Err(format!("Label column {} does not exist ({} columns)", column, n_columns).into());
+        }
+        if label_columns.contains(&column) {
+            return Err(format!("Column {} is already a label", column).into());
+        }
+        label_columns.push(column);
+    }
+    if let Some(column) = label_columns.iter().find(|c| options.ignored_columns.contains(c)) {
+        return Err(format!("Column {} is a label and cannot be ignored", column).into());
+    }
+
+    let feature_columns = options.feature_columns(n_columns);
+    let n_heads = label_columns.len();
+    let total_bytes = std::fs::metadata(path)?.len().max(1) as f64;
+    let mut proceed = |rows: usize, record: &csv::StringRecord, filling: bool| {
+        let fraction = record.position().map_or(0.0, |p| p.byte() as f64 / total_bytes);
+        !rows.is_multiple_of(LOAD_PROGRESS_ROWS) || on_progress(LoadProgress { rows, fraction, filling })
+    };
+
+    let mut label_values = vec![LabelValues::default(); n_heads];
+    let mut n_candidates = 0;
+    let mut ragged_lines = Vec::new();
+    let mut dropped_rows = 0;
+    let mut skipped_rows = 0;
+    for (i, result) in rdr.records().enumerate() {
+        let record = result?;
+        if !proceed(i, &record, false) {
+            return Ok(None);
+        }
+        match read_record(i, record, n_columns, &feature_columns, delimiter, options)? {
+            RecordOutcome::Ragged(line) => ragged_lines.push(line),
+            RecordOutcome::Dropped => dropped_rows += 1,
+            RecordOutcome::Skipped => skipped_rows += 1,
+            RecordOutcome::Empty => {}
+            RecordOutcome::Row { record, .. } => {
+                n_candidates += 1;
+                for (values, &column) in label_values.iter_mut().zip(&label_columns) {
+                    values.add(record[column].trim(), delimiter);
+                }
+            }
+        }
+    }
+    
+    if !ragged_lines.is_empty() {
+        let shown = ragged_lines.iter().take(10).map(|l| l.to_string()).collect::<Vec<_>>().join(", ");
+        return Err(format!(
+            "{} rows do not have the header's {} fields (lines {}{})",
+            ragged_lines.len(), n_columns, shown, if ragged_lines.len() > 10 { ", ..." } else { "" }
+        ).into());
+    }
+    if dropped_rows > 0 {
+        println!("Dropped {} rows with a field count different from the header", dropped_rows);
+    }
+    if skipped_rows > 0 {
+        println!("Skipped {} rows with an empty or non-numeric feature", skipped_rows);
+    }
+    if n_candidates == 0 {
+        return Err(format!("No valid data found in {}", path).into());
+    }
+    
+    let mut codecs = Vec::new();
+    let mut class_names = Vec::new();
+    for (values, &column) in label_values.into_iter().zip(&label_columns) {
+        let (codec, names) = decode_label_column(column, values)?;
+        codecs.push(codec);
+        class_names.push(names);
+    }
+    
+    // Rows with an unreadable numeric label are skipped like rows with a bad feature
+    rdr.seek(data_start)?;
+    let mut features = Array2::<f64>::zeros((n_candidates, feature_columns.len()));
+    let mut labels = Array2::<f64>::zeros((n_candidates, n_heads));
+    let mut missing_cells = Vec::new();
+    let mut n_rows = 0;
+    let mut bad_labels = 0;
+    for (i, result) in rdr.records().enumerate() {
+        let record = result?;
+        if !proceed(i, &record, true) {
+            return Ok(None);
+        }
+        let RecordOutcome::Row { line, record, features: values } =
+            read_record(i, record, n_columns, &feature_columns, delimiter, options)? else {
+            continue;
+        };
+        let row_labels: Option<Vec<f64>> = codecs.iter().zip(&label_columns)
+            .map(|(codec, &column)| codec.code(record[column].trim(), delimiter))
+            .collect();
+        let Some(row_labels) = row_labels else {
+            if options.missing_values == MissingValues::Error {
+                return Err(format!("Line {}: the label is not a number", line).into());
+            }
+            bad_labels += 1;
+            continue;
+        };
+        for (j, value) in values.into_iter().enumerate() {
+            match value {
+                Some(value) => features[[n_rows, j]] = value,
+                None => missing_cells.push((n_rows, j)),
+            }
+        }
+        labels.row_mut(n_rows).assign(&Array1::from(row_labels));
+        n_rows += 1;
+    }
+    if bad_labels > 0 {
+        println!("Skipped {} rows with an unreadable label", bad_labels);
+    }
+    if n_rows == 0 {
+        return Err(format!("No valid data found in {}", path).into());
+    }
+    // Rows skipped for their label leave unused rows at the end
+    let mut features = features.slice_move(s![..n_rows, ..]);
+    let labels = labels.slice_move(s![..n_rows, ..]);
+    fill_column_means(&mut features, &missing_cells, &feature_columns)?;
+
+    println!("Successfully loaded dataset from {} with {} samples and {} features", 
+             path, n_rows, feature_columns.len());
+
+    let summary = LoadSummary {
+        rows: n_rows,
+        skipped_rows: dropped_rows + skipped_rows + bad_labels,
+        imputed_values: missing_cells.len(),
+    };
+    Ok(Some((features, labels, class_names, summary, column_names)))
+}
+
+// Replace the missing cells (still 0.0) with the mean of the parsed values in their column
+fn fill_column_means(features: &mut Array2<f64>, missing_cells: &[(usize, usize)], columns: &[usize]) -> Result<(), Box<dyn Error>> {
+    if missing_cells.is_empty() {
+        return Ok(());
+    }
+    let sums = features.sum_axis(Axis(0));
+    let mut counts = vec![features.nrows(); features.ncols()];
+    for &(_, j) in missing_cells {
+        counts[j] -= 1;
+    }
+
+    for &(i, j) in missing_cells {
+        if counts[j] == 0 {
+            return Err(format!("Column {} has no numeric values to take a mean of", columns[j]).into());
+        }
+        features[[i, j]] = sums[j] / counts[j] as f64;
+    }
+    println!("Filled {} missing values with their column mean", missing_cells.len());
+    Ok(())
+}
+
+// Read the numbers of a list file separated by whitespace or commas, naming the
+// list as `what` in errors. Lines starting with '#' are treated as comments.
+fn load_number_list<T: std::str::FromStr>(path: &str, what: &str) -> Result<Vec<T>, Box<dyn Error>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {} file {}: {}", what, path, e))?;
+
+    let mut values = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        for token in line.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+            let value = token.parse::<T>()
+                .map_err(|_| format!("Invalid {} '{}' in {}", what, token, path))?;
+            values.push(value);
+        }
+    }
+    Ok(values)
+}
+
+// Read 0-based data row indices (header excluded)
+fn load_row_indices(path: &str) -> Result<Vec<usize>, Box<dyn Error>> {
+    let indices = load_number_list(path, "row index")?;
+    if indices.is_empty() {
+        return Err(format!("Row index file {} contains no indices", path).into());
+    }
+
+    Ok(indices)
+}
+
+// Per-feature multipliers, one per feature column in order (same separators as the row index file)
+fn load_feature_weights(path: &str) -> Result<Vec<f64>, Box<dyn Error>> {
+    load_number_list(path, "feature weight")
+}
 
-    let mut rdr = ReaderBuilder::new()
-        .has_headers(true)
-        .delimiter(delimiter)
-        .from_path(path)?;
+// Scale each feature column by its multiplier; the vector must cover every feature
+fn apply_feature_weights(x: &mut Array2<f64>, weights: &[f64]) -> Result<(), Box<dyn Error>> {
+    if weights.len() != x.ncols() {
+        return Err(format!(
+            "Feature weight file has {} values but the dataset has {} features",
+            weights.len(), x.ncols()
+        ).into());
+    }
+    *x *= &Array1::from(weights.to_vec());
+    Ok(())
+}
 
-    let mut features: Vec<Vec<f64>> = Vec::new();
-    let mut labels: Vec<f64> = Vec::new();
+// Fit z-score statistics on the columns of `x` and standardize it in place
+fn normalize_features(x: &mut Array2<f64>) -> Normalization {
+    let means = x.mean_axis(Axis(0)).expect("training data has rows");
+    let stds = x.std_axis(Axis(0), 0.0).mapv(|s| if s > 0.0 { s } else { 1.0 });
+    let normalization = Normalization { means: means.to_vec(), stds: stds.to_vec() };
+    normalization.apply(x);
+    normalization
+}
 
-    for result in rdr.records() {
-        let record = result?;
-        let vals: Result<Vec<f64>, _> = record.iter().map(|s| s.trim().parse::<f64>()).collect();
-        if let Ok(vals) = vals {
-            if !vals.is_empty() {
-                let (x, y) = vals.split_at(vals.len() - 1);
-                features.push(x.to_vec());
-                labels.push(y[0]);
-            }
-        }
+// Row indices of the validation and the training share. Stratified, each
+// class of the primary label is shuffled and split on its own so both shares
+// keep the full dataset's class ratio, and the combined lists are shuffled
+// again so the training rows don't start out grouped by class.
+fn split_indices(y: &Array2<f64>, validation_split: f64, stratified: bool, rng: &mut StdRng) -> (Vec<usize>, Vec<usize>) {
+    let mut indices: Vec<usize> = (0..y.nrows()).collect();
+    if !stratified {
+        indices.shuffle(rng);
+        let n_val = (y.nrows() as f64 * validation_split).round() as usize;
+        let train = indices.split_off(n_val.min(indices.len()));
+        return (indices, train);
     }
 
-    // Check if we have any data
-    if features.is_empty() {
-        return Err(format!("No valid data found in {}", path).into());
+    let (mut validation, mut train) = (Vec::new(), Vec::new());
+    let (positives, negatives): (Vec<usize>, Vec<usize>) = indices.into_iter().partition(|&i| y[[i, 0]] >= 0.5);
+    for mut class in [negatives, positives] {
+        class.shuffle(rng);
+        let n_val = (class.len() as f64 * validation_split).round() as usize;
+        train.extend(class.split_off(n_val));
+        validation.extend(class);
     }
+    validation.shuffle(rng);
+    train.shuffle(rng);
+    (validation, train)
+}
 
-    // Check if all feature vectors have the same length
-    let feature_len = features[0].len();
-    if features.iter().any(|f| f.len() != feature_len) {
-        return Err("Inconsistent feature dimensions in dataset".into());
+// Keep only the given rows of the features and labels
+fn select_rows(
+    x: &Array2<f64>,
+    y: &Array2<f64>,
+    indices: &[usize],
+) -> Result<(Array2<f64>, Array2<f64>), Box<dyn Error>> {
+    let n_samples = x.nrows();
+    if let Some(&bad) = indices.iter().find(|&&i| i >= n_samples) {
+        return Err(format!("Row index {} is out of range (dataset has {} rows)", bad, n_samples).into());
     }
 
-    let feature_array = Array2::from_shape_vec((features.len(), features[0].len()), features.concat())?;
-    let label_array = Array2::from_shape_vec((labels.len(), 1), labels)?;
+    Ok((x.select(Axis(0), indices), y.select(Axis(0), indices)))
+}
 
-    println!("Successfully loaded dataset from {} with {} samples and {} features", 
-             path, features.len(), feature_len);
+// Check the loaded data against the network before any matrix product, so a
+// dataset switched after preview fails with a message instead of an ndarray panic
+fn validate_training_shapes(x: &Array2<f64>, y: &Array2<f64>, hidden_layers: &[usize]) -> Result<(), Box<dyn Error>> {
+    let (n_samples, n_features) = x.dim();
+    if n_samples == 0 {
+        return Err("Dataset has no rows to train on".into());
+    }
+    if n_features == 0 {
+        return Err("Dataset has no feature columns left to train on".into());
+    }
+    if y.nrows() != n_samples {
+        return Err(format!("Dataset has {} feature rows but {} labels", n_samples, y.nrows()).into());
+    }
+    if y.ncols() == 0 {
+        return Err("Dataset has no label column".into());
+    }
+    if hidden_layers.is_empty() {
+        return Err("At least one hidden layer is required".into());
+    }
+    if hidden_layers.contains(&0) {
+        return Err("Hidden layer sizes must be at least 1".into());
+    }
+    Ok(())
+}
+
+/// File format of the saved loss figure
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlotFormat {
+    Png,
+    /// Vector output for publication figures
+    Svg,
+}
 
-    Ok((feature_array, label_array))
+impl PlotFormat {
+    pub fn path(self) -> &'static str {
+        match self {
+            PlotFormat::Png => "result/lossfigure.png",
+            PlotFormat::Svg => "result/lossfigure.svg",
+        }
+    }
 }
 
-fn plot_loss(losses: &[f64], epochs: usize) -> Result<(), Box<dyn Error>> {
+pub fn plot_loss(job: &PlotJob, format: PlotFormat) -> Result<(), Box<dyn Error>> {
     // Create result directory if it doesn't exist
     std::fs::create_dir_all("result")?;
 
-    let root = BitMapBackend::new("result/lossfigure.png", (640, 480)).into_drawing_area();
+    // Loss on top, accuracy below
+    let size = (640, 720);
+    match format {
+        PlotFormat::Png => draw_loss_chart(BitMapBackend::new(format.path(), size).into_drawing_area(), job),
+        PlotFormat::Svg => draw_loss_chart(SVGBackend::new(format.path(), size).into_drawing_area(), job),
+    }
+}
+
+// The loss figure, drawn the same way on every backend
+fn draw_loss_chart<DB: DrawingBackend>(root: DrawingArea<DB, plotters::coord::Shift>, job: &PlotJob) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
     root.fill(&WHITE)?;
+    let panels = root.split_evenly((2, 1));
 
-    let max_loss = losses.iter().cloned().fold(f64::NAN, f64::max);
-    let mut chart = ChartBuilder::on(&root)
+    // The y-axis starts at 0 like the live chart in the UI. Diverged epochs
+    // (NaN or inf) are left out, an infinite range would never finish drawing.
+    let max_loss = job.losses.iter().cloned().filter(|loss| loss.is_finite()).fold(1e-6, f64::max);
+    let mut chart = ChartBuilder::on(&panels[0])
         .caption("Training Loss", ("sans-serif", 30))
         .margin(20)
         .x_label_area_size(30)
         .y_label_area_size(40)
-        .build_cartesian_2d(0..epochs, 0.0..max_loss)?;
+        .build_cartesian_2d(0..job.epochs, 0.0..max_loss)?;
 
     chart.configure_mesh().draw()?;
 
     chart.draw_series(LineSeries::new(
-        losses.iter().enumerate().map(|(i, &loss)| (i, loss)),
+        job.losses.iter().enumerate()
+            .filter(|(_, loss)| loss.is_finite())
+            .map(|(i, &loss)| (job.first_loss_epoch + i, loss)),
         &RED,
     ))?;
 
+    // Accuracy on a fixed 0..100 axis, R² up to 1 (and below 0 if it got there)
+    let (caption, y_range) = match job.task_type {
+        TaskType::Classification => ("Accuracy (%)", 0.0..100.0),
+        TaskType::Regression => ("R²", job.accuracies.iter().map(|&(_, r2)| r2).filter(|r2| r2.is_finite()).fold(0.0, f64::min)..1.0),
+    };
+    let mut chart = ChartBuilder::on(&panels[1])
+        .caption(caption, ("sans-serif", 30))
+        .margin(20)
+        .x_label_area_size(30)
+        .y_label_area_size(40)
+        .build_cartesian_2d(0..job.epochs, y_range)?;
+
+    chart.configure_mesh().draw()?;
+
+    chart.draw_series(LineSeries::new(
+        job.accuracies.iter().copied().filter(|(_, accuracy)| accuracy.is_finite()),
+        &BLUE,
+    ))?;
+
+    root.present()?;
     Ok(())
 }
 
+// JSON has no NaN/inf, so non-finite values are written as null
+fn json_number(value: f64) -> String {
+    if value.is_finite() { value.to_string() } else { "null".to_string() }
+}
+
+/// Machine-readable progress stream (one JSON object per logged epoch, or per
+/// epoch with a live history limit and a null accuracy in between) that
+/// external dashboards can tail while training runs. Runs append to the same
+/// file, each starting with a `run_start` object holding the start time, the
+/// dataset and the config.
+struct ProgressLog {
+    file: std::fs::File,
+}
+
+impl ProgressLog {
+    fn open(path: &str, dataset_path: &str, config: &NetworkConfig) -> Result<Self, Box<dyn Error>> {
+        std::fs::create_dir_all("result")?;
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let started = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+        writeln!(
+            file,
+            "{{\"run_start\":{},\"dataset\":{},\"config\":{}}}",
+            started, serde_json::to_string(dataset_path)?, serde_json::to_string(config)?
+        )?;
+        file.flush()?;
+        Ok(Self { file })
+    }
+
+    fn write(&mut self, epoch: usize, loss: f64, accuracy: f64, learning_rate: f64) -> std::io::Result<()> {
+        writeln!(
+            self.file,
+            "{{\"epoch\":{},\"loss\":{},\"accuracy\":{},\"lr\":{}}}",
+            epoch, json_number(loss), json_number(accuracy), json_number(learning_rate)
+        )?;
+        self.file.flush()
+    }
+}
+
+/// Curves of the saved figure, also handed to the background plotting thread
+pub struct PlotJob {
+    pub losses: Vec<f64>,
+    /// Epoch of `losses[0]`
+    pub first_loss_epoch: usize,
+    /// (epoch, accuracy or R²) at the epochs it was measured
+    pub accuracies: Vec<(usize, f64)>,
+    /// Length of the x axis
+    pub epochs: usize,
+    pub task_type: TaskType,
+}
+
+// Write the figure and report the outcome to the UI. Saving is best-effort:
+// a failure is reported but never aborts the run.
+fn render_loss_plot(app: &Arc<Mutex<NeuralNetworkApp>>, job: &PlotJob) {
+    let path = PlotFormat::Png.path();
+    match plot_loss(job, PlotFormat::Png) {
+        Ok(()) => {
+            println!("Loss plot saved to {}", path);
+            app.lock().unwrap().set_plot_status(&format!("Loss plot saved to {}", path));
+        }
+        Err(e) => {
+            eprintln!("Warning: could not save loss plot: {}", e);
+            app.lock().unwrap().set_plot_status("Loss plot was not saved");
+            app.lock().unwrap().show_toast(&format!("Loss plot was not saved: {}", e));
+        }
+    }
+}
+
+// Plotting thread so the training thread can report completion immediately
+fn spawn_plot_worker(app: Arc<Mutex<NeuralNetworkApp>>) -> mpsc::Sender<PlotJob> {
+    let (tx, rx) = mpsc::channel::<PlotJob>();
+    thread::spawn(move || {
+        for job in rx {
+            render_loss_plot(&app, &job);
+        }
+    });
+    tx
+}
+
+// `losses` holds every epoch from epoch 0, the figure starts at the metrics
+// start epoch like the charts
+fn save_loss_plot(
+    app: &Arc<Mutex<NeuralNetworkApp>>,
+    plot_tx: &mpsc::Sender<PlotJob>,
+    background: bool,
+    losses: &[f64],
+    epochs: usize,
+) {
+    // Accuracy comes from the progress updates, with the queued epochs applied first
+    let job = {
+        let app = app.lock().unwrap();
+        app.drain_progress();
+        let data_ref = app.get_training_data();
+        let data = data_ref.lock().unwrap();
+        let first_loss_epoch = data.run_config.as_ref().map_or(0, |config| config.metrics_start_epoch).min(losses.len());
+        PlotJob {
+            losses: losses[first_loss_epoch..].to_vec(),
+            first_loss_epoch,
+            accuracies: data.measured_accuracies.clone(),
+            epochs,
+            task_type: data.run_config.as_ref().map_or(TaskType::Classification, |config| config.task_type),
+        }
+    };
+    if background {
+        app.lock().unwrap().set_plot_status("Saving loss plot...");
+        // Worker is gone, fall back to plotting here
+        if let Err(mpsc::SendError(job)) = plot_tx.send(job) {
+            render_loss_plot(app, &job);
+        }
+    } else {
+        render_loss_plot(app, &job);
+    }
+}
+
+// Bootstrap confidence interval for the final accuracy (skipped when n_resamples
+// is 0) and the accuracy within each true class
+fn report_accuracy_interval(
+    app: &Arc<Mutex<NeuralNetworkApp>>,
+    y_pred: ArrayView2<f64>,
+    y_true: ArrayView2<f64>,
+    n_resamples: usize,
+    rule: DecisionRule,
+    rng: &mut StdRng,
+) {
+    // (true class, predicted right) of every sample
+    let outcomes: Vec<(bool, bool)> = y_pred
+        .iter()
+        .zip(y_true.iter())
+        .map(|(&p, &y)| (y >= 0.5, rule.predict(p) == (y >= 0.5)))
+        .collect();
+    let correct: Vec<bool> = outcomes.iter().map(|&(_, correct)| correct).collect();
+    let interval = stats::bootstrap_accuracy(&correct, n_resamples, rng);
+    if let Some(ci) = interval {
+        println!("Bootstrap accuracy: {:.2}% (95% CI {:.2}% - {:.2}%)", ci.mean, ci.lower, ci.upper);
+    }
+    let class_accuracy = ClassAccuracy::from_outcomes(outcomes);
+    let format_class = |class: usize| class_accuracy.accuracy(class).map_or("-".to_string(), |accuracy| format!("{:.2}%", accuracy));
+    println!("Accuracy by class: class 0 {} | class 1 {}", format_class(0), format_class(1));
+    let app = app.lock().unwrap();
+    app.set_accuracy_interval(interval);
+    app.set_class_accuracy(class_accuracy);
+}
+
+// Block the training thread while the run is paused, returning how long it waited
+fn wait_while_paused(training_data: &Mutex<TrainingData>, pause_changed: &Condvar, stop_flag: &AtomicBool) -> std::time::Duration {
+    let mut data = training_data.lock().unwrap();
+    if !data.paused {
+        return std::time::Duration::ZERO;
+    }
+    let began = std::time::Instant::now();
+    println!("Training paused");
+    // The timeout only guards against a missed notification
+    while data.paused && !stop_flag.load(Ordering::Relaxed) {
+        data = pause_changed.wait_timeout(data, std::time::Duration::from_millis(100)).unwrap().0;
+    }
+    println!("Training resumed");
+    began.elapsed()
+}
+
+// Run the observers of a logged epoch and apply what they asked for: the
+// learning rate of the next epochs and a snapshot of `model` in `snapshot_dir`.
+// Returns whether one of them requested a stop.
+fn notify_observers(
+    app: &Arc<Mutex<NeuralNetworkApp>>,
+    observers: &[observer::EpochObserver],
+    event: &observer::EpochEvent,
+    learning_rate: &mut f64,
+    model: &ModelWeights,
+    snapshot_dir: &str,
+) -> bool {
+    let mut control = observer::EpochControl::new(*learning_rate);
+    for observer in observers {
+        (observer.lock().unwrap())(event, &mut control);
+    }
+    if control.learning_rate != *learning_rate {
+        println!("Learning rate changed to {} at epoch {}", control.learning_rate, event.epoch + 1);
+        *learning_rate = control.learning_rate;
+    }
+    if control.snapshot_requested() {
+        let path = model::snapshot_path(snapshot_dir, event.epoch);
+        match app.lock().unwrap().save_snapshot(&path, model) {
+            Ok(()) => println!("Snapshot of epoch {} saved to {}", event.epoch + 1, path),
+            Err(e) => eprintln!("Warning: could not save the snapshot {}: {}", path, e),
+        }
+    }
+    control.stop_requested()
+}
+
 fn train_neural_network(
     app: Arc<Mutex<NeuralNetworkApp>>,
+    plot_tx: mpsc::Sender<PlotJob>,
 ) -> Result<(), Box<dyn Error>> {
     // Get configuration
-    let (config, dataset_path) = {
+    let (config, dataset_path, resume) = {
         let app_locked = app.lock().unwrap();
         let config_ref = app_locked.get_network_config();
         let config = config_ref.lock().unwrap().clone();
         
         let data_ref = app_locked.get_training_data();
-        let data = data_ref.lock().unwrap();
+        let mut data = data_ref.lock().unwrap();
         let dataset_path = data.dataset_path.clone();
+        data.run_config = Some(config.clone());
+        
+        // "Continue" requests extend the previous run from its retained weights
+        // and its full loss series, which the charts may only hold part of
+        let resume = match (data.resume_epochs.take(), data.trained_weights.clone()) {
+            (Some(extra), Some(weights)) => Some((extra, weights, data.epochs_run, std::mem::take(&mut data.loss_record))),
+            _ => None,
+        };
         
-        (config, dataset_path)
+        (config, dataset_path, resume)
     };
     
-    let epochs = config.epochs;
-    let hidden_size = config.hidden_size;
-    let learning_rate = config.learning_rate;
+    let start_epoch = resume.as_ref().map_or(0, |(_, _, epochs_run, _)| *epochs_run);
+    let epochs = match &resume {
+        Some((extra, _, epochs_run, _)) => epochs_run + extra,
+        None => config.epochs,
+    };
+    let hidden_layers = config.hidden_layers.clone();
+    let mut learning_rate = config.learning_rate;
     
-    println!("Starting training with: Epochs={}, Hidden Size={}, Learning Rate={}", 
-             epochs, hidden_size, learning_rate);
+    println!("Starting training with: Epochs={}, Hidden Layers=[{}], Learning Rate={}", 
+             epochs, frontend_new::format_hidden_layers(&hidden_layers), learning_rate);
     println!("Using dataset: {}", dataset_path);
     
-    let (x, y_true) = load_data(&dataset_path)?;
-    let (n_samples, n_features) = x.dim();
+    // The load reports its progress to the UI and stops early on a stop request,
+    // like the epoch loop below
+    let stop_flag = app.lock().unwrap().stop_flag();
+    let load_options = config.load_options();
+    let loaded = load_data_with_progress(&dataset_path, &load_options, |progress| {
+        app.lock().unwrap().set_load_progress(Some(progress));
+        !stop_flag.load(Ordering::Relaxed)
+    });
+    app.lock().unwrap().set_load_progress(None);
+    let Some((mut x, mut y_true, class_names, summary, column_names)) = loaded? else {
+        println!("Loading {} canceled", dataset_path);
+        app.lock().unwrap().training_canceled();
+        return Ok(());
+    };
+    app.lock().unwrap().set_class_names(class_names[0].clone());
+    app.lock().unwrap().set_load_summary(summary);
+    app.lock().unwrap().set_column_names(column_names, &load_options);
+    
+    if !config.row_index_file.trim().is_empty() {
+        let indices = load_row_indices(config.row_index_file.trim())?;
+        (x, y_true) = select_rows(&x, &y_true, &indices)?;
+        println!("Using {} selected rows from {}", indices.len(), config.row_index_file.trim());
+    }
+    if !config.feature_weights_file.trim().is_empty() {
+        let weights = load_feature_weights(config.feature_weights_file.trim())?;
+        apply_feature_weights(&mut x, &weights)?;
+        println!("Scaled features by the weights in {}", config.feature_weights_file.trim());
+    }
+    validate_training_shapes(&x, &y_true, &hidden_layers)?;
+    let n_heads = y_true.ncols();
+    if n_heads > 1 {
+        println!("Training {} output heads on a shared hidden layer", n_heads);
+    }
+    
+    app.lock().unwrap().set_feature_correlations(stats::feature_label_correlations(&x, &y_true));
+    
+    // Zero-variance columns carry no information, flag them and optionally drop them
+    let constant = stats::constant_columns(&x);
+    let mut dropped = Vec::new();
+    if !constant.is_empty() {
+        let list = constant.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ");
+        if config.drop_constant_features {
+            if constant.len() == x.ncols() {
+                return Err("All feature columns are constant, nothing left to train on".into());
+            }
+            let keep: Vec<usize> = (0..x.ncols()).filter(|i| !constant.contains(i)).collect();
+            x = x.select(Axis(1), &keep);
+            println!("Dropped constant features: {}", list);
+            dropped = constant;
+        } else {
+            eprintln!("Warning: constant features {} carry no information", list);
+            app.lock().unwrap().show_toast(&format!(
+                "Features {} are constant. Enable \"Drop Constant Features\" to remove them.", list
+            ));
+        }
+    }
+    app.lock().unwrap().set_dropped_features(dropped);
+
+    // A configured seed, or the fixed one in deterministic mode, makes the weight
+    // init, shuffles and split repeat across runs
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None if config.deterministic => StdRng::seed_from_u64(DETERMINISTIC_SEED),
+        None => StdRng::from_rng(thread_rng())?,
+    };
+    if let Some(seed) = config.seed {
+        println!("Random seed: {}", seed);
+    }
 
-    let mut rng = thread_rng();
-    let mut w1 = Array2::random_using((n_features, hidden_size), StandardNormal, &mut rng);
-    let mut b1 = Array2::zeros((1, hidden_size));
-    let mut w2 = Array2::random_using((hidden_size, 1), StandardNormal, &mut rng);
-    let mut b2 = Array2::zeros((1, 1));
+    // Hold out a shuffled share of the rows to watch for overfitting, drawn
+    // from the run's rng so deterministic runs always get the same split.
+    // Regression labels have no classes to stratify by.
+    let mut validation = if config.validation_split > 0.0 {
+        let stratified = config.stratified && config.task_type == TaskType::Classification;
+        let (val_rows, train_rows) = split_indices(&y_true, config.validation_split, stratified, &mut rng);
+        let n_val = val_rows.len();
+        if n_val == 0 || train_rows.is_empty() {
+            return Err(format!(
+                "A validation split of {} leaves no rows for training or validation ({} rows)",
+                config.validation_split, x.nrows()
+            ).into());
+        }
+        let validation = select_rows(&x, &y_true, &val_rows)?;
+        (x, y_true) = select_rows(&x, &y_true, &train_rows)?;
+        let kind = if stratified { ", stratified by class" } else { "" };
+        println!("Holding out {} rows for validation{}, training on {}", n_val, kind, x.nrows());
+        Some(validation)
+    } else {
+        None
+    };
+    
+    // Standardize with the training rows' statistics, held-out rows reuse them
+    let normalization = config.normalize_features.then(|| normalize_features(&mut x));
+    if let (Some(normalization), Some((x_val, _))) = (&normalization, validation.as_mut()) {
+        normalization.apply(x_val);
+    }
+    app.lock().unwrap().set_normalization(normalization);
+    let (n_samples, n_features) = x.dim();
 
+    let import_dir = config.weights_import_dir.trim();
+    // Every epoch's loss from epoch 0, whatever the live charts keep
     let mut losses = Vec::new();
+    let mut model = if let Some((_, weights, _, previous_losses)) = resume {
+        // Continue from the weights the last run ended with
+        let restart_hint = |e: String| format!("Cannot continue, configuration changed since the last run ({}). Restart training instead.", e);
+        weights.validate_shapes(n_features, &hidden_layers, n_heads).map_err(|e| restart_hint(e.to_string()))?;
+        if weights.skip.is_some() != config.skip_connection {
+            return Err(restart_hint("skip connection toggled".to_string()).into());
+        }
+        if weights.batch_norm.is_some() != config.batch_norm {
+            return Err(restart_hint("batch norm toggled".to_string()).into());
+        }
+        losses = previous_losses;
+        weights
+    } else if !import_dir.is_empty() {
+        // Start from weights exported by this app or another tool
+        let mut imported = ModelWeights::import_csv(import_dir)?;
+        imported.validate_shapes(n_features, &hidden_layers, n_heads)?;
+        if !config.skip_connection {
+            imported.skip = None;
+        }
+        imported
+    } else {
+        // Optionally start each output at its base rate, logit(positive rate), so
+        // skewed datasets don't spend the first epochs just learning the prior
+        let mut output_bias = Array2::zeros((1, n_heads));
+        if config.prior_bias_init {
+            for (bias, labels) in output_bias.iter_mut().zip(y_true.columns()) {
+                let positive_rate = labels.iter().filter(|&&y| y >= 0.5).count() as f64 / n_samples as f64;
+                let p = positive_rate.clamp(1e-6, 1.0 - 1e-6);
+                *bias = (p / (1.0 - p)).ln();
+                println!("Output bias initialized to the class prior {:.3} (logit {:.3})", positive_rate, *bias);
+            }
+        }
+        let weights = initial_weights(n_features, &hidden_layers, n_heads, config.init_scheme, &mut rng);
+        let mut biases: Vec<Array2<f64>> = hidden_layers.iter().map(|&size| Array2::zeros((1, size))).collect();
+        biases.push(output_bias);
+        ModelWeights { weights, biases, skip: None, batch_norm: None }
+    };
+    
+    // Residual path from the input to the first hidden output: the identity when
+    // the sizes match, otherwise a learned n_features x h1 projection
+    let first_hidden = hidden_layers[0];
+    let skip_is_projection = n_features != first_hidden;
+    if model.skip.is_none() && config.skip_connection {
+        model.skip = Some(if skip_is_projection {
+            Array2::random_using((n_features, first_hidden), StandardNormal, &mut rng)
+        } else {
+            Array2::eye(n_features)
+        });
+    }
+    if model.skip.is_some() {
+        if skip_is_projection {
+            println!("Skip connection: learned {}x{} projection", n_features, first_hidden);
+        } else {
+            println!("Skip connection: identity");
+        }
+    }
 
-    let mut final_pred = Array2::zeros((n_samples, 1));
+    // Batch norm starts as the identity (imported weights have none either)
+    if model.batch_norm.is_none() && config.batch_norm {
+        model.batch_norm = Some(hidden_layers.iter().map(|&size| BatchNorm::new(size)).collect());
+    }
 
-    for epoch in 0..epochs {
-        // Check if training should be stopped, only check for confirmed stop
-        let should_stop = {
-            let app_lock = app.lock().unwrap();
-            let data_ref = app_lock.get_training_data();
-            let data = data_ref.lock().unwrap();
-            data.should_stop
-        };
+    // Continued runs start with fresh Adam moments
+    let mut optimizer = OptimizerState::new(config.optimizer, config.momentum);
+    
+    // 0 or anything at least the dataset size trains on the full batch
+    let batch_size = if config.batch_size == 0 { n_samples } else { config.batch_size.min(n_samples) };
+    let mut indices: Vec<usize> = (0..n_samples).collect();
+    
+    let mut early_stopping = config.early_stopping
+        .then(|| EarlyStopping::new(config.patience, config.min_epochs, config.mode));
+    let mut best_weights = None;
+    
+    let mut started_at = std::time::Instant::now();
+    app.lock().unwrap().set_target_epochs(start_epoch, epochs);
+    
+    // Registered observers plus the built-in policies enabled in the config
+    let mut observers = app.lock().unwrap().epoch_observers();
+    if let Some(factor) = config.divergence_factor {
+        observers.push(observer::divergence_guard(factor));
+    }
+    
+    // With a live history limit the progress log holds the full record
+    let mut progress_log = if config.progress_log || config.live_history_limit.is_some() {
+        Some(ProgressLog::open(PROGRESS_LOG_PATH, &dataset_path, &config)?)
+    } else {
+        None
+    };
+    
+    // Progress goes to the UI over a channel, stop requests come back through `stop_flag`
+    let (training_data, pause_signal) = {
+        let app_lock = app.lock().unwrap();
+        (app_lock.get_training_data(), app_lock.pause_signal())
+    };
+
+    for epoch in start_epoch..epochs {
+        // Time spent paused doesn't count toward the time limit
+        started_at += wait_while_paused(&training_data, &pause_signal, &stop_flag);
         
-        if should_stop {
+        // Check if training should be stopped, only check for confirmed stop
+        if stop_flag.load(Ordering::Relaxed) {
             println!("Training stopped early at epoch {}/{}", epoch, epochs);
             
             // Jika sudah ada beberapa epoch yang selesai, kita bisa menghitung akurasi
             if epoch > 0 {
-                // Calculate final accuracy based on the current weights
-                let z1 = x.dot(&w1) + &b1;
-                let a1 = relu(&z1);
-                let z2 = a1.dot(&w2) + &b2;
-                let y_pred = sigmoid(&z2);
+                // Calculate final accuracy based on the current (or best) weights
+                let weights = restore_best_weights(&app, &config, best_weights, model);
+                let (_, accuracy) = report_final_metrics(&app, &config, &x, &y_true, &weights, &mut rng);
                 
-                let predictions = y_pred.mapv(|v| if v >= 0.5 { 1.0 } else { 0.0 });
-                let correct = predictions
-                    .iter()
-                    .zip(y_true.iter())
-                    .filter(|(p, y)| (*p - *y).abs() < 1e-6)
-                    .count();
-                let accuracy = (correct as f64 / n_samples as f64) * 100.0;
+                // Keep the weights so they can be exported
+                app.lock().unwrap().set_trained_weights(weights);
                 
                 // Mark training as completed with the current accuracy
                 app.lock().unwrap().training_completed(accuracy);
+                app.lock().unwrap().save_run_history();
                 
                 // Save the current loss plot
                 if !losses.is_empty() {
-                    plot_loss(&losses, epochs)?;
+                    save_loss_plot(&app, &plot_tx, config.background_plotting, &losses, epochs);
                 }
+                app.lock().unwrap().set_loss_record(losses);
             } else {
                 // Jika belum ada epoch yang selesai, tandai sebagai tidak selesai
-                let app_lock = app.lock().unwrap();
-                let data_ref = app_lock.get_training_data();
-                let mut data = data_ref.lock().unwrap();
-                data.training_in_progress = false;
-                data.completed = false;
+                app.lock().unwrap().training_canceled();
             }
             
             return Ok(());
         }
         
-        let z1 = x.dot(&w1) + &b1;
-        let a1 = relu(&z1);
-        let z2 = a1.dot(&w2) + &b2;
-        let y_pred = sigmoid(&z2);
+        // The schedule scales the base rate, which observers may have changed
+        let epoch_rate = config.lr_schedule.rate(learning_rate, epoch);
+        // Penalty of the weights the epoch's predictions are made with
+        let penalty = l2_penalty(&model, config.l2_lambda);
+        
+        // One update per mini-batch, over a fresh shuffle of the rows when enabled.
+        // Features and labels are selected with the same indices so rows stay aligned,
+        // and predictions are written back in the original order. An unshuffled
+        // full batch trains on `x` as it is and draws nothing from the rng.
+        let y_pred = if batch_size >= n_samples && !config.shuffle {
+            train_step(&mut model, &mut optimizer, &x, &y_true, &config, epoch_rate, &mut rng)
+        } else {
+            if config.shuffle {
+                indices.shuffle(&mut rng);
+            }
+            let mut y_pred = Array2::zeros(y_true.raw_dim());
+            for batch in indices.chunks(batch_size) {
+                let x_batch = x.select(Axis(0), batch);
+                let y_batch = y_true.select(Axis(0), batch);
+                let batch_pred = train_step(&mut model, &mut optimizer, &x_batch, &y_batch, &config, epoch_rate, &mut rng);
+                for (row, &i) in batch_pred.rows().into_iter().zip(batch) {
+                    y_pred.row_mut(i).assign(&row);
+                }
+            }
+            y_pred
+        };
 
-        let loss = binary_cross_entropy(&y_pred, &y_true);
+        // Each head has its own BCE (or MSE); their sum drives the shared backward pass.
+        // With mini-batches this is the size-weighted average over the batches.
+        // The L2 penalty is included so the chart shows the regularized objective.
+        let loss = task_loss(&config, &y_pred, &y_true) * n_heads as f64 + penalty;
+        
+        // A diverged run can't recover. The bad value is kept out of the curves,
+        // the finite part is still saved and the UI shows the error.
+        if !loss.is_finite() {
+            save_loss_plot(&app, &plot_tx, config.background_plotting, &losses, epochs);
+            return Err(format!(
+                "The loss became {} at epoch {}, training diverged. Try a lower learning rate or enable gradient clipping.",
+                if loss.is_nan() { "NaN" } else { "infinite" }, epoch + 1
+            ).into());
+        }
         losses.push(loss);
-
-        let dz2 = &y_pred - &y_true;
-        let dw2 = a1.t().dot(&dz2) / n_samples as f64;
-        let db2 = dz2.sum_axis(Axis(0)) / n_samples as f64;
-
-        let da1 = dz2.dot(&w2.t());
-        let dz1 = da1 * relu_deriv(&z1);
-        let dw1 = x.t().dot(&dz1) / n_samples as f64;
-        let db1 = dz1.sum_axis(Axis(0)) / n_samples as f64;
-
-        w1 -= &(dw1 * learning_rate);
-        b1 -= &(db1 * learning_rate);
-        w2 -= &(dw2 * learning_rate);
-        b2 -= &(db2 * learning_rate);
-
-        final_pred = y_pred.clone();
+        
+        // Held-out (loss, accuracy or R²) with the weights after this epoch's updates
+        let validation_pred = validation.as_ref().map(|(x_val, y_val)| (forward(x_val.view(), &model, &config), y_val));
+        let validation_metrics = validation_pred.as_ref().map(|(y_val_pred, y_val)| {
+            let val_loss = task_loss(&config, y_val_pred, y_val) * n_heads as f64;
+            (val_loss, task_metric(&config, y_val_pred, y_val))
+        });
+        
+        // Early stopping and the best-weights checkpoint watch the monitored
+        // metric, on the held-out rows when there are any
+        let checkpoint = validation.is_some() && config.restore_best_weights;
+        let monitored = (checkpoint || early_stopping.is_some()).then(|| match (&validation_pred, validation_metrics) {
+            (Some((y_val_pred, y_val)), Some((val_loss, _))) => monitored_value(config.monitor, val_loss, y_val_pred, y_val, &config),
+            _ => monitored_value(config.monitor, loss, &y_pred, &y_true, &config),
+        });
+        if let (Some(value), true) = (monitored, checkpoint) {
+            record_best_weights(&mut best_weights, epoch, value, config.mode, &model);
+        }
 
         // Calculate accuracy periodically
-        if epoch % LOG_INTERVAL == 0 || epoch == epochs - 1 {
-            let predictions = y_pred.mapv(|v| if v >= 0.5 { 1.0 } else { 0.0 });
-            let correct = predictions
-                .iter()
-                .zip(y_true.iter())
-                .filter(|(p, y)| (*p - *y).abs() < 1e-6)
-                .count();
-            let accuracy = (correct as f64 / n_samples as f64) * 100.0;
+        if config.is_logged_epoch(epoch, epochs) {
+            let accuracy = task_metric(&config, &y_pred, &y_true);
+            
+            if let Some(log) = progress_log.as_mut() {
+                if let Err(e) = log.write(epoch, loss, accuracy, epoch_rate) {
+                    eprintln!("Warning: could not write {}: {}", PROGRESS_LOG_PATH, e);
+                }
+            }
             
             // Update progress with accuracy
-            app.lock().unwrap().update_progress(epoch, loss, accuracy);
+            app.lock().unwrap().send_progress(ProgressUpdate {
+                epoch, loss, accuracy: Some(accuracy), validation: validation_metrics, learning_rate: epoch_rate,
+            });
+            
+            // Sampled here only, binning every weight each epoch would slow small networks down
+            let histograms = model.weights.iter()
+                .map(|w| stats::histogram(w.iter().copied(), WEIGHT_HISTOGRAM_BINS))
+                .collect::<Option<Vec<_>>>();
+            if let Some(histograms) = histograms {
+                app.lock().unwrap().set_weight_histograms(epoch, histograms);
+            }
+            
+            let event = observer::EpochEvent { epoch, loss, accuracy };
+            if notify_observers(&app, &observers, &event, &mut learning_rate, &model, model::SNAPSHOT_DIR) {
+                println!("Training stopped by an epoch observer at epoch {}/{}", epoch + 1, epochs);
+                break;
+            }
         } else {
-            // Update progress without accuracy
-            app.lock().unwrap().update_progress(epoch, loss, -1.0);
+            if let Some(log) = progress_log.as_mut().filter(|_| config.live_history_limit.is_some()) {
+                if let Err(e) = log.write(epoch, loss, f64::NAN, epoch_rate) {
+                    eprintln!("Warning: could not write {}: {}", PROGRESS_LOG_PATH, e);
+                }
+            }
+            
+            // Update progress without accuracy, R² isn't estimated from the loss so it's always sent
+            let accuracy = (config.task_type == TaskType::Regression).then(|| task_metric(&config, &y_pred, &y_true));
+            app.lock().unwrap().send_progress(ProgressUpdate {
+                epoch, loss, accuracy, validation: validation_metrics, learning_rate: epoch_rate,
+            });
         }
         
-        // Small sleep to give UI time to breathe
-        std::thread::sleep(std::time::Duration::from_millis(1));
+        if let (Some(stopper), Some(value)) = (early_stopping.as_mut(), monitored) {
+            if stopper.should_stop(epoch, value) {
+                println!("Early stopping at epoch {}/{} (best {} {:.4})",
+                         epoch + 1, epochs, config.monitor.label(), stopper.best_value);
+                break;
+            }
+        }
+        
+        // Time-boxed runs end like early stopping, with full metrics
+        if let Some(limit) = config.max_train_seconds {
+            if started_at.elapsed().as_secs_f64() >= limit {
+                println!("Time limit of {:.1}s reached at epoch {}/{}", limit, epoch + 1, epochs);
+                break;
+            }
+        }
     }
 
     // Save loss plot to file
-    plot_loss(&losses, epochs)?;
-
-    // Calculate final accuracy
-    let predictions = final_pred.mapv(|v| if v >= 0.5 { 1.0 } else { 0.0 });
-    let correct = predictions
-        .iter()
-        .zip(y_true.iter())
-        .filter(|(p, y)| (*p - *y).abs() < 1e-6)
-        .count();
+    save_loss_plot(&app, &plot_tx, config.background_plotting, &losses, epochs);
+    app.lock().unwrap().set_loss_record(losses);
 
-    let accuracy = (correct as f64 / n_samples as f64) * 100.0;
+    // Calculate final accuracy with the final (or best) weights
+    let weights = restore_best_weights(&app, &config, best_weights, model);
+    let (_, accuracy) = report_final_metrics(&app, &config, &x, &y_true, &weights, &mut rng);
+    
+    if let Some((x_val, y_val)) = &validation {
+        let (val_pred, _) = evaluate_batched(x_val, y_val, &weights, &config);
+        println!("Validation {}: {} on {} held-out rows (training: {})",
+                 config.task_type.metric_label(), config.task_type.format_metric(task_metric(&config, &val_pred, y_val)),
+                 x_val.nrows(), config.task_type.format_metric(accuracy));
+    }
+    
+    // Does the hidden layer pay off? Same data, no hidden layer.
+    if config.compare_baseline && config.task_type == TaskType::Classification {
+        let baseline = train_logistic_regression(&x, &head(&y_true, 0).to_owned(), &config);
+        println!("Logistic regression baseline accuracy: {:.2}% (network: {:.2}%)", baseline, accuracy);
+        app.lock().unwrap().set_baseline_accuracy(Some(baseline));
+    }
+    
+    // Keep the weights so they can be exported
+    app.lock().unwrap().set_trained_weights(weights);
     
     // Mark training as completed
     app.lock().unwrap().training_completed(accuracy);
+    app.lock().unwrap().save_run_history();
 
     Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    
     // Ensure directories exist
     let csv_dir = std::path::Path::new("csv");
     let result_dir = std::path::Path::new("result");
@@ -279,6 +2136,14 @@ fn main() -> Result<(), Box<dyn Error>> {
         std::fs::create_dir(result_dir)?;
     }
     
+    if args.headless {
+        if let Err(e) = cli::run_headless(&args) {
+            eprintln!("Training error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    
     // Create application options with a default window size
     let native_options = eframe::NativeOptions {
         viewport: eframe::egui::ViewportBuilder::default()
@@ -298,6 +2163,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             // Clone the app and wrap in Arc<Mutex<>>
             let app_wrapped = Arc::new(Mutex::new(app));
             let app_clone = app_wrapped.clone();
+            let plot_tx = spawn_plot_worker(app_wrapped.clone());
             
             // Set up training callback
             {
@@ -306,11 +2172,13 @@ fn main() -> Result<(), Box<dyn Error>> {
                 // Set up the training callback but don't start training automatically
                 app_locked.handle_train_click(move || {
                     let app_training = app_clone.clone();
+                    let plot_tx = plot_tx.clone();
                     
                     // Run training in a separate thread
                     thread::spawn(move || {
-                        if let Err(e) = train_neural_network(app_training) {
+                        if let Err(e) = train_neural_network(app_training.clone(), plot_tx) {
                             eprintln!("Training error: {}", e);
+                            app_training.lock().unwrap().training_failed(&e.to_string());
                         }
                     });
                 });
@@ -327,4 +2195,367 @@ fn main() -> Result<(), Box<dyn Error>> {
     )?;
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Write `contents` to a file of this test run and return its path
+    fn write_dataset(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("neural_network_test_{}_{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn feature_weights_must_cover_every_feature() {
+        let path = write_dataset("feature_weights.txt", "# age, income\n2.0, 0.5\n");
+        assert_eq!(load_feature_weights(&path).unwrap(), vec![2.0, 0.5]);
+        let mut x = Array2::<f64>::ones((2, 3));
+        let err = apply_feature_weights(&mut x, &[2.0, 0.5]).unwrap_err();
+        assert!(err.to_string().contains("2 values but the dataset has 3 features"), "{}", err);
+
+        let path = write_dataset("bad_feature_weights.txt", "2.0 x\n");
+        let err = load_feature_weights(&path).unwrap_err();
+        assert!(err.to_string().contains("Invalid feature weight 'x'"), "{}", err);
+    }
+
+    #[test]
+    fn feature_weights_scale_before_normalization() {
+        let raw = ndarray::array![[1.0, 10.0], [3.0, 30.0], [5.0, 20.0]];
+        let weights = [2.0, 0.5];
+        // Training scales the columns, then fits the z-score on the scaled values
+        let mut x = raw.clone();
+        apply_feature_weights(&mut x, &weights).unwrap();
+        let normalization = normalize_features(&mut x);
+        assert_eq!(normalization.means, vec![6.0, 10.0]);
+
+        let inputs = prepare_inputs(raw, Some(&weights), &[], Some(&normalization)).unwrap();
+        for (a, b) in inputs.iter().zip(x.iter()) {
+            assert!((a - b).abs() < 1e-12, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn stratified_split_keeps_the_class_ratio() {
+        // 97 positives in 1000 rows, scattered through the file
+        let y = Array2::from_shape_fn((1000, 1), |(i, _)| if i % 10 == 3 && i < 970 { 1.0 } else { 0.0 });
+        let mut rng = StdRng::seed_from_u64(5);
+        let (validation, train) = split_indices(&y, 0.25, true, &mut rng);
+        assert_eq!(validation.len() + train.len(), 1000);
+        let mut all: Vec<usize> = validation.iter().chain(&train).copied().collect();
+        all.sort_unstable();
+        assert_eq!(all, (0..1000).collect::<Vec<_>>());
+
+        // Each class contributes its own rounded share of rows
+        let positives = validation.iter().filter(|&&i| y[[i, 0]] == 1.0).count();
+        let expected = 0.097 * validation.len() as f64;
+        assert!((positives as f64 - expected).abs() <= 1.0, "{} positives of {} validation rows", positives, validation.len());
+    }
+
+    #[test]
+    fn sigmoid_saturates_without_nan() {
+        assert_eq!(stable_sigmoid(1000.0), 1.0);
+        assert_eq!(stable_sigmoid(-1000.0), 0.0);
+        assert_eq!(stable_sigmoid(f64::MAX), 1.0);
+        assert_eq!(stable_sigmoid(f64::MIN), 0.0);
+        assert_eq!(stable_sigmoid(0.0), 0.5);
+        // The negative branch keeps small probabilities instead of rounding them to 0
+        assert!(stable_sigmoid(-700.0) > 0.0);
+        for v in [0.5, 3.0, 30.0, 700.0] {
+            assert!((stable_sigmoid(v) + stable_sigmoid(-v) - 1.0).abs() < 1e-15, "{}", v);
+        }
+    }
+
+    #[test]
+    fn ignored_id_column_is_not_a_feature() {
+        let path = write_dataset("id.csv", "id,a,b,label\n1,0.5,1.0,0\n2,0.7,2.0,1\n3,0.1,3.0,0\n");
+        let options = LoadOptions { ignored_columns: vec![0], ..LoadOptions::default() };
+        let (x, y, _, _, _) = load_data(&path, &options).unwrap();
+        assert_eq!(x.ncols(), 2);
+        assert_eq!(x.row(1).to_vec(), vec![0.7, 2.0]);
+        assert_eq!(y.column(0).to_vec(), vec![0.0, 1.0, 0.0]);
+
+        let (all, _, _, _, _) = load_data(&path, &LoadOptions::default()).unwrap();
+        assert_eq!(all.ncols(), 3);
+    }
+
+    #[test]
+    fn semicolon_file_reads_comma_decimals() {
+        let path = write_dataset("semicolon.csv", "a;b;label\n1,5;2;0\n3,25;-0,5;1\n");
+        assert_eq!(detect_delimiter("1,5;2;0"), b';');
+        let (x, y, _, _, _) = load_data(&path, &LoadOptions::default()).unwrap();
+        assert_eq!(x.row(0).to_vec(), vec![1.5, 2.0]);
+        assert_eq!(x.row(1).to_vec(), vec![3.25, -0.5]);
+        assert_eq!(y.column(0).to_vec(), vec![0.0, 1.0]);
+        assert_eq!(parse_number("3,25", b','), None);
+    }
+
+    #[test]
+    fn tab_and_pipe_files_split_into_columns() {
+        assert_eq!(detect_delimiter("a\tb\tlabel"), b'\t');
+        assert_eq!(detect_delimiter("a|b|label"), b'|');
+        assert_eq!(detect_delimiter("a,b,label"), b',');
+        for (name, delimiter) in [("tab.tsv", "\t"), ("pipe.csv", "|")] {
+            let contents = "a,b,label\n0.5,1.5,0\n2.5,-1,1\n".replace(',', delimiter);
+            let (x, y, _, _, _) = load_data(&write_dataset(name, &contents), &LoadOptions::default()).unwrap();
+            assert_eq!(x.row(1).to_vec(), vec![2.5, -1.0], "{}", name);
+            assert_eq!(y.column(0).to_vec(), vec![0.0, 1.0], "{}", name);
+        }
+    }
+
+    #[test]
+    fn switching_datasets_fails_with_a_message() {
+        // Generated datasets with three and two feature columns
+        let generated = |name: &str, features: usize| {
+            let path = std::env::temp_dir().join(format!("neural_network_test_{}_{}", std::process::id(), name));
+            let path = path.to_string_lossy().into_owned();
+            let (x, y) = synthetic::SyntheticSpec { samples: 20, features, ..synthetic::SyntheticSpec::default() }.generate().unwrap();
+            synthetic::write_dataset_csv(&path, &x, &y).unwrap();
+            path
+        };
+        let wide = generated("switch_wide.csv", 3);
+        let narrow = generated("switch_narrow.csv", 2);
+        let hidden_layers = [4];
+
+        // Weights and ignored columns set up for the wide dataset
+        let options = LoadOptions { ignored_columns: vec![0], ..LoadOptions::default() };
+        let (x, y, _, _, _) = load_data(&wide, &options).unwrap();
+        validate_training_shapes(&x, &y, &hidden_layers).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let model = ModelWeights {
+            weights: initial_weights(x.ncols(), &hidden_layers, 1, InitScheme::StandardNormal, &mut rng),
+            biases: vec![Array2::zeros((1, 4)), Array2::zeros((1, 1))],
+            skip: None,
+            batch_norm: None,
+        };
+
+        // The narrow dataset is selected before training starts
+        let (x, y, _, _, _) = load_data(&narrow, &options).unwrap();
+        let error = model.validate_shapes(x.ncols(), &hidden_layers, y.ncols()).unwrap_err();
+        assert_eq!(error.to_string(), "Weight shape mismatch for w1: expected 1x4, found 2x4");
+
+        // Ignoring every feature column of the narrow dataset leaves nothing to load
+        let options = LoadOptions { ignored_columns: vec![0, 1], ..LoadOptions::default() };
+        let error = load_data(&narrow, &options).unwrap_err();
+        assert!(error.to_string().starts_with("No valid data found"), "{}", error);
+        let error = validate_training_shapes(&Array2::zeros((2, 0)), &y, &hidden_layers).unwrap_err();
+        assert_eq!(error.to_string(), "Dataset has no feature columns left to train on");
+    }
+
+    #[test]
+    fn padded_rows_keep_every_field_in_its_column() {
+        let path = write_dataset("ragged.csv", "label,a,b\n1,0.5\n0,0.2,0.3\n1,0.7,0.8,9\n");
+        let options = LoadOptions { label_column: Some(0), ragged_rows: RaggedRows::Pad, ..LoadOptions::default() };
+        let (x, y, _, _, _) = load_data(&path, &options).unwrap();
+        assert_eq!(x.rows().into_iter().map(|row| row.to_vec()).collect::<Vec<_>>(), vec![vec![0.5, 0.0], vec![0.2, 0.3], vec![0.7, 0.8]]);
+        assert_eq!(y.column(0).to_vec(), vec![1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn constant_column_is_detected_and_dropped() {
+        let path = write_dataset("constant.csv", "a,same,b,label\n0.1,5,1.0,0\n0.7,5,2.0,1\n0.4,5,3.0,0\n");
+        let (x, _, _, _, _) = load_data(&path, &LoadOptions::default()).unwrap();
+        let constant = stats::constant_columns(&x);
+        assert_eq!(constant, vec![1]);
+
+        // Training and prediction inputs both lose the column
+        let mut kept = prepare_inputs(x, None, &constant, None).unwrap();
+        assert_eq!(kept.column(1).to_vec(), vec![1.0, 2.0, 3.0]);
+        normalize_features(&mut kept);
+        assert!(kept.iter().all(|v| v.is_finite()));
+        let row = Array2::from_shape_vec((1, 3), vec![0.2, 5.0, 4.0]).unwrap();
+        assert_eq!(prepare_inputs(row, None, &constant, None).unwrap().row(0).to_vec(), vec![0.2, 4.0]);
+    }
+
+    #[test]
+    fn wide_dataset_trains() {
+        let n_features = 500;
+        let mut rng = StdRng::seed_from_u64(7);
+        let x = Array2::random_using((200, n_features), StandardNormal, &mut rng);
+        let y = x.map_axis(Axis(1), |row| if row.iter().take(10).sum::<f64>() > 0.0 { 1.0 } else { 0.0 }).insert_axis(Axis(1));
+        assert_eq!(stats::feature_label_correlations(&x, &y).len(), n_features);
+
+        let config = NetworkConfig { hidden_layers: vec![32], init_scheme: InitScheme::He, ..NetworkConfig::default() };
+        validate_training_shapes(&x, &y, &config.hidden_layers).unwrap();
+        let weights = initial_weights(n_features, &config.hidden_layers, 1, config.init_scheme, &mut rng);
+        let biases = vec![Array2::zeros((1, 32)), Array2::zeros((1, 1))];
+        let mut model = ModelWeights { weights, biases, skip: None, batch_norm: None };
+        let mut optimizer = OptimizerState::new(config.optimizer, config.momentum);
+        let losses: Vec<f64> = (0..20)
+            .map(|_| binary_cross_entropy(&train_step(&mut model, &mut optimizer, &x, &y, &config, config.learning_rate, &mut rng), &y))
+            .collect();
+        assert!(losses.iter().all(|loss| loss.is_finite()), "{:?}", losses);
+        assert!(losses[19] < losses[0], "{:?}", losses);
+    }
+
+    #[test]
+    fn he_init_starts_with_a_lower_loss() {
+        let (mut x, y, _, _, _) = load_data("csv/pollution_dataset5k.csv", &LoadOptions::default()).unwrap();
+        let y = head(&y, 0).to_owned();
+        normalize_features(&mut x);
+
+        // Loss the first epoch reports, averaged over a few seeds
+        let first_epoch_loss = |init_scheme: InitScheme| {
+            let config = NetworkConfig { init_scheme, ..NetworkConfig::default() };
+            let seeds = 5;
+            let total: f64 = (0..seeds).map(|seed| {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let weights = initial_weights(x.ncols(), &config.hidden_layers, 1, config.init_scheme, &mut rng);
+                let biases = config.hidden_layers.iter().chain([1].iter()).map(|&size| Array2::zeros((1, size))).collect();
+                let mut model = ModelWeights { weights, biases, skip: None, batch_norm: None };
+                let mut optimizer = OptimizerState::new(config.optimizer, config.momentum);
+                binary_cross_entropy(&train_step(&mut model, &mut optimizer, &x, &y, &config, config.learning_rate, &mut rng), &y)
+            }).sum();
+            total / seeds as f64
+        };
+        let (standard_normal, he) = (first_epoch_loss(InitScheme::StandardNormal), first_epoch_loss(InitScheme::He));
+        assert!(he < standard_normal, "He {} vs StandardNormal {}", he, standard_normal);
+    }
+
+    // Benchmark of the rayon path, see parallel.rs:
+    // cargo test --release parallel_training_matches_serial -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn parallel_training_matches_serial() {
+        let (mut x, y, _, _, _) = load_data("csv/pollution_dataset5k.csv", &LoadOptions::default()).unwrap();
+        let y = head(&y, 0).to_owned();
+        normalize_features(&mut x);
+        let epochs = 5000;
+
+        let run = |parallel: bool| {
+            // Deterministic runs always take the serial path
+            let config = NetworkConfig { parallel, deterministic: false, ..NetworkConfig::default() };
+            let mut rng = StdRng::seed_from_u64(DETERMINISTIC_SEED);
+            let weights = initial_weights(x.ncols(), &config.hidden_layers, 1, config.init_scheme, &mut rng);
+            let biases = config.hidden_layers.iter().chain([1].iter()).map(|&size| Array2::zeros((1, size))).collect();
+            let mut model = ModelWeights { weights, biases, skip: None, batch_norm: None };
+            let mut optimizer = OptimizerState::new(config.optimizer, config.momentum);
+            let start = std::time::Instant::now();
+            let losses: Vec<f64> = (0..epochs)
+                .map(|_| binary_cross_entropy(&train_step(&mut model, &mut optimizer, &x, &y, &config, config.learning_rate, &mut rng), &y))
+                .collect();
+            (start.elapsed().as_secs_f64(), losses)
+        };
+        let (serial_seconds, serial) = run(false);
+        let (parallel_seconds, parallel) = run(true);
+        println!(
+            "{} epochs on {} rows, {} threads: serial {:.2}s, parallel {:.2}s, final loss {:.6}",
+            epochs, x.nrows(), rayon::current_num_threads(), serial_seconds, parallel_seconds, serial[epochs - 1]
+        );
+        assert_eq!(serial, parallel);
+    }
+
+    #[test]
+    fn gradient_clipping_keeps_a_diverging_run_finite() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let x = Array2::random_using((100, 5), StandardNormal, &mut rng);
+        let y = x.map_axis(Axis(1), |row| 10.0 * row.sum()).insert_axis(Axis(1));
+        // A learning rate this large overshoots further on every step without clipping
+        let run = |grad_clip: Option<f64>| {
+            let config = NetworkConfig { task_type: TaskType::Regression, hidden_layers: vec![8], learning_rate: 0.5, grad_clip, ..NetworkConfig::default() };
+            let mut rng = StdRng::seed_from_u64(3);
+            let weights = initial_weights(5, &config.hidden_layers, 1, config.init_scheme, &mut rng);
+            let biases = vec![Array2::zeros((1, 8)), Array2::zeros((1, 1))];
+            let mut model = ModelWeights { weights, biases, skip: None, batch_norm: None };
+            let mut optimizer = OptimizerState::new(config.optimizer, config.momentum);
+            (0..50)
+                .map(|_| mean_squared_error(&train_step(&mut model, &mut optimizer, &x, &y, &config, config.learning_rate, &mut rng), &y))
+                .collect::<Vec<f64>>()
+        };
+        let unclipped = run(None);
+        let clipped = run(Some(1.0));
+        assert!(unclipped.iter().any(|loss| !loss.is_finite()), "{:?}", unclipped);
+        assert!(clipped.iter().all(|loss| loss.is_finite()), "{:?}", clipped);
+        assert!(clipped[49] < clipped[0], "{:?}", clipped);
+    }
+
+    #[test]
+    fn epoch_observers_retune_snapshot_and_stop() {
+        let dir = std::env::temp_dir().join(format!("neural_network_test_{}_snapshots", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let dir = dir.to_string_lossy().into_owned();
+        let config = NetworkConfig { hidden_layers: vec![2], ..NetworkConfig::default() };
+        let mut app = NeuralNetworkApp::without_saved_comparisons();
+        app.get_training_data().lock().unwrap().run_config = Some(config.clone());
+        // Halve the rate at the first logged epoch, snapshot the second, stop from 90% accuracy
+        app.add_epoch_observer(|event, control| match event.epoch {
+            0 => control.learning_rate /= 2.0,
+            100 => control.request_snapshot(),
+            _ => {}
+        });
+        app.add_epoch_observer(|event, control| {
+            if event.accuracy >= 90.0 {
+                control.request_stop();
+            }
+        });
+        let observers = app.epoch_observers();
+        let app = Arc::new(Mutex::new(app));
+        let model = ModelWeights {
+            weights: vec![Array2::from_elem((3, 2), 0.5), Array2::from_elem((2, 1), -1.0)],
+            biases: vec![Array2::zeros((1, 2)), Array2::zeros((1, 1))],
+            skip: None,
+            batch_norm: None,
+        };
+
+        let mut learning_rate = 0.01;
+        let mut notify = |epoch, accuracy| {
+            let event = observer::EpochEvent { epoch, loss: 0.5, accuracy };
+            notify_observers(&app, &observers, &event, &mut learning_rate, &model, &dir)
+        };
+        assert!(!notify(0, 70.0));
+        assert!(!notify(100, 85.0));
+        assert!(notify(200, 92.0));
+        assert_eq!(learning_rate, 0.005);
+        let snapshot = model::load_model(&model::snapshot_path(&dir, 100)).unwrap();
+        assert_eq!(snapshot.weights.weights, model.weights);
+        assert!(snapshot.config == config);
+        assert!(!std::path::Path::new(&model::snapshot_path(&dir, 0)).exists());
+    }
+
+    #[test]
+    fn progress_log_marks_each_run_start() {
+        let path = std::env::temp_dir().join(format!("neural_network_test_{}_progress.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let path = path.to_string_lossy().into_owned();
+        for _ in 0..2 {
+            let mut log = ProgressLog::open(&path, "csv/data.csv", &NetworkConfig::default()).unwrap();
+            log.write(0, 0.5, 90.0, 0.01).unwrap();
+        }
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        for run in lines.chunks(2) {
+            assert_eq!(run[0]["dataset"], "csv/data.csv");
+            assert!(run[0]["run_start"].is_u64() && run[0]["config"].is_object());
+            assert_eq!(run[1]["epoch"], 0);
+        }
+    }
+
+    #[test]
+    fn checkpoint_follows_the_monitor_direction() {
+        let model = ModelWeights { weights: vec![Array2::zeros((1, 1))], biases: vec![Array2::zeros((1, 1))], skip: None, batch_norm: None };
+        let mut best = None;
+        for (epoch, auc) in [0.6, 0.8, 0.7].into_iter().enumerate() {
+            record_best_weights(&mut best, epoch, auc, MonitorMode::Max, &model);
+        }
+        assert_eq!(best.map(|best| (best.epoch, best.value)), Some((1, 0.8)));
+
+        let config = NetworkConfig::default();
+        let y_pred = Array2::from_shape_vec((4, 1), vec![0.9, 0.2, 0.6, 0.4]).unwrap();
+        let y_true = Array2::from_shape_vec((4, 1), vec![1.0, 0.0, 0.0, 1.0]).unwrap();
+        assert_eq!(monitored_value(Metric::Auc, 0.0, &y_pred, &y_true, &config), 0.75);
+        assert_eq!(monitored_value(Metric::Auc, 0.0, &y_pred, &Array2::ones((4, 1)), &config), 0.5);
+    }
+
+    #[test]
+    fn early_stopping_waits_for_min_epochs() {
+        let mut early_stopping = EarlyStopping::new(2, 5, MonitorMode::Min);
+        for epoch in 0..4 {
+            assert!(!early_stopping.should_stop(epoch, 1.0), "stopped at epoch {}", epoch);
+        }
+        assert!(early_stopping.should_stop(4, 1.0));
+    }
+}
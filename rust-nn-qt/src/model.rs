@@ -0,0 +1,566 @@
+use ndarray::{Array1, Array2};
+use csv::{ReaderBuilder, WriterBuilder};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+use crate::frontend_new::{NetworkConfig, TaskType};
+
+pub const MODEL_PATH: &str = "result/model.json";
+/// Directory of the models saved mid-run when an epoch observer asks for a snapshot
+pub const SNAPSHOT_DIR: &str = "result";
+
+// Where the snapshot of the 0-based `epoch` goes, numbered from 1 like the progress output
+pub fn snapshot_path(dir: &str, epoch: usize) -> String {
+    format!("{}/snapshot_epoch_{}.json", dir, epoch + 1)
+}
+
+/// Trained parameters of the network
+#[derive(Clone, Debug)]
+pub struct ModelWeights {
+    /// One matrix per layer, hidden layers first and the output layer last:
+    /// n_features x h1, h1 x h2, ..., h_last x n_heads
+    pub weights: Vec<Array2<f64>>,
+    /// The matching 1 x layer size bias rows
+    pub biases: Vec<Array2<f64>>,
+    /// Input-to-hidden shortcut added after the first hidden activation
+    /// (n_features x h1), the identity when both sizes match, otherwise a learned projection
+    pub skip: Option<Array2<f64>>,
+    /// Batch normalization of each hidden pre-activation, one entry per hidden layer
+    pub batch_norm: Option<Vec<BatchNorm>>,
+}
+
+/// Added to the variance before the square root so constant units stay finite
+pub const BATCH_NORM_EPSILON: f64 = 1e-5;
+/// Weight of the previous running statistics when a batch's are folded in
+pub const BATCH_NORM_MOMENTUM: f64 = 0.9;
+
+/// Learned scale and shift of a batch-normalized hidden layer, and the running
+/// mean and variance of its pre-activations that replace the batch statistics at
+/// inference. All are 1 x layer size rows like the biases.
+#[derive(Clone, Debug)]
+pub struct BatchNorm {
+    pub gamma: Array2<f64>,
+    pub beta: Array2<f64>,
+    pub running_mean: Array2<f64>,
+    pub running_var: Array2<f64>,
+}
+
+impl BatchNorm {
+    /// The identity transform of a layer with `size` units
+    pub fn new(size: usize) -> Self {
+        Self {
+            gamma: Array2::ones((1, size)),
+            beta: Array2::zeros((1, size)),
+            running_mean: Array2::zeros((1, size)),
+            running_var: Array2::ones((1, size)),
+        }
+    }
+
+    /// Factor each centered pre-activation is multiplied by at inference
+    pub fn inference_scale(&self) -> Array2<f64> {
+        &self.gamma / &self.running_var.mapv(|var| (var + BATCH_NORM_EPSILON).sqrt())
+    }
+
+    /// Normalize pre-activations with the running statistics
+    pub fn apply(&self, z: &Array2<f64>) -> Array2<f64> {
+        (z - &self.running_mean) * &self.inference_scale() + &self.beta
+    }
+}
+
+/// Trainable parameters of a network with these dimensions: weights and biases of
+/// every layer, plus the skip projection and the batch norm scales and shifts. The
+/// identity shortcut used when the feature count equals the first hidden size has
+/// no parameters.
+pub fn parameter_count(n_features: usize, hidden_layers: &[usize], n_heads: usize, skip_connection: bool, batch_norm: bool) -> usize {
+    let first_hidden = hidden_layers.first().copied().unwrap_or(n_heads);
+    let skip = if skip_connection && n_features != first_hidden { n_features * first_hidden } else { 0 };
+    let normalization = if batch_norm { 2 * hidden_layers.iter().sum::<usize>() } else { 0 };
+    let sizes: Vec<usize> = std::iter::once(n_features)
+        .chain(hidden_layers.iter().copied())
+        .chain(std::iter::once(n_heads))
+        .collect();
+    sizes.windows(2).map(|pair| pair[0] * pair[1] + pair[1]).sum::<usize>() + skip + normalization
+}
+
+/// How raw CSV rows become the network's input, stored next to exported weights
+/// so the forward pass can be reproduced elsewhere
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Preprocessing {
+    /// 0-based CSV columns that are never features
+    pub ignored_columns: Vec<usize>,
+    /// CSV column of head 0 (None = the last column)
+    #[serde(default)]
+    pub label_column: Option<usize>,
+    /// CSV columns of the extra output heads (the label column is always head 0)
+    pub extra_label_columns: Vec<usize>,
+    /// Per-feature multipliers applied before anything else (None = all 1.0)
+    pub feature_weights: Option<Vec<f64>>,
+    /// Constant feature columns removed after the multipliers
+    pub dropped_features: Vec<usize>,
+    /// Label values behind the primary head's 0 and 1
+    pub class_names: [String; 2],
+    /// Z-score statistics applied last (None = features are fed unscaled)
+    pub normalization: Option<Normalization>,
+}
+
+/// Per-feature mean and standard deviation of the training rows, so inputs
+/// seen later get exactly the transform the network was trained on
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Normalization {
+    pub means: Vec<f64>,
+    /// 1.0 for constant columns, which are then only centered
+    pub stds: Vec<f64>,
+}
+
+impl Normalization {
+    pub fn apply(&self, x: &mut Array2<f64>) {
+        *x -= &Array1::from(self.means.clone());
+        *x /= &Array1::from(self.stds.clone());
+    }
+}
+
+// Layout of the JSON export up to the output layer, repeated in the file
+// itself with the output step of the task appended
+const JSON_LAYOUT: &str = "Row-major nested lists. x is 1 x n_features (CSV row without ignored and \
+label columns, times feature_weights, without dropped_features, then (x - means) / stds when \
+normalization is not null). weights[i] is inputs x size of \
+layer i (n_features x h1, ..., h_last x n_heads), biases[i] is 1 x size, skip is n_features x h1 or null. \
+a = x; for each hidden layer i: z = a @ weights[i] + biases[i], when batch_norm is not null \
+z = (z - running_mean) / sqrt(running_var + 1e-5) * gamma + beta with batch_norm[i], \
+a = hidden_activation(z), plus x @ skip \
+after the first one (leaky_relu slope 0.01); ";
+const JSON_CLASSIFICATION_OUTPUT: &str = "p = sigmoid(clip(a @ weights[-1] + biases[-1], -logit_clamp, logit_clamp)), \
+without the clip when logit_clamp is null.";
+const JSON_REGRESSION_OUTPUT: &str = "y = a @ weights[-1] + biases[-1], the predicted values without an output activation.";
+
+/// A `BatchNorm` as plain lists for the model and JSON files
+#[derive(Serialize, Deserialize)]
+pub struct BatchNormRows {
+    pub gamma: Vec<f64>,
+    pub beta: Vec<f64>,
+    pub running_mean: Vec<f64>,
+    pub running_var: Vec<f64>,
+}
+
+impl BatchNormRows {
+    fn from_layer(layer: &BatchNorm) -> Self {
+        Self {
+            gamma: layer.gamma.iter().copied().collect(),
+            beta: layer.beta.iter().copied().collect(),
+            running_mean: layer.running_mean.iter().copied().collect(),
+            running_var: layer.running_var.iter().copied().collect(),
+        }
+    }
+
+    fn to_layer(&self) -> Result<BatchNorm, Box<dyn Error>> {
+        let row = |values: &[f64]| Array2::from_shape_vec((1, values.len()), values.to_vec());
+        Ok(BatchNorm {
+            gamma: row(&self.gamma)?,
+            beta: row(&self.beta)?,
+            running_mean: row(&self.running_mean)?,
+            running_var: row(&self.running_var)?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    layout: String,
+    hidden_activation: &'static str,
+    output_activation: &'static str,
+    logit_clamp: Option<f64>,
+    preprocessing: &'a Preprocessing,
+    weights: Vec<Vec<Vec<f64>>>,
+    biases: Vec<Vec<Vec<f64>>>,
+    skip: Option<Vec<Vec<f64>>>,
+    batch_norm: Option<Vec<BatchNormRows>>,
+}
+
+/// A trained network as written by "Save Model": the matrices with their
+/// shapes, the config it was trained with and the input preprocessing
+#[derive(Serialize, Deserialize)]
+pub struct SavedModel {
+    pub config: NetworkConfig,
+    pub preprocessing: Preprocessing,
+    /// (rows, cols) of each weights[i], checked against the matrices on load
+    pub layer_shapes: Vec<(usize, usize)>,
+    pub weights: Vec<Vec<Vec<f64>>>,
+    pub biases: Vec<Vec<Vec<f64>>>,
+    pub skip: Option<Vec<Vec<f64>>>,
+    /// Missing in files saved before batch norm existed
+    #[serde(default)]
+    pub batch_norm: Option<Vec<BatchNormRows>>,
+}
+
+// Serialize a trained network and everything needed to use it again to one JSON file
+pub fn save_model(path: &str, weights: &ModelWeights, config: &NetworkConfig, preprocessing: &Preprocessing) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = Path::new(path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let model = SavedModel {
+        config: config.clone(),
+        preprocessing: preprocessing.clone(),
+        layer_shapes: weights.weights.iter().map(|w| w.dim()).collect(),
+        weights: weights.weights.iter().map(nested_rows).collect(),
+        biases: weights.biases.iter().map(nested_rows).collect(),
+        skip: weights.skip.as_ref().map(nested_rows),
+        batch_norm: weights.batch_norm_rows(),
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&model)?)?;
+
+    println!("Model saved to {}", path);
+    Ok(())
+}
+
+/// A network read back by `load_model`
+#[derive(Clone)]
+pub struct LoadedModel {
+    pub weights: ModelWeights,
+    pub config: NetworkConfig,
+    pub preprocessing: Preprocessing,
+}
+
+// Read a file written by `save_model`, checking the matrices against the stored
+// shapes and against each other before anything runs on them
+pub fn load_model(path: &str) -> Result<LoadedModel, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let saved: SavedModel = serde_json::from_str(&text).map_err(|e| format!("Invalid model file {}: {}", path, e))?;
+
+    let weights = saved.weights.iter().map(|rows| matrix_from_rows(rows)).collect::<Result<Vec<_>, _>>()?;
+    let biases = saved.biases.iter().map(|rows| matrix_from_rows(rows)).collect::<Result<Vec<_>, _>>()?;
+    let skip = saved.skip.as_deref().map(matrix_from_rows).transpose()?;
+    let batch_norm = saved.batch_norm.as_ref()
+        .map(|layers| layers.iter().map(BatchNormRows::to_layer).collect::<Result<Vec<_>, _>>())
+        .transpose()?;
+    let model = ModelWeights { weights, biases, skip, batch_norm };
+
+    let shapes: Vec<(usize, usize)> = model.weights.iter().map(|w| w.dim()).collect();
+    if shapes != saved.layer_shapes {
+        return Err(format!("Model file {} declares layer shapes {:?} but holds {:?}", path, saved.layer_shapes, shapes).into());
+    }
+    if model.weights.len() < 2 {
+        return Err(format!("Model file {} needs at least one hidden and the output layer", path).into());
+    }
+    let hidden: Vec<usize> = shapes[..shapes.len() - 1].iter().map(|&(_, cols)| cols).collect();
+    model.validate_shapes(model.n_features(), &hidden, shapes[shapes.len() - 1].1)?;
+    if let Some(skip) = &model.skip {
+        if skip.dim() != (model.n_features(), hidden[0]) {
+            return Err(format!("Skip matrix is {:?}, expected {:?}", skip.dim(), (model.n_features(), hidden[0])).into());
+        }
+    }
+
+    println!("Model loaded from {} ({} layers)", path, model.weights.len());
+    Ok(LoadedModel { weights: model, config: saved.config, preprocessing: saved.preprocessing })
+}
+
+fn matrix_from_rows(rows: &[Vec<f64>]) -> Result<Array2<f64>, Box<dyn Error>> {
+    let n_cols = rows.first().map_or(0, |row| row.len());
+    if rows.iter().any(|row| row.len() != n_cols) {
+        return Err("Inconsistent row length in a model matrix".into());
+    }
+    Ok(Array2::from_shape_vec((rows.len(), n_cols), rows.concat())?)
+}
+
+fn nested_rows(matrix: &Array2<f64>) -> Vec<Vec<f64>> {
+    matrix.rows().into_iter().map(|row| row.to_vec()).collect()
+}
+
+const SKIP_FILE: &str = "skip.csv";
+
+// Layer i (0-based) is stored as w{i+1}.csv and b{i+1}.csv, so a single hidden
+// layer gives the original w1/b1/w2/b2 files
+fn layer_files(layer: usize) -> (String, String) {
+    (format!("w{}.csv", layer + 1), format!("b{}.csv", layer + 1))
+}
+
+impl ModelWeights {
+    pub fn n_features(&self) -> usize {
+        self.weights.first().map_or(0, |w| w.nrows())
+    }
+
+    pub fn parameter_count(&self) -> usize {
+        let hidden: Vec<usize> = self.weights[..self.weights.len().saturating_sub(1)].iter().map(|w| w.ncols()).collect();
+        let n_heads = self.weights.last().map_or(0, |w| w.ncols());
+        parameter_count(self.n_features(), &hidden, n_heads, self.skip.is_some(), self.batch_norm.is_some())
+    }
+
+    fn batch_norm_rows(&self) -> Option<Vec<BatchNormRows>> {
+        self.batch_norm.as_ref().map(|layers| layers.iter().map(BatchNormRows::from_layer).collect())
+    }
+    
+    // Check the matrices against the network shape implied by the config and dataset
+    pub fn validate_shapes(&self, n_features: usize, hidden_layers: &[usize], n_heads: usize) -> Result<(), Box<dyn Error>> {
+        if self.weights.len() != hidden_layers.len() + 1 || self.biases.len() != self.weights.len() {
+            return Err(format!(
+                "Layer count mismatch: expected {} weight matrices, found {}",
+                hidden_layers.len() + 1, self.weights.len()
+            ).into());
+        }
+
+        let sizes: Vec<usize> = std::iter::once(n_features)
+            .chain(hidden_layers.iter().copied())
+            .chain(std::iter::once(n_heads))
+            .collect();
+        let mut expected = Vec::new();
+        let mut actual = Vec::new();
+        for (i, pair) in sizes.windows(2).enumerate() {
+            let (w_file, b_file) = layer_files(i);
+            expected.push((w_file, (pair[0], pair[1])));
+            expected.push((b_file, (1, pair[1])));
+            actual.push(self.weights[i].dim());
+            actual.push(self.biases[i].dim());
+        }
+        if let Some(skip) = &self.skip {
+            expected.push(("skip".to_string(), (n_features, sizes[1])));
+            actual.push(skip.dim());
+        }
+        if let Some(batch_norm) = &self.batch_norm {
+            if batch_norm.len() != hidden_layers.len() {
+                return Err(format!(
+                    "Batch norm count mismatch: expected {} layers, found {}",
+                    hidden_layers.len(), batch_norm.len()
+                ).into());
+            }
+            for (i, (layer, &size)) in batch_norm.iter().zip(hidden_layers).enumerate() {
+                for (name, row) in [("gamma", &layer.gamma), ("beta", &layer.beta), ("mean", &layer.running_mean), ("var", &layer.running_var)] {
+                    expected.push((format!("batch norm {} {}", i + 1, name), (1, size)));
+                    actual.push(row.dim());
+                }
+            }
+        }
+
+        for ((name, want), got) in expected.into_iter().zip(actual) {
+            if want != got {
+                return Err(format!(
+                    "Weight shape mismatch for {}: expected {}x{}, found {}x{}",
+                    name.trim_end_matches(".csv"), want.0, want.1, got.0, got.1
+                ).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    // Write w1.csv, b1.csv, w2.csv, b2.csv, ... (plus skip.csv with a skip connection),
+    // one matrix row per line and no header, into `dir`
+    pub fn export_csv(&self, dir: &str, delimiter: u8) -> Result<(), Box<dyn Error>> {
+        if self.batch_norm.is_some() {
+            return Err("The CSV layout has no place for batch norm parameters, export JSON or save the model instead".into());
+        }
+        std::fs::create_dir_all(dir)?;
+
+        let mut files = Vec::new();
+        for (i, (w, b)) in self.weights.iter().zip(&self.biases).enumerate() {
+            let (w_file, b_file) = layer_files(i);
+            files.push((w_file, w));
+            files.push((b_file, b));
+        }
+        files.extend(self.skip.as_ref().map(|s| (SKIP_FILE.to_string(), s)));
+
+        for (file_name, matrix) in files {
+            let mut wtr = WriterBuilder::new()
+                .delimiter(delimiter)
+                .from_path(Path::new(dir).join(file_name))?;
+            for row in matrix.rows() {
+                wtr.write_record(row.iter().map(|v| v.to_string()))?;
+            }
+            wtr.flush()?;
+        }
+
+        // Files of a deeper (or skip-connected) network exported here before would be
+        // picked up by `import_csv`, so remove them
+        let mut layer = self.weights.len();
+        while Path::new(dir).join(layer_files(layer).0).exists() {
+            let (w_file, b_file) = layer_files(layer);
+            std::fs::remove_file(Path::new(dir).join(w_file))?;
+            let _ = std::fs::remove_file(Path::new(dir).join(b_file));
+            layer += 1;
+        }
+        if self.skip.is_none() && Path::new(dir).join(SKIP_FILE).exists() {
+            std::fs::remove_file(Path::new(dir).join(SKIP_FILE))?;
+        }
+
+        println!("Weights exported to {}", dir);
+        Ok(())
+    }
+
+    /// Write all matrices and the preprocessing to one JSON file for other tools.
+    /// The matrices are nested lists of rows, so in Python
+    /// `w = [np.array(m) for m in d["weights"]]` gives `w[0]` the shape
+    /// (n_features, h1), and for a ReLU network with one hidden layer
+    /// `p = sigmoid(np.maximum(x @ w[0] + b[0], 0) @ w[1] + b[1])` (plus `x @ skip`
+    /// inside the hidden term when `skip` is not null) gives the probabilities.
+    /// A regression network has no output activation ("identity") and no clamp.
+    /// Other activations are named in `hidden_activation`, the batch norm step
+    /// is spelled out in `layout`.
+    pub fn export_json(
+        &self,
+        path: &str,
+        hidden_activation: &'static str,
+        task_type: TaskType,
+        logit_clamp: Option<f64>,
+        preprocessing: &Preprocessing,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(dir) = Path::new(path).parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let (output_activation, output_step, logit_clamp) = match task_type {
+            TaskType::Classification => ("sigmoid", JSON_CLASSIFICATION_OUTPUT, logit_clamp),
+            TaskType::Regression => ("identity", JSON_REGRESSION_OUTPUT, None),
+        };
+        let export = JsonExport {
+            layout: format!("{}{}", JSON_LAYOUT, output_step),
+            hidden_activation,
+            output_activation,
+            logit_clamp,
+            preprocessing,
+            weights: self.weights.iter().map(nested_rows).collect(),
+            biases: self.biases.iter().map(nested_rows).collect(),
+            skip: self.skip.as_ref().map(nested_rows),
+            batch_norm: self.batch_norm_rows(),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&export)?)?;
+
+        println!("Weights exported to {}", path);
+        Ok(())
+    }
+
+    // Read the layer files written by `export_csv` (or by another tool) from `dir`:
+    // w1/b1 up to the last wN/bN found, at least one hidden and the output layer
+    pub fn import_csv(dir: &str) -> Result<Self, Box<dyn Error>> {
+        let mut weights = Vec::new();
+        let mut biases = Vec::new();
+        let mut layer = 0;
+        loop {
+            let (w_file, b_file) = layer_files(layer);
+            let w_path = Path::new(dir).join(&w_file);
+            if !w_path.exists() && layer >= 2 {
+                break;
+            }
+            weights.push(read_matrix_csv(&w_path)?);
+            biases.push(read_matrix_csv(&Path::new(dir).join(&b_file))?);
+            layer += 1;
+        }
+        // Optional, only written for networks trained with a skip connection
+        let skip_path = Path::new(dir).join(SKIP_FILE);
+        let skip = if skip_path.exists() { Some(read_matrix_csv(&skip_path)?) } else { None };
+
+        println!("Weights imported from {} ({} layers)", dir, weights.len());
+        Ok(Self { weights, biases, skip, batch_norm: None })
+    }
+}
+
+fn read_matrix_csv(path: &Path) -> Result<Array2<f64>, Box<dyn Error>> {
+    if !path.exists() {
+        return Err(format!("Weight file not found: {}", path.display()).into());
+    }
+
+    // Same delimiter detection as the dataset loader, plus tabs from tab-separated exports
+    let first_line = std::fs::read_to_string(path)?
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let delimiter = if first_line.contains(';') {
+        b';'
+    } else if first_line.contains('\t') {
+        b'\t'
+    } else {
+        b','
+    };
+
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(delimiter)
+        .from_path(path)?;
+
+    let mut values = Vec::new();
+    let mut n_rows = 0;
+    let mut n_cols = 0;
+    for result in rdr.records() {
+        let record = result?;
+        let row: Result<Vec<f64>, _> = record.iter().map(|s| s.trim().parse::<f64>()).collect();
+        let row = row.map_err(|e| format!("Invalid number in {}: {}", path.display(), e))?;
+        if n_rows == 0 {
+            n_cols = row.len();
+        } else if row.len() != n_cols {
+            return Err(format!("Inconsistent row length in {}", path.display()).into());
+        }
+        values.extend(row);
+        n_rows += 1;
+    }
+
+    if n_rows == 0 {
+        return Err(format!("Weight file {} is empty", path.display()).into());
+    }
+
+    Ok(Array2::from_shape_vec((n_rows, n_cols), values)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Unique file of this test run in the temp directory
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("neural_network_test_{}_{}", std::process::id(), name)).to_string_lossy().into_owned()
+    }
+
+    fn small_model() -> ModelWeights {
+        ModelWeights {
+            weights: vec![
+                Array2::from_shape_vec((2, 3), vec![0.1, -0.2, 0.3, 0.4, 0.5, -0.6]).unwrap(),
+                Array2::from_shape_vec((3, 1), vec![0.7, -0.8, 0.9]).unwrap(),
+            ],
+            biases: vec![Array2::from_shape_vec((1, 3), vec![0.01, 0.02, 0.03]).unwrap(), Array2::from_elem((1, 1), -0.5)],
+            skip: None,
+            batch_norm: None,
+        }
+    }
+
+    fn preprocessing() -> Preprocessing {
+        Preprocessing {
+            ignored_columns: vec![0],
+            label_column: None,
+            extra_label_columns: Vec::new(),
+            feature_weights: None,
+            dropped_features: Vec::new(),
+            class_names: ["no".to_string(), "yes".to_string()],
+            normalization: Some(Normalization { means: vec![1.0, 2.0], stds: vec![0.5, 1.0] }),
+        }
+    }
+
+    #[test]
+    fn saved_model_keeps_weights_and_class_names() {
+        let path = temp_path("model.json");
+        let weights = small_model();
+        save_model(&path, &weights, &NetworkConfig::default(), &preprocessing()).unwrap();
+        let loaded = load_model(&path).unwrap();
+        assert_eq!(loaded.weights.weights, weights.weights);
+        assert_eq!(loaded.weights.biases, weights.biases);
+        assert_eq!(loaded.preprocessing.class_names, ["no".to_string(), "yes".to_string()]);
+        assert_eq!(loaded.preprocessing.normalization, preprocessing().normalization);
+    }
+
+    #[test]
+    fn json_export_names_the_output_of_the_task() {
+        let path = temp_path("weights.json");
+        let read = |path: &str| -> serde_json::Value { serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap() };
+
+        small_model().export_json(&path, "relu", TaskType::Classification, Some(10.0), &preprocessing()).unwrap();
+        let classifier = read(&path);
+        assert_eq!(classifier["output_activation"], "sigmoid");
+        assert_eq!(classifier["logit_clamp"], 10.0);
+        assert!(classifier["layout"].as_str().unwrap().contains("p = sigmoid("));
+
+        small_model().export_json(&path, "relu", TaskType::Regression, Some(10.0), &preprocessing()).unwrap();
+        let regressor = read(&path);
+        assert_eq!(regressor["output_activation"], "identity");
+        assert!(regressor["logit_clamp"].is_null());
+        let layout = regressor["layout"].as_str().unwrap();
+        assert!(layout.contains("y = a @ weights[-1] + biases[-1]") && !layout.contains("sigmoid(clip"));
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::{Arc, Mutex};
+
+/// Metrics of a logged epoch, as passed to epoch observers
+#[derive(Clone, Copy, Debug)]
+pub struct EpochEvent {
+    pub epoch: usize,
+    pub loss: f64,
+    /// Accuracy (%) of the primary head
+    pub accuracy: f64,
+}
+
+/// Handle through which an observer steers the running training loop.
+/// Requests are applied after all observers of the epoch have run.
+#[derive(Clone, Debug)]
+pub struct EpochControl {
+    /// Learning rate used from the next epoch on, starts at the current one
+    pub learning_rate: f64,
+    stop: bool,
+    snapshot: bool,
+}
+
+impl EpochControl {
+    pub fn new(learning_rate: f64) -> Self {
+        Self { learning_rate, stop: false, snapshot: false }
+    }
+
+    // End training like early stopping, with full final metrics
+    pub fn request_stop(&mut self) {
+        self.stop = true;
+    }
+
+    // Save the current weights with the run's config and preprocessing to
+    // result/snapshot_epoch_N.json, training carries on
+    pub fn request_snapshot(&mut self) {
+        self.snapshot = true;
+    }
+
+    pub fn stop_requested(&self) -> bool {
+        self.stop
+    }
+
+    pub fn snapshot_requested(&self) -> bool {
+        self.snapshot
+    }
+}
+
+/// Called every logged epoch by the training loop
+pub type EpochObserver = Arc<Mutex<dyn FnMut(&EpochEvent, &mut EpochControl) + Send>>;
+
+/// Built-in policy: stop when the loss is no longer finite or has grown to
+/// `factor` times the best loss seen so far
+pub fn divergence_guard(factor: f64) -> EpochObserver {
+    let mut best_loss = f64::INFINITY;
+    Arc::new(Mutex::new(move |event: &EpochEvent, control: &mut EpochControl| {
+        if !event.loss.is_finite() || event.loss > best_loss * factor {
+            println!("Loss diverged at epoch {} ({:.4}, best {:.4}), stopping", event.epoch + 1, event.loss, best_loss);
+            control.request_stop();
+        }
+        best_loss = best_loss.min(event.loss);
+    }))
+}
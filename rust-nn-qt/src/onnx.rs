@@ -0,0 +1,495 @@
+// ONNX export of a trained network, written with a small protobuf encoder so
+// no code generator or runtime crate is needed. Only the handful of onnx.proto
+// fields the graph uses are encoded, numbered as in onnx.proto (IR version 7,
+// default domain opset 13).
+//
+// The graph takes the raw feature values (the CSV row without ignored and
+// label columns, like `predict_one`) and applies the preprocessing before the
+// layers: Mul by the feature weights, Gather of the kept columns, Sub/Div by
+// the z-score statistics. Each layer is a Gemm, batch norm is folded into the
+// hidden Gemms with the running statistics, the skip is a MatMul added after
+// the first activation, and classification ends in Clip (logit clamp) and
+// Sigmoid. Dropout does nothing at inference and has no node.
+
+use std::error::Error;
+use std::path::Path;
+
+use ndarray::Array2;
+
+use crate::frontend_new::{Activation, NetworkConfig, TaskType};
+use crate::model::{ModelWeights, Preprocessing};
+
+pub const ONNX_PATH: &str = "result/model.onnx";
+
+const IR_VERSION: u64 = 7;
+const OPSET_VERSION: u64 = 13;
+// TensorProto.DataType
+const FLOAT: u64 = 1;
+const INT64: u64 = 7;
+// AttributeProto.AttributeType
+const ATTRIBUTE_FLOAT: u64 = 1;
+const ATTRIBUTE_INT: u64 = 2;
+const LEAKY_RELU_ALPHA: f32 = crate::LEAKY_RELU_SLOPE as f32;
+
+// One protobuf message being encoded, fields are appended in call order
+#[derive(Default)]
+struct Message(Vec<u8>);
+
+impl Message {
+    fn raw_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn key(&mut self, field: u64, wire_type: u64) {
+        self.raw_varint(field << 3 | wire_type);
+    }
+
+    fn varint(mut self, field: u64, value: u64) -> Self {
+        self.key(field, 0);
+        self.raw_varint(value);
+        self
+    }
+
+    fn float(mut self, field: u64, value: f32) -> Self {
+        self.key(field, 5);
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn bytes(mut self, field: u64, bytes: &[u8]) -> Self {
+        self.key(field, 2);
+        self.raw_varint(bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    fn string(self, field: u64, text: &str) -> Self {
+        self.bytes(field, text.as_bytes())
+    }
+
+    fn message(self, field: u64, message: Message) -> Self {
+        self.bytes(field, &message.0)
+    }
+}
+
+// TensorProto with little-endian raw_data
+fn tensor(name: &str, dims: &[usize], data_type: u64, raw_data: Vec<u8>) -> Message {
+    dims.iter()
+        .fold(Message::default(), |message, &dim| message.varint(1, dim as u64))
+        .varint(2, data_type)
+        .string(8, name)
+        .bytes(9, &raw_data)
+}
+
+fn float_tensor(name: &str, matrix: &Array2<f64>) -> Message {
+    let raw_data = matrix.iter().flat_map(|&v| (v as f32).to_le_bytes()).collect();
+    tensor(name, &[matrix.nrows(), matrix.ncols()], FLOAT, raw_data)
+}
+
+// Float tensor of shape [n_columns], broadcast over the rows
+fn float_row(name: &str, values: &[f64]) -> Message {
+    let raw_data = values.iter().flat_map(|&v| (v as f32).to_le_bytes()).collect();
+    tensor(name, &[values.len()], FLOAT, raw_data)
+}
+
+fn float_scalar(name: &str, value: f64) -> Message {
+    tensor(name, &[], FLOAT, (value as f32).to_le_bytes().to_vec())
+}
+
+// ValueInfoProto of a float [batch, columns] tensor with a symbolic batch size
+fn matrix_info(name: &str, columns: usize) -> Message {
+    let shape = Message::default()
+        .message(1, Message::default().string(2, "batch"))
+        .message(1, Message::default().varint(1, columns as u64));
+    let tensor_type = Message::default().varint(1, FLOAT).message(2, shape);
+    Message::default().string(1, name).message(2, Message::default().message(1, tensor_type))
+}
+
+// The graph's nodes and initializers, each value named after the step that made it
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<Message>,
+    initializers: Vec<Message>,
+}
+
+impl Graph {
+    fn node(&mut self, op_type: &str, inputs: &[&str], output: &str, attributes: Vec<Message>) -> String {
+        let node = inputs.iter().fold(Message::default(), |node, input| node.string(1, input));
+        let node = attributes.into_iter().fold(
+            node.string(2, output).string(3, output).string(4, op_type),
+            |node, attribute| node.message(5, attribute),
+        );
+        self.nodes.push(node);
+        output.to_string()
+    }
+
+    fn initializer(&mut self, tensor: Message) {
+        self.initializers.push(tensor);
+    }
+}
+
+fn float_attribute(name: &str, value: f32) -> Message {
+    Message::default().string(1, name).float(2, value).varint(20, ATTRIBUTE_FLOAT)
+}
+
+fn int_attribute(name: &str, value: u64) -> Message {
+    Message::default().string(1, name).varint(3, value).varint(20, ATTRIBUTE_INT)
+}
+
+// Weights and bias of a hidden layer with its batch norm folded in:
+// (x·W + b - mean) * scale + beta = x·(W * scale) + ((b - mean) * scale + beta)
+fn folded_layer(weights: &ModelWeights, layer: usize) -> (Array2<f64>, Array2<f64>) {
+    let (w, b) = (&weights.weights[layer], &weights.biases[layer]);
+    match weights.batch_norm.as_ref().map(|batch_norm| &batch_norm[layer]) {
+        Some(batch_norm) => {
+            let scale = batch_norm.inference_scale();
+            (w * &scale, (b - &batch_norm.running_mean) * &scale + &batch_norm.beta)
+        }
+        None => (w.clone(), b.clone()),
+    }
+}
+
+/// Write the network as an ONNX model with input "x" (batch x raw feature
+/// values) and output "y" (batch x heads: probabilities, or the predicted
+/// values of a regression run)
+pub fn export_onnx(path: &str, weights: &ModelWeights, config: &NetworkConfig, preprocessing: &Preprocessing) -> Result<(), Box<dyn Error>> {
+    let n_hidden = weights.weights.len() - 1;
+    let n_inputs = weights.n_features() + preprocessing.dropped_features.len();
+    let n_heads = weights.weights[n_hidden].ncols();
+    let mut graph = Graph::default();
+
+    let mut x = "x".to_string();
+    if let Some(feature_weights) = &preprocessing.feature_weights {
+        graph.initializer(float_row("feature_weights", feature_weights));
+        x = graph.node("Mul", &[&x, "feature_weights"], "weighted", vec![]);
+    }
+    if !preprocessing.dropped_features.is_empty() {
+        let keep: Vec<u8> = (0..n_inputs)
+            .filter(|i| !preprocessing.dropped_features.contains(i))
+            .flat_map(|i| (i as i64).to_le_bytes())
+            .collect();
+        graph.initializer(tensor("kept_features", &[weights.n_features()], INT64, keep));
+        x = graph.node("Gather", &[&x, "kept_features"], "kept", vec![int_attribute("axis", 1)]);
+    }
+    if let Some(normalization) = &preprocessing.normalization {
+        graph.initializer(float_row("means", &normalization.means));
+        graph.initializer(float_row("stds", &normalization.stds));
+        let centered = graph.node("Sub", &[&x, "means"], "centered", vec![]);
+        x = graph.node("Div", &[&centered, "stds"], "normalized", vec![]);
+    }
+
+    let mut a = x.clone();
+    for layer in 0..n_hidden {
+        let (w, b) = folded_layer(weights, layer);
+        let (w_name, b_name) = (format!("w{}", layer + 1), format!("b{}", layer + 1));
+        graph.initializer(float_tensor(&w_name, &w));
+        graph.initializer(float_tensor(&b_name, &b));
+        let z = graph.node("Gemm", &[&a, &w_name, &b_name], &format!("z{}", layer + 1), vec![]);
+        let h = format!("h{}", layer + 1);
+        a = match config.activation {
+            Activation::ReLU => graph.node("Relu", &[&z], &h, vec![]),
+            Activation::LeakyReLU => graph.node("LeakyRelu", &[&z], &h, vec![float_attribute("alpha", LEAKY_RELU_ALPHA)]),
+            Activation::Tanh => graph.node("Tanh", &[&z], &h, vec![]),
+            Activation::Sigmoid => graph.node("Sigmoid", &[&z], &h, vec![]),
+        };
+        if let Some(skip) = weights.skip.as_ref().filter(|_| layer == 0) {
+            graph.initializer(float_tensor("skip", skip));
+            let shortcut = graph.node("MatMul", &[&x, "skip"], "shortcut", vec![]);
+            a = graph.node("Add", &[&a, &shortcut], "h1_skip", vec![]);
+        }
+    }
+
+    let (w_name, b_name) = (format!("w{}", n_hidden + 1), format!("b{}", n_hidden + 1));
+    graph.initializer(float_tensor(&w_name, &weights.weights[n_hidden]));
+    graph.initializer(float_tensor(&b_name, &weights.biases[n_hidden]));
+    match config.task_type {
+        TaskType::Classification => {
+            let mut logits = graph.node("Gemm", &[&a, &w_name, &b_name], "logits", vec![]);
+            if let Some(limit) = config.logit_clamp {
+                graph.initializer(float_scalar("clamp_min", -limit));
+                graph.initializer(float_scalar("clamp_max", limit));
+                logits = graph.node("Clip", &[&logits, "clamp_min", "clamp_max"], "clamped", vec![]);
+            }
+            graph.node("Sigmoid", &[&logits], "y", vec![]);
+        }
+        TaskType::Regression => {
+            graph.node("Gemm", &[&a, &w_name, &b_name], "y", vec![]);
+        }
+    }
+
+    let graph_proto = graph.nodes.into_iter().fold(Message::default(), |proto, node| proto.message(1, node))
+        .string(2, "neural_network");
+    let graph_proto = graph.initializers.into_iter().fold(graph_proto, |proto, tensor| proto.message(5, tensor))
+        .message(11, matrix_info("x", n_inputs))
+        .message(12, matrix_info("y", n_heads));
+    let model = Message::default()
+        .varint(1, IR_VERSION)
+        .string(2, "neural_network")
+        .string(3, env!("CARGO_PKG_VERSION"))
+        .message(7, graph_proto)
+        .message(8, Message::default().string(1, "").varint(2, OPSET_VERSION));
+
+    if let Some(dir) = Path::new(path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, model.0)?;
+    println!("ONNX model exported to {} ({} raw feature inputs, {} outputs)", path, n_inputs, n_heads);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use ndarray::Axis;
+    use crate::model::{BatchNorm, Normalization};
+
+    // A decoded protobuf field: varint, length-delimited or 32-bit value
+    #[derive(Clone, Copy, Debug)]
+    enum Field<'a> {
+        Varint(u64),
+        Bytes(&'a [u8]),
+        Fixed32(u32),
+    }
+
+    fn read_varint(bytes: &[u8], at: &mut usize) -> u64 {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = bytes[*at];
+            *at += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte < 0x80 {
+                return value;
+            }
+            shift += 7;
+        }
+    }
+
+    // Fields of a message in their encoded order
+    fn decode(bytes: &[u8]) -> Vec<(u64, Field<'_>)> {
+        let mut fields = Vec::new();
+        let mut at = 0;
+        while at < bytes.len() {
+            let key = read_varint(bytes, &mut at);
+            let field = match key & 7 {
+                0 => Field::Varint(read_varint(bytes, &mut at)),
+                2 => {
+                    let len = read_varint(bytes, &mut at) as usize;
+                    at += len;
+                    Field::Bytes(&bytes[at - len..at])
+                }
+                5 => {
+                    at += 4;
+                    Field::Fixed32(u32::from_le_bytes(bytes[at - 4..at].try_into().unwrap()))
+                }
+                wire_type => panic!("unexpected wire type {}", wire_type),
+            };
+            fields.push((key >> 3, field));
+        }
+        fields
+    }
+
+    fn messages<'a>(fields: &[(u64, Field<'a>)], number: u64) -> Vec<&'a [u8]> {
+        fields.iter()
+            .filter_map(|&(n, field)| match field {
+                Field::Bytes(bytes) if n == number => Some(bytes),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn strings(fields: &[(u64, Field<'_>)], number: u64) -> Vec<String> {
+        messages(fields, number).into_iter().map(|bytes| String::from_utf8(bytes.to_vec()).unwrap()).collect()
+    }
+
+    fn varints(fields: &[(u64, Field<'_>)], number: u64) -> Vec<u64> {
+        fields.iter()
+            .filter_map(|&(n, field)| match field {
+                Field::Varint(value) if n == number => Some(value),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn float_field(fields: &[(u64, Field<'_>)], number: u64) -> Option<f32> {
+        fields.iter().find_map(|&(n, field)| match field {
+            Field::Fixed32(bits) if n == number => Some(f32::from_bits(bits)),
+            _ => None,
+        })
+    }
+
+    // Run a decoded graph on `x` with the f32 initializers it stores. Rows and
+    // scalars become 1 x n matrices, which broadcast over the batch.
+    fn evaluate(graph: &[(u64, Field<'_>)], x: &Array2<f64>) -> Array2<f64> {
+        let mut values = HashMap::from([("x".to_string(), x.clone())]);
+        let mut indices: HashMap<String, Vec<usize>> = HashMap::new();
+        for tensor in messages(graph, 5).into_iter().map(decode) {
+            let (name, dims, raw) = (strings(&tensor, 8).remove(0), varints(&tensor, 1), messages(&tensor, 9)[0]);
+            if varints(&tensor, 2) == [INT64] {
+                indices.insert(name, raw.chunks(8).map(|b| i64::from_le_bytes(b.try_into().unwrap()) as usize).collect());
+            } else {
+                let data: Vec<f64> = raw.chunks(4).map(|b| f32::from_le_bytes(b.try_into().unwrap()) as f64).collect();
+                let shape = if dims.len() == 2 { (dims[0] as usize, dims[1] as usize) } else { (1, data.len()) };
+                values.insert(name, Array2::from_shape_vec(shape, data).unwrap());
+            }
+        }
+        for node in messages(graph, 1).into_iter().map(decode) {
+            let inputs = strings(&node, 1);
+            let arg = |i: usize| &values[&inputs[i]];
+            let output = match strings(&node, 4)[0].as_str() {
+                "Mul" => arg(0) * arg(1),
+                "Sub" => arg(0) - arg(1),
+                "Div" => arg(0) / arg(1),
+                "Add" => arg(0) + arg(1),
+                "MatMul" => arg(0).dot(arg(1)),
+                "Gemm" => arg(0).dot(arg(1)) + arg(2),
+                "Gather" => arg(0).select(Axis(1), &indices[&inputs[1]]),
+                "Relu" => arg(0).mapv(|v| v.max(0.0)),
+                "LeakyRelu" => {
+                    let alpha = messages(&node, 5).into_iter().map(decode)
+                        .find(|attribute| strings(attribute, 1) == ["alpha"])
+                        .and_then(|attribute| float_field(&attribute, 2))
+                        .unwrap() as f64;
+                    arg(0).mapv(|v| if v > 0.0 { v } else { alpha * v })
+                }
+                "Tanh" => arg(0).mapv(f64::tanh),
+                "Sigmoid" => arg(0).mapv(|v| 1.0 / (1.0 + (-v).exp())),
+                "Clip" => {
+                    let (min, max) = (arg(1)[[0, 0]], arg(2)[[0, 0]]);
+                    arg(0).mapv(|v| v.clamp(min, max))
+                }
+                op => panic!("no evaluator for {}", op),
+            };
+            values.insert(strings(&node, 2).remove(0), output);
+        }
+        values.remove("y").unwrap()
+    }
+
+    #[test]
+    fn exported_graph_computes_what_predict_row_does() {
+        let preprocessing = Preprocessing {
+            ignored_columns: Vec::new(),
+            label_column: None,
+            extra_label_columns: Vec::new(),
+            feature_weights: Some(vec![1.0, 2.0, 0.5]),
+            dropped_features: vec![1],
+            class_names: ["0".to_string(), "1".to_string()],
+            normalization: Some(Normalization { means: vec![1.0, 2.0], stds: vec![0.5, 1.5] }),
+        };
+        let weights = ModelWeights {
+            weights: vec![
+                Array2::from_shape_vec((2, 3), vec![0.1, -0.2, 0.3, 0.4, 0.5, -0.6]).unwrap(),
+                Array2::from_shape_vec((3, 2), vec![0.7, -0.8, 0.9, 0.2, -0.4, 1.1]).unwrap(),
+            ],
+            biases: vec![Array2::from_shape_vec((1, 3), vec![0.01, 0.02, 0.03]).unwrap(), Array2::from_shape_vec((1, 2), vec![-0.5, 0.3]).unwrap()],
+            skip: Some(Array2::from_shape_vec((2, 3), vec![0.2, 0.0, -0.3, 0.1, 0.4, 0.0]).unwrap()),
+            batch_norm: Some(vec![BatchNorm {
+                gamma: Array2::from_shape_vec((1, 3), vec![1.2, 0.8, 1.0]).unwrap(),
+                beta: Array2::from_shape_vec((1, 3), vec![0.1, -0.1, 0.0]).unwrap(),
+                running_mean: Array2::from_shape_vec((1, 3), vec![0.05, -0.1, 0.2]).unwrap(),
+                running_var: Array2::from_shape_vec((1, 3), vec![0.9, 1.5, 0.4]).unwrap(),
+            }]),
+        };
+        let rows = Array2::from_shape_vec((4, 3), vec![
+            1.2, 0.0, 3.0,
+            -2.0, 5.0, 8.0,
+            0.4, -1.0, 1.0,
+            6.0, 2.0, -4.0,
+        ]).unwrap();
+        let path = std::env::temp_dir().join(format!("neural_network_test_{}_evaluated.onnx", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+
+        // A clamp of 1.0 cuts some of these logits, so the Clip node is exercised too
+        for (activation, task_type) in [(Activation::LeakyReLU, TaskType::Classification), (Activation::Tanh, TaskType::Regression)] {
+            let config = NetworkConfig {
+                activation, task_type, logit_clamp: Some(1.0), skip_connection: true, batch_norm: true,
+                ..NetworkConfig::default()
+            };
+            export_onnx(&path, &weights, &config, &preprocessing).unwrap();
+            let bytes = std::fs::read(&path).unwrap();
+            let graph = decode(messages(&decode(&bytes), 7)[0]);
+            let outputs = evaluate(&graph, &rows);
+            for (row, output) in rows.rows().into_iter().zip(outputs.rows()) {
+                let expected = crate::predict_row(
+                    &row.to_vec(), &weights, &config,
+                    preprocessing.feature_weights.as_deref(), &preprocessing.dropped_features, preprocessing.normalization.as_ref(),
+                ).unwrap();
+                for (&got, want) in output.iter().zip(expected) {
+                    assert!((got - want).abs() < 1e-5, "{:?}: ONNX {} vs predict_row {}", activation, got, want);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exported_graph_has_the_expected_nodes_and_initializers() {
+        let weights = ModelWeights {
+            weights: vec![
+                Array2::from_shape_vec((2, 3), vec![0.1, -0.2, 0.3, 0.4, 0.5, -0.6]).unwrap(),
+                Array2::from_shape_vec((3, 1), vec![0.7, -0.8, 0.9]).unwrap(),
+            ],
+            biases: vec![Array2::from_shape_vec((1, 3), vec![0.01, 0.02, 0.03]).unwrap(), Array2::from_elem((1, 1), -0.5)],
+            skip: None,
+            batch_norm: None,
+        };
+        let config = NetworkConfig { activation: Activation::LeakyReLU, logit_clamp: Some(8.0), ..NetworkConfig::default() };
+        let preprocessing = Preprocessing {
+            ignored_columns: Vec::new(),
+            label_column: None,
+            extra_label_columns: Vec::new(),
+            feature_weights: Some(vec![1.0, 2.0, 0.5]),
+            dropped_features: vec![1],
+            class_names: ["0".to_string(), "1".to_string()],
+            normalization: Some(Normalization { means: vec![1.0, 2.0], stds: vec![0.5, 1.0] }),
+        };
+        let path = std::env::temp_dir().join(format!("neural_network_test_{}_model.onnx", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        export_onnx(&path, &weights, &config, &preprocessing).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        let model = decode(&bytes);
+        assert_eq!(varints(&model, 1), vec![IR_VERSION]);
+        let opset = decode(messages(&model, 8)[0]);
+        assert_eq!((strings(&opset, 1), varints(&opset, 2)), (vec![String::new()], vec![OPSET_VERSION]));
+
+        let graph = decode(messages(&model, 7)[0]);
+        let nodes: Vec<_> = messages(&graph, 1).into_iter().map(decode).collect();
+        let op_types: Vec<String> = nodes.iter().flat_map(|node| strings(node, 4)).collect();
+        assert_eq!(op_types, ["Mul", "Gather", "Sub", "Div", "Gemm", "LeakyRelu", "Gemm", "Clip", "Sigmoid"]);
+        // Each node reads the previous one's output
+        for pair in nodes.windows(2) {
+            assert_eq!(strings(&pair[1], 1)[0], strings(&pair[0], 2)[0]);
+        }
+        assert_eq!(strings(&nodes[0], 1)[0], "x");
+        assert_eq!(strings(&nodes[8], 2), vec!["y".to_string()]);
+
+        let initializers: Vec<_> = messages(&graph, 5).into_iter().map(decode).collect();
+        let names: Vec<String> = initializers.iter().flat_map(|tensor| strings(tensor, 8)).collect();
+        assert_eq!(names, ["feature_weights", "kept_features", "means", "stds", "w1", "b1", "w2", "b2", "clamp_min", "clamp_max"]);
+        let kept = &initializers[1];
+        assert_eq!((varints(kept, 1), varints(kept, 2)), (vec![2], vec![INT64]));
+        let kept: Vec<i64> = messages(kept, 9)[0].chunks(8).map(|b| i64::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(kept, vec![0, 2]);
+        let w1 = &initializers[4];
+        assert_eq!((varints(w1, 1), varints(w1, 2)), (vec![2, 3], vec![FLOAT]));
+        let w1_values: Vec<f32> = messages(w1, 9)[0].chunks(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(w1_values, vec![0.1, -0.2, 0.3, 0.4, 0.5, -0.6]);
+
+        // Three raw inputs go in (one is dropped by the Gather), one head comes out
+        let columns = |value_info: &[u8]| {
+            let value_info = decode(value_info);
+            let tensor_type = decode(messages(&decode(messages(&value_info, 2)[0]), 1)[0]);
+            let dims: Vec<_> = messages(&decode(messages(&tensor_type, 2)[0]), 1).into_iter().map(decode).collect();
+            (strings(&value_info, 1)[0].clone(), strings(&dims[0], 2), varints(&dims[1], 1))
+        };
+        assert_eq!(columns(messages(&graph, 11)[0]), ("x".to_string(), vec!["batch".to_string()], vec![3]));
+        assert_eq!(columns(messages(&graph, 12)[0]), ("y".to_string(), vec!["batch".to_string()], vec![1]));
+        let leaky_relu = decode(messages(&nodes[5], 5)[0]);
+        assert_eq!(float_field(&leaky_relu, 2), Some(LEAKY_RELU_ALPHA));
+    }
+}
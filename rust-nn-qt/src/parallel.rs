@@ -0,0 +1,70 @@
+// Rayon-backed versions of the training step's matrix products and
+// element-wise maps, enabled by `NetworkConfig::parallel`.
+//
+// Products are split over row blocks of the left operand, so each output
+// element is still one dot product summed in the serial order and the losses
+// match the serial path bit for bit. The weight gradients (`inputs^T · dz`)
+// reduce over the samples and have only as many rows as the layer has inputs,
+// so they stay below `MIN_ROWS_PER_TASK` and run serially.
+//
+// Benchmark: the ignored test `parallel_training_matches_serial` in main.rs
+// trains the default config (hidden [16], full batch) for 5000 epochs on
+// pollution_dataset5k.csv from the same seed, serially and in parallel, and
+// checks the losses are identical:
+//
+//     RAYON_NUM_THREADS=4 cargo test --release parallel_training_matches_serial -- --ignored --nocapture
+//
+// The only machine it ran on so far has a single core, where a 4-thread pool
+// took 5.3-6.4s against 4.1-4.7s serially (final loss 0.131024 on both): the
+// scheduling overhead, no speedup. Deterministic runs ignore `parallel` and
+// always take the serial path.
+
+use ndarray::{concatenate, Array2, ArrayView2, Axis};
+use rayon::prelude::*;
+
+// Fewer rows than this per task cost more to schedule than they save
+const MIN_ROWS_PER_TASK: usize = 256;
+
+/// `a · b`, with row blocks of `a` multiplied on the rayon pool when `parallel`
+pub fn dot(a: ArrayView2<f64>, b: ArrayView2<f64>, parallel: bool) -> Array2<f64> {
+    let tasks = (a.nrows() / MIN_ROWS_PER_TASK).min(rayon::current_num_threads());
+    if !parallel || tasks < 2 {
+        return a.dot(&b);
+    }
+    let rows_per_task = a.nrows().div_ceil(tasks);
+    let blocks: Vec<ArrayView2<f64>> = a.axis_chunks_iter(Axis(0), rows_per_task).collect();
+    let products: Vec<Array2<f64>> = blocks.par_iter().map(|block| block.dot(&b)).collect();
+    let views: Vec<ArrayView2<f64>> = products.iter().map(|product| product.view()).collect();
+    concatenate(Axis(0), &views).expect("row blocks share the column count")
+}
+
+/// `a.mapv(f)`, evaluated on the rayon pool when `parallel`
+pub fn mapv(a: &Array2<f64>, f: impl Fn(f64) -> f64 + Sync + Send, parallel: bool) -> Array2<f64> {
+    if !parallel {
+        return a.mapv(f);
+    }
+    let mut out = a.clone();
+    out.par_mapv_inplace(f);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray_rand::RandomExt;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rand_distr::StandardNormal;
+
+    #[test]
+    fn row_blocks_multiply_like_the_serial_product() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let a = Array2::<f64>::random_using((1000, 13), StandardNormal, &mut rng);
+        let b = Array2::<f64>::random_using((13, 16), StandardNormal, &mut rng);
+        // A pool of its own, so the product is split even on a single core
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let product = pool.install(|| dot(a.view(), b.view(), true));
+        assert_eq!(product, a.dot(&b));
+        assert_eq!(pool.install(|| mapv(&product, f64::tanh, true)), product.mapv(f64::tanh));
+    }
+}
@@ -0,0 +1,344 @@
+// Training in f32 or f64 for the precision comparison. The core is a plain
+// network generic over its element type: the configured hidden layers,
+// activation, init scheme, batch size and shuffling, a sigmoid output with
+// binary cross entropy and gradient descent at the base learning rate, from the
+// same initial weights and batch order in both precisions. The other training
+// options (optimizer, regularization, batch norm, skip connection, clamps,
+// schedules, ...) have no counterpart here, so a config that enables any of them
+// is refused instead of being compared as a different network.
+
+use std::error::Error;
+use std::ops::SubAssign;
+use std::time::Instant;
+
+use ndarray::{Array1, Array2, Axis, LinalgScalar, ScalarOperand};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::frontend_new::{Activation, LrSchedule, NetworkConfig, Optimizer, TaskType};
+use crate::stats::DecisionRule;
+
+/// Element type the comparison trains in
+pub trait Float: LinalgScalar + ScalarOperand + PartialOrd + SubAssign {
+    const NAME: &'static str;
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn tanh(self) -> Self;
+}
+
+impl Float for f32 {
+    const NAME: &'static str = "f32";
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+    fn exp(self) -> Self {
+        f32::exp(self)
+    }
+    fn ln(self) -> Self {
+        f32::ln(self)
+    }
+    fn tanh(self) -> Self {
+        f32::tanh(self)
+    }
+}
+
+impl Float for f64 {
+    const NAME: &'static str = "f64";
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn exp(self) -> Self {
+        f64::exp(self)
+    }
+    fn ln(self) -> Self {
+        f64::ln(self)
+    }
+    fn tanh(self) -> Self {
+        f64::tanh(self)
+    }
+}
+
+/// Outcome of training in one precision
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrecisionRun {
+    pub seconds: f64,
+    /// Training loss of the last epoch
+    pub loss: f64,
+    /// Accuracy (%) on the validation rows, or the training rows without a split
+    pub accuracy: f64,
+}
+
+/// The same seeded configuration trained in f32 and in f64
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrecisionComparison {
+    pub single: PrecisionRun,
+    pub double: PrecisionRun,
+    pub epochs: usize,
+    /// Rows the accuracy was measured on and whether they were held out
+    pub evaluated_rows: usize,
+    pub on_validation: bool,
+}
+
+impl PrecisionComparison {
+    /// How many times faster the f32 run was
+    pub fn speedup(&self) -> f64 {
+        self.double.seconds / self.single.seconds
+    }
+
+    /// f32 accuracy minus f64 accuracy, in percentage points
+    pub fn accuracy_gap(&self) -> f64 {
+        self.single.accuracy - self.double.accuracy
+    }
+}
+
+fn activate<T: Float>(activation: Activation, z: &Array2<T>) -> Array2<T> {
+    let zero = T::from_f64(0.0);
+    match activation {
+        Activation::ReLU => z.mapv(|v| if v > zero { v } else { zero }),
+        Activation::LeakyReLU => z.mapv(|v| if v > zero { v } else { v * T::from_f64(crate::LEAKY_RELU_SLOPE) }),
+        Activation::Tanh => z.mapv(T::tanh),
+        Activation::Sigmoid => z.mapv(sigmoid),
+    }
+}
+
+fn activation_deriv<T: Float>(activation: Activation, z: &Array2<T>, a: &Array2<T>) -> Array2<T> {
+    let (zero, one) = (T::from_f64(0.0), T::from_f64(1.0));
+    match activation {
+        Activation::ReLU => z.mapv(|v| if v > zero { one } else { zero }),
+        Activation::LeakyReLU => z.mapv(|v| if v > zero { one } else { T::from_f64(crate::LEAKY_RELU_SLOPE) }),
+        Activation::Tanh => a.mapv(|v| one - v * v),
+        Activation::Sigmoid => a.mapv(|v| v * (one - v)),
+    }
+}
+
+// Same two-branch form as `stable_sigmoid`, exp never sees a large positive argument
+fn sigmoid<T: Float>(v: T) -> T {
+    let one = T::from_f64(1.0);
+    if v >= T::from_f64(0.0) {
+        one / (one + (T::from_f64(0.0) - v).exp())
+    } else {
+        let e = v.exp();
+        e / (one + e)
+    }
+}
+
+fn binary_cross_entropy<T: Float>(y_pred: &Array2<T>, y_true: &Array2<T>) -> f64 {
+    let (eps, one) = (T::from_f64(1e-7), T::from_f64(1.0));
+    let total = y_pred.iter().zip(y_true).fold(0.0, |total, (&p, &y)| {
+        let p = if p < eps { eps } else if p > one - eps { one - eps } else { p };
+        total - (y * p.ln() + (one - y) * (one - p).ln()).to_f64()
+    });
+    total / y_pred.len() as f64
+}
+
+struct Network<T> {
+    weights: Vec<Array2<T>>,
+    biases: Vec<Array1<T>>,
+    activation: Activation,
+}
+
+impl<T: Float> Network<T> {
+    fn new(initial: &[Array2<f64>], activation: Activation) -> Self {
+        Self {
+            weights: initial.iter().map(|w| w.mapv(T::from_f64)).collect(),
+            biases: initial.iter().map(|w| Array1::from_elem(w.ncols(), T::from_f64(0.0))).collect(),
+            activation,
+        }
+    }
+
+    // Pre-activations and activations of every layer, the last activation is the output
+    fn forward(&self, x: &Array2<T>) -> (Vec<Array2<T>>, Vec<Array2<T>>) {
+        let n_hidden = self.weights.len() - 1;
+        let mut zs = Vec::with_capacity(self.weights.len());
+        let mut activations = vec![x.clone()];
+        for (layer, (w, b)) in self.weights.iter().zip(&self.biases).enumerate() {
+            let z = activations[layer].dot(w) + b;
+            let a = if layer < n_hidden { activate(self.activation, &z) } else { z.mapv(sigmoid) };
+            zs.push(z);
+            activations.push(a);
+        }
+        (zs, activations)
+    }
+
+    // One gradient descent update on a batch, returns the batch's predictions
+    fn train_step(&mut self, x: &Array2<T>, y_true: &Array2<T>, learning_rate: T) -> Array2<T> {
+        let (zs, activations) = self.forward(x);
+        let n_samples = T::from_f64(x.nrows() as f64);
+        let y_pred = activations[activations.len() - 1].clone();
+        let mut dz = &y_pred - y_true;
+        for layer in (0..self.weights.len()).rev() {
+            let dw = activations[layer].t().dot(&dz) / n_samples;
+            let db = dz.sum_axis(Axis(0)) / n_samples;
+            if layer > 0 {
+                let da = dz.dot(&self.weights[layer].t());
+                dz = da * activation_deriv(self.activation, &zs[layer - 1], &activations[layer]);
+            }
+            self.weights[layer] -= &(dw * learning_rate);
+            self.biases[layer] -= &(db * learning_rate);
+        }
+        y_pred
+    }
+}
+
+fn accuracy<T: Float>(y_pred: &Array2<T>, y_true: &Array2<T>, rule: DecisionRule) -> f64 {
+    let half = T::from_f64(0.5);
+    let correct = y_pred.iter().zip(y_true).filter(|&(&p, &y)| rule.predict(p.to_f64()) == (y >= half)).count();
+    correct as f64 / y_pred.len() as f64 * 100.0
+}
+
+/// Options of `config` the comparison's plain network doesn't implement
+pub fn unsupported_options(config: &NetworkConfig) -> Vec<&'static str> {
+    [
+        (config.task_type != TaskType::Classification, "regression"),
+        (config.optimizer != Optimizer::Sgd, "Adam optimizer"),
+        (config.momentum > 0.0, "momentum"),
+        (config.lr_schedule != LrSchedule::Constant, "learning rate schedule"),
+        (config.l2_lambda > 0.0, "L2 weight decay"),
+        (config.dropout_rate > 0.0, "dropout"),
+        (config.grad_clip.is_some(), "gradient clipping"),
+        (config.batch_norm, "batch norm"),
+        (config.skip_connection, "skip connection"),
+        (config.logit_clamp.is_some(), "logit clamp"),
+        (config.prior_bias_init, "prior bias init"),
+        (!config.weights_import_dir.is_empty(), "imported weights"),
+        (!config.row_index_file.is_empty(), "row index file"),
+        (!config.feature_weights_file.is_empty(), "feature weights file"),
+        (config.drop_constant_features, "dropping constant features"),
+        (config.early_stopping, "early stopping"),
+        (config.restore_best_weights && config.validation_split > 0.0, "restoring the best weights"),
+        (config.max_train_seconds.is_some(), "training time limit"),
+        (config.divergence_factor.is_some(), "divergence guard"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, name)| enabled.then_some(name))
+    .collect()
+}
+
+// Train the network from `initial` in precision T, shuffling with a fresh rng from `seed`
+fn train<T: Float>(
+    train_rows: (&Array2<f64>, &Array2<f64>),
+    evaluation_rows: (&Array2<f64>, &Array2<f64>),
+    initial: &[Array2<f64>],
+    config: &NetworkConfig,
+    seed: u64,
+) -> PrecisionRun {
+    let (x, y_true) = (train_rows.0.mapv(T::from_f64), train_rows.1.mapv(T::from_f64));
+    let n_samples = x.nrows();
+    let batch_size = if config.batch_size == 0 { n_samples } else { config.batch_size.min(n_samples) };
+    let learning_rate = T::from_f64(config.learning_rate);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut indices: Vec<usize> = (0..n_samples).collect();
+    let mut network = Network::<T>::new(initial, config.activation);
+
+    let started_at = Instant::now();
+    let mut loss = f64::NAN;
+    for _ in 0..config.epochs {
+        if config.shuffle {
+            indices.shuffle(&mut rng);
+        }
+        let mut y_pred = Array2::from_elem(y_true.raw_dim(), T::from_f64(0.0));
+        for batch in indices.chunks(batch_size) {
+            let batch_pred = network.train_step(&x.select(Axis(0), batch), &y_true.select(Axis(0), batch), learning_rate);
+            for (row, &i) in batch_pred.rows().into_iter().zip(batch) {
+                y_pred.row_mut(i).assign(&row);
+            }
+        }
+        loss = binary_cross_entropy(&y_pred, &y_true);
+    }
+    let seconds = started_at.elapsed().as_secs_f64();
+
+    let (x_eval, y_eval) = (evaluation_rows.0.mapv(T::from_f64), evaluation_rows.1.mapv(T::from_f64));
+    let (_, activations) = network.forward(&x_eval);
+    let accuracy = accuracy(&activations[activations.len() - 1], &y_eval, config.decision_rule());
+    PrecisionRun { seconds, loss, accuracy }
+}
+
+/// Load `path` and train the plain form of `config` on its primary label in
+/// f32 and in f64, with the configured seed (or the deterministic one)
+pub fn compare_precisions(path: &str, config: &NetworkConfig) -> Result<PrecisionComparison, Box<dyn Error>> {
+    let unsupported = unsupported_options(config);
+    if !unsupported.is_empty() {
+        return Err(format!("The precision comparison trains a plain network, turn off: {}", unsupported.join(", ")).into());
+    }
+    let (x, y, _, _, _) = crate::load_data(path, &config.load_options())?;
+    let y = crate::head(&y, 0).to_owned();
+    crate::validate_training_shapes(&x, &y, &config.hidden_layers)?;
+
+    let seed = config.seed.unwrap_or(crate::DETERMINISTIC_SEED);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mut validation, (mut x, y)) = if config.validation_split > 0.0 {
+        let (val_rows, train_rows) = crate::split_indices(&y, config.validation_split, config.stratified, &mut rng);
+        if val_rows.is_empty() || train_rows.is_empty() {
+            return Err(format!("A validation split of {} leaves no rows for training or validation", config.validation_split).into());
+        }
+        (Some(crate::select_rows(&x, &y, &val_rows)?), crate::select_rows(&x, &y, &train_rows)?)
+    } else {
+        (None, (x, y))
+    };
+    if config.normalize_features {
+        let normalization = crate::normalize_features(&mut x);
+        if let Some((x_val, _)) = validation.as_mut() {
+            normalization.apply(x_val);
+        }
+    }
+
+    let initial = crate::initial_weights(x.ncols(), &config.hidden_layers, 1, config.init_scheme, &mut rng);
+
+    let evaluation = validation.as_ref().map_or((&x, &y), |(x_val, y_val)| (x_val, y_val));
+    let single = train::<f32>((&x, &y), evaluation, &initial, config, seed);
+    let double = train::<f64>((&x, &y), evaluation, &initial, config, seed);
+    println!(
+        "Precision comparison over {} epochs: {} {:.2}s {:.2}%, {} {:.2}s {:.2}%",
+        config.epochs, f32::NAME, single.seconds, single.accuracy, f64::NAME, double.seconds, double.accuracy
+    );
+    Ok(PrecisionComparison {
+        single,
+        double,
+        epochs: config.epochs,
+        evaluated_rows: evaluation.0.nrows(),
+        on_validation: validation.is_some(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precisions_train_alike_from_the_same_start() {
+        let x = Array2::from_shape_fn((40, 2), |(i, j)| (i as f64 * 0.37 + j as f64).sin());
+        let y = Array2::from_shape_fn((40, 1), |(i, _)| if x[[i, 0]] + x[[i, 1]] > 0.0 { 1.0 } else { 0.0 });
+        let config = NetworkConfig { hidden_layers: vec![4], epochs: 200, batch_size: 8, ..NetworkConfig::default() };
+        let initial = vec![
+            Array2::from_shape_fn((2, 4), |(i, j)| (i + 2 * j) as f64 * 0.1 - 0.3),
+            Array2::from_shape_fn((4, 1), |(i, _)| i as f64 * 0.2 - 0.3),
+        ];
+        let single = train::<f32>((&x, &y), (&x, &y), &initial, &config, 1);
+        let double = train::<f64>((&x, &y), (&x, &y), &initial, &config, 1);
+        assert!((single.loss - double.loss).abs() < 1e-4, "{} vs {}", single.loss, double.loss);
+        assert_eq!(single.accuracy, double.accuracy);
+    }
+
+    #[test]
+    fn configs_with_options_the_comparison_lacks_are_refused() {
+        assert!(unsupported_options(&NetworkConfig::default()).is_empty());
+        let config = NetworkConfig {
+            optimizer: Optimizer::ADAM_DEFAULT,
+            dropout_rate: 0.2,
+            logit_clamp: Some(10.0),
+            ..NetworkConfig::default()
+        };
+        assert_eq!(unsupported_options(&config), ["Adam optimizer", "dropout", "logit clamp"]);
+        let error = compare_precisions("missing.csv", &config).unwrap_err().to_string();
+        assert!(error.contains("Adam optimizer, dropout, logit clamp"), "{}", error);
+    }
+}
@@ -0,0 +1,107 @@
+use plotters::prelude::*;
+use std::error::Error;
+
+use crate::frontend_new::{format_hidden_layers, NetworkConfig, TrainingData};
+
+pub const REPORT_PATH: &str = "result/report.png";
+
+// One-image summary of a finished run: loss and accuracy curves, the
+// confusion matrix and the key metrics, captioned with config and dataset.
+pub fn generate_report_image(
+    path: &str,
+    data: &TrainingData,
+    config: &NetworkConfig,
+) -> Result<(), Box<dyn Error>> {
+    if data.regression_metrics.is_some() {
+        return Err("The report image is only available for classification runs".into());
+    }
+    let confusion = data.confusion_matrix.ok_or("No finished run to report on")?;
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let dataset_name = data.dataset_path.strip_prefix("csv/").unwrap_or(&data.dataset_path);
+    let caption = format!(
+        "{} | epochs {} | hidden [{}] | lr {}",
+        dataset_name, data.epochs_run, format_hidden_layers(&config.hidden_layers), config.learning_rate
+    );
+
+    let root = BitMapBackend::new(path, (1200, 900)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let root = root.titled(&caption, ("sans-serif", 24))?;
+    let panels = root.split_evenly((2, 2));
+
+    // Loss curve (same style as the saved loss figure)
+    let max_loss = data.losses.iter().cloned().filter(|loss| loss.is_finite()).fold(0.0, f64::max).max(1e-6);
+    let mut chart = ChartBuilder::on(&panels[0])
+        .caption("Training Loss", ("sans-serif", 20))
+        .margin(15)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d(data.first_epoch..data.first_epoch + data.losses.len().max(1), 0.0..max_loss)?;
+    chart.configure_mesh().draw()?;
+    chart.draw_series(LineSeries::new(
+        data.losses.iter().enumerate()
+            .filter(|(_, loss)| loss.is_finite())
+            .map(|(i, &loss)| (data.first_epoch + i, loss)),
+        &RED,
+    ))?;
+
+    // Accuracy curve
+    let mut chart = ChartBuilder::on(&panels[1])
+        .caption("Accuracy (%)", ("sans-serif", 20))
+        .margin(15)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d(data.first_epoch..data.first_epoch + data.accuracies.len().max(1), 0.0..100.0)?;
+    chart.configure_mesh().draw()?;
+    chart.draw_series(LineSeries::new(
+        data.accuracies.iter().enumerate().map(|(i, &acc)| (data.first_epoch + i, acc)),
+        &BLUE,
+    ))?;
+
+    // Confusion matrix as a 2x2 grid shaded by count
+    let area = &panels[2];
+    area.draw(&Text::new("Confusion Matrix (rows: actual, cols: predicted)", (20, 15), ("sans-serif", 18)))?;
+    let cells = [
+        ("TN", confusion.tn, 0, 0),
+        ("FP", confusion.fp, 1, 0),
+        ("FN", confusion.fn_, 0, 1),
+        ("TP", confusion.tp, 1, 1),
+    ];
+    let total = confusion.total().max(1) as f64;
+    let (cell_w, cell_h, left, top) = (200, 150, 120, 60);
+    for (name, count, col, row) in cells {
+        let x0 = left + col * cell_w;
+        let y0 = top + row * cell_h;
+        let shade = 255 - (count as f64 / total * 180.0) as u8;
+        area.draw(&Rectangle::new([(x0, y0), (x0 + cell_w, y0 + cell_h)], RGBColor(shade, shade, 255).filled()))?;
+        area.draw(&Rectangle::new([(x0, y0), (x0 + cell_w, y0 + cell_h)], BLACK))?;
+        area.draw(&Text::new(format!("{}: {}", name, count), (x0 + 60, y0 + 65), ("sans-serif", 22)))?;
+    }
+    let names = &data.class_names;
+    area.draw(&Text::new(format!("actual {}", names.negative), (20, top + 65), ("sans-serif", 16)))?;
+    area.draw(&Text::new(format!("actual {}", names.positive), (20, top + cell_h + 65), ("sans-serif", 16)))?;
+
+    // Key metrics as text
+    let area = &panels[3];
+    let mut lines = vec![format!("Accuracy: {:.2}%", data.accuracy)];
+    if let Some(ci) = data.accuracy_interval {
+        lines.push(format!("95% CI: {:.2}% - {:.2}%", ci.lower, ci.upper));
+    }
+    lines.push(format!("Precision: {:.3}", confusion.precision()));
+    lines.push(format!("Recall: {:.3}", confusion.recall()));
+    lines.push(format!("F1 score: {:.3}", confusion.f1()));
+    lines.push(format!("Final loss: {:.4}", data.loss));
+    lines.push(format!("Samples evaluated: {}", confusion.total()));
+    if let Some(weights) = &data.trained_weights {
+        lines.push(format!("Parameters: {}", weights.parameter_count()));
+    }
+    for (i, line) in lines.iter().enumerate() {
+        area.draw(&Text::new(line.as_str(), (40, 40 + i as i32 * 40), ("sans-serif", 24)))?;
+    }
+
+    root.present()?;
+    println!("Report image saved to {}", path);
+    Ok(())
+}
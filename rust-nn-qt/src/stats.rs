@@ -0,0 +1,459 @@
+use ndarray::{Array2, ArrayView2, Axis};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// Pearson correlation between two equally long series (0.0 if either is constant)
+fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    if n == 0.0 {
+        return 0.0;
+    }
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+
+    let mut cov = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for (&va, &vb) in a.iter().zip(b.iter()) {
+        cov += (va - mean_a) * (vb - mean_b);
+        var_a += (va - mean_a).powi(2);
+        var_b += (vb - mean_b).powi(2);
+    }
+
+    if var_a == 0.0 || var_b == 0.0 {
+        return 0.0;
+    }
+    cov / (var_a.sqrt() * var_b.sqrt())
+}
+
+/// Correlation of every feature column with the label, as (column index, r)
+/// sorted by decreasing absolute correlation.
+pub fn feature_label_correlations(x: &Array2<f64>, y: &Array2<f64>) -> Vec<(usize, f64)> {
+    let labels: Vec<f64> = y.column(0).to_vec();
+    let mut correlations: Vec<(usize, f64)> = x
+        .axis_iter(Axis(1))
+        .enumerate()
+        .map(|(i, column)| (i, pearson(&column.to_vec(), &labels)))
+        .collect();
+
+    correlations.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+    correlations
+}
+
+/// Feature columns whose values are all identical (zero variance)
+pub fn constant_columns(x: &Array2<f64>) -> Vec<usize> {
+    x.axis_iter(Axis(1))
+        .enumerate()
+        .filter(|(_, column)| match column.iter().next() {
+            Some(&first) => column.iter().all(|&v| v == first),
+            None => false,
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Bootstrap estimate of the accuracy (%) with a 95% confidence interval
+#[derive(Clone, Copy, Debug)]
+pub struct AccuracyInterval {
+    pub mean: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl AccuracyInterval {
+    pub fn half_width(&self) -> f64 {
+        (self.upper - self.lower) / 2.0
+    }
+}
+
+/// Resample the per-sample correctness of the trained model `n_resamples` times
+/// (with replacement) and report the mean accuracy and the 2.5/97.5 percentiles.
+pub fn bootstrap_accuracy<R: Rng>(correct: &[bool], n_resamples: usize, rng: &mut R) -> Option<AccuracyInterval> {
+    let n = correct.len();
+    if n == 0 || n_resamples == 0 {
+        return None;
+    }
+
+    let mut accuracies: Vec<f64> = (0..n_resamples)
+        .map(|_| {
+            let hits = (0..n).filter(|_| correct[rng.gen_range(0..n)]).count();
+            hits as f64 / n as f64 * 100.0
+        })
+        .collect();
+    accuracies.sort_by(|a, b| a.total_cmp(b));
+
+    let percentile = |p: f64| accuracies[((p * (n_resamples - 1) as f64).round() as usize).min(n_resamples - 1)];
+    Some(AccuracyInterval {
+        mean: accuracies.iter().sum::<f64>() / n_resamples as f64,
+        lower: percentile(0.025),
+        upper: percentile(0.975),
+    })
+}
+
+/// Which class a probability exactly at the decision threshold is predicted as
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum TieBreak {
+    /// p >= threshold is class 1
+    #[default]
+    Positive,
+    /// Only p > threshold is class 1
+    Negative,
+}
+
+impl TieBreak {
+    pub const ALL: [TieBreak; 2] = [TieBreak::Positive, TieBreak::Negative];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TieBreak::Positive => "tie → class 1",
+            TieBreak::Negative => "tie → class 0",
+        }
+    }
+}
+
+/// How a probability becomes a class. Every metric binarizes through `predict`,
+/// so the threshold and ties are handled the same way everywhere.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecisionRule {
+    pub threshold: f64,
+    pub tie_break: TieBreak,
+}
+
+impl DecisionRule {
+    pub fn predict(&self, probability: f64) -> bool {
+        match self.tie_break {
+            TieBreak::Positive => probability >= self.threshold,
+            TieBreak::Negative => probability > self.threshold,
+        }
+    }
+}
+
+/// One of the four outcomes of a binary prediction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfusionCell {
+    TruePositive,
+    FalsePositive,
+    TrueNegative,
+    FalseNegative,
+}
+
+impl ConfusionCell {
+    pub fn of(predicted: bool, actual: bool) -> Self {
+        match (predicted, actual) {
+            (true, true) => ConfusionCell::TruePositive,
+            (true, false) => ConfusionCell::FalsePositive,
+            (false, false) => ConfusionCell::TrueNegative,
+            (false, true) => ConfusionCell::FalseNegative,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConfusionCell::TruePositive => "True Positives",
+            ConfusionCell::FalsePositive => "False Positives",
+            ConfusionCell::TrueNegative => "True Negatives",
+            ConfusionCell::FalseNegative => "False Negatives",
+        }
+    }
+}
+
+/// Counts of a binary classifier's outcomes
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConfusionMatrix {
+    pub tp: usize,
+    pub fp: usize,
+    pub tn: usize,
+    pub fn_: usize,
+}
+
+impl ConfusionMatrix {
+    // `predictions` are probabilities or 0.0/1.0 values, binarized by `rule`;
+    // labels count as 1 from 0.5 up
+    pub fn from_predictions(predictions: ArrayView2<f64>, y_true: ArrayView2<f64>, rule: DecisionRule) -> Self {
+        let mut matrix = Self::default();
+        matrix.accumulate(predictions, y_true, rule);
+        matrix
+    }
+
+    // Add the outcomes of one chunk of predictions, so large sets can be
+    // counted as they are evaluated instead of all at once
+    pub fn accumulate(&mut self, predictions: ArrayView2<f64>, y_true: ArrayView2<f64>, rule: DecisionRule) {
+        for (&p, &y) in predictions.iter().zip(y_true.iter()) {
+            match ConfusionCell::of(rule.predict(p), y >= 0.5) {
+                ConfusionCell::TruePositive => self.tp += 1,
+                ConfusionCell::FalsePositive => self.fp += 1,
+                ConfusionCell::TrueNegative => self.tn += 1,
+                ConfusionCell::FalseNegative => self.fn_ += 1,
+            }
+        }
+    }
+
+    pub fn count(&self, cell: ConfusionCell) -> usize {
+        match cell {
+            ConfusionCell::TruePositive => self.tp,
+            ConfusionCell::FalsePositive => self.fp,
+            ConfusionCell::TrueNegative => self.tn,
+            ConfusionCell::FalseNegative => self.fn_,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.tp + self.fp + self.tn + self.fn_
+    }
+
+    // Share of correct predictions in %
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.tp + self.tn, self.total()) * 100.0
+    }
+
+    pub fn precision(&self) -> f64 {
+        ratio(self.tp, self.tp + self.fp)
+    }
+
+    pub fn recall(&self) -> f64 {
+        ratio(self.tp, self.tp + self.fn_)
+    }
+
+    pub fn f1(&self) -> f64 {
+        let (p, r) = (self.precision(), self.recall());
+        if p + r == 0.0 { 0.0 } else { 2.0 * p * r / (p + r) }
+    }
+}
+
+/// Correct predictions among the samples of each true class, [class 0, class 1].
+/// The share per class is its recall, which the overall accuracy hides when
+/// one class is much rarer than the other.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ClassAccuracy {
+    pub correct: [usize; 2],
+    pub total: [usize; 2],
+}
+
+impl ClassAccuracy {
+    // `outcomes` pairs each sample's true class (true = 1) with whether it was predicted right
+    pub fn from_outcomes(outcomes: impl IntoIterator<Item = (bool, bool)>) -> Self {
+        let mut accuracy = Self::default();
+        for (actual, correct) in outcomes {
+            let class = usize::from(actual);
+            accuracy.total[class] += 1;
+            accuracy.correct[class] += usize::from(correct);
+        }
+        accuracy
+    }
+
+    // Share of `class` (0 or 1) predicted right in %, None when no sample has that class
+    pub fn accuracy(&self, class: usize) -> Option<f64> {
+        (self.total[class] > 0).then(|| ratio(self.correct[class], self.total[class]) * 100.0)
+    }
+}
+
+/// Fit of a continuous prediction, for regression runs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegressionMetrics {
+    /// Mean squared error
+    pub mse: f64,
+    /// Coefficient of determination, 1 - SSE / SST (1.0 = perfect, 0.0 = as good as the mean)
+    pub r2: f64,
+}
+
+impl RegressionMetrics {
+    // A constant target has no variance to explain, R2 is then 1.0 for an
+    // exact fit and 0.0 otherwise
+    pub fn from_predictions(predictions: ArrayView2<f64>, y_true: ArrayView2<f64>) -> Self {
+        let n = y_true.len().max(1) as f64;
+        let mean = y_true.sum() / n;
+        let sse: f64 = predictions.iter().zip(y_true.iter()).map(|(&p, &y)| (y - p) * (y - p)).sum();
+        let sst: f64 = y_true.iter().map(|&y| (y - mean) * (y - mean)).sum();
+        let r2 = if sst > 0.0 { 1.0 - sse / sst } else if sse == 0.0 { 1.0 } else { 0.0 };
+        Self { mse: sse / n, r2 }
+    }
+}
+
+/// The samples behind a confusion matrix: features, labels and predicted
+/// probabilities of the final evaluation, row for row
+#[derive(Clone, Debug)]
+pub struct EvaluatedSamples {
+    pub features: Array2<f64>,
+    pub labels: Vec<f64>,
+    pub probabilities: Vec<f64>,
+    /// Threshold and tie rule the matrix was computed with
+    pub rule: DecisionRule,
+}
+
+impl EvaluatedSamples {
+    // Confusion matrix of the samples under their current rule
+    pub fn confusion_matrix(&self) -> ConfusionMatrix {
+        let n = self.probabilities.len();
+        let probabilities = ArrayView2::from_shape((n, 1), &self.probabilities).expect("one probability per row");
+        let labels = ArrayView2::from_shape((n, 1), &self.labels).expect("one label per row");
+        ConfusionMatrix::from_predictions(probabilities, labels, self.rule)
+    }
+
+    // Accuracy of each true class under the current rule
+    pub fn class_accuracy(&self) -> ClassAccuracy {
+        ClassAccuracy::from_outcomes(
+            self.probabilities.iter().zip(self.labels.iter()).map(|(&p, &y)| (y >= 0.5, self.rule.predict(p) == (y >= 0.5))),
+        )
+    }
+
+    // Row indices of the samples that landed in `cell`
+    pub fn indices_in(&self, cell: ConfusionCell) -> Vec<usize> {
+        self.probabilities
+            .iter()
+            .zip(self.labels.iter())
+            .enumerate()
+            .filter(|(_, (&p, &y))| ConfusionCell::of(self.rule.predict(p), y >= 0.5) == cell)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// One probability bin of a reliability diagram
+#[derive(Clone, Copy, Debug)]
+pub struct CalibrationBin {
+    pub mean_predicted: f64,
+    /// Share of the bin's samples whose label is 1
+    pub observed_positive: f64,
+    pub count: usize,
+}
+
+/// Reliability diagram of predicted probabilities against observed outcomes
+#[derive(Clone, Debug)]
+pub struct Calibration {
+    /// Non-empty bins in increasing probability order
+    pub bins: Vec<CalibrationBin>,
+    /// Expected Calibration Error: count-weighted mean |observed - predicted| over the bins
+    pub expected_error: f64,
+}
+
+/// Bin the probabilities into `n_bins` equal-width bins over [0, 1] (p = 1.0
+/// goes into the last bin) and compare each bin's mean prediction with its
+/// observed positive fraction.
+pub fn calibration_curve(probabilities: &[f64], labels: &[f64], n_bins: usize) -> Option<Calibration> {
+    if probabilities.is_empty() || n_bins == 0 {
+        return None;
+    }
+
+    let mut sums = vec![(0.0, 0.0, 0usize); n_bins];
+    for (&p, &y) in probabilities.iter().zip(labels.iter()) {
+        let bin = ((p * n_bins as f64) as usize).min(n_bins - 1);
+        sums[bin].0 += p;
+        sums[bin].1 += if y >= 0.5 { 1.0 } else { 0.0 };
+        sums[bin].2 += 1;
+    }
+
+    let bins: Vec<CalibrationBin> = sums
+        .into_iter()
+        .filter(|&(_, _, count)| count > 0)
+        .map(|(predicted, positives, count)| CalibrationBin {
+            mean_predicted: predicted / count as f64,
+            observed_positive: positives / count as f64,
+            count,
+        })
+        .collect();
+    let total = probabilities.len() as f64;
+    let expected_error = bins
+        .iter()
+        .map(|bin| bin.count as f64 / total * (bin.observed_positive - bin.mean_predicted).abs())
+        .sum();
+    Some(Calibration { bins, expected_error })
+}
+
+/// Receiver operating characteristic of predicted probabilities
+#[derive(Clone, Debug)]
+pub struct RocCurve {
+    /// (false positive rate, true positive rate) from (0, 0) to (1, 1), one point per distinct threshold
+    pub points: Vec<[f64; 2]>,
+    /// Area under the curve by the trapezoidal rule (0.5 = chance, 1.0 = perfect ranking)
+    pub auc: f64,
+}
+
+/// Sweep the threshold down through every distinct probability, counting the
+/// samples at or above it as positive. Tied probabilities move together, so
+/// ties give a diagonal segment. None unless both classes are present.
+pub fn roc_curve(probabilities: &[f64], labels: &[f64]) -> Option<RocCurve> {
+    let mut samples: Vec<(f64, bool)> = probabilities.iter().zip(labels.iter())
+        .map(|(&p, &y)| (p, y >= 0.5))
+        .collect();
+    let positives = samples.iter().filter(|(_, positive)| *positive).count();
+    let negatives = samples.len() - positives;
+    if positives == 0 || negatives == 0 {
+        return None;
+    }
+    samples.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut points = vec![[0.0, 0.0]];
+    let (mut tp, mut fp) = (0usize, 0usize);
+    for (i, &(p, positive)) in samples.iter().enumerate() {
+        if positive { tp += 1 } else { fp += 1 }
+        if samples.get(i + 1).is_none_or(|next| next.0 != p) {
+            points.push([ratio(fp, negatives), ratio(tp, positives)]);
+        }
+    }
+    let auc = points.windows(2)
+        .map(|pair| (pair[1][0] - pair[0][0]) * (pair[0][1] + pair[1][1]) / 2.0)
+        .sum();
+    Some(RocCurve { points, auc })
+}
+
+/// Counts of values in equal-width bins
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    /// Lower edge of the first bin
+    pub start: f64,
+    pub bin_width: f64,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    pub fn bin_center(&self, bin: usize) -> f64 {
+        self.start + (bin as f64 + 0.5) * self.bin_width
+    }
+}
+
+/// Bin the finite values into `n_bins` equal-width bins from their minimum to
+/// their maximum (the maximum goes into the last bin). Equal values share one
+/// bin of width 1. None without finite values.
+pub fn histogram(values: impl IntoIterator<Item = f64>, n_bins: usize) -> Option<Histogram> {
+    let values: Vec<f64> = values.into_iter().filter(|v| v.is_finite()).collect();
+    if values.is_empty() || n_bins == 0 {
+        return None;
+    }
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == min {
+        return Some(Histogram { start: min - 0.5, bin_width: 1.0, counts: vec![values.len()] });
+    }
+
+    let bin_width = (max - min) / n_bins as f64;
+    let mut counts = vec![0; n_bins];
+    for v in values {
+        counts[(((v - min) / bin_width) as usize).min(n_bins - 1)] += 1;
+    }
+    Some(Histogram { start: min, bin_width, counts })
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 { 0.0 } else { numerator as f64 / denominator as f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probability_at_the_threshold_follows_the_tie_break() {
+        let y_pred = Array2::from_elem((1, 1), 0.5);
+        let y_true = Array2::from_elem((1, 1), 1.0);
+        let positive = DecisionRule { threshold: 0.5, tie_break: TieBreak::Positive };
+        let negative = DecisionRule { threshold: 0.5, tie_break: TieBreak::Negative };
+        assert!(positive.predict(0.5));
+        assert!(!negative.predict(0.5));
+        assert_eq!(ConfusionMatrix::from_predictions(y_pred.view(), y_true.view(), positive).count(ConfusionCell::TruePositive), 1);
+        assert_eq!(ConfusionMatrix::from_predictions(y_pred.view(), y_true.view(), negative).count(ConfusionCell::FalseNegative), 1);
+        // Off the threshold both rules agree
+        for rule in [positive, negative] {
+            assert!(rule.predict(0.5 + 1e-12) && !rule.predict(0.5 - 1e-12));
+        }
+    }
+}
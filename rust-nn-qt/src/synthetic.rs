@@ -0,0 +1,148 @@
+use csv::WriterBuilder;
+use ndarray::{Array1, Array2, Axis};
+use ndarray_rand::RandomExt;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+use std::error::Error;
+
+/// How the two classes are separated in feature space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Separability {
+    /// A random hyperplane through the data
+    Linear,
+    /// A sphere around the origin, not solvable by a linear model
+    Nonlinear,
+}
+
+impl Separability {
+    pub const ALL: [Separability; 2] = [Separability::Linear, Separability::Nonlinear];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Separability::Linear => "linear",
+            Separability::Nonlinear => "nonlinear",
+        }
+    }
+}
+
+/// Settings of a generated binary dataset, the same settings and seed always
+/// give the same rows
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntheticSpec {
+    pub samples: usize,
+    pub features: usize,
+    /// Share of labels flipped after separation (0.0 = perfectly separable)
+    pub noise: f64,
+    /// Share of rows in class 1
+    pub positive_ratio: f64,
+    pub separability: Separability,
+    pub seed: u64,
+}
+
+impl Default for SyntheticSpec {
+    fn default() -> Self {
+        Self {
+            samples: 1000,
+            features: 4,
+            noise: 0.05,
+            positive_ratio: 0.5,
+            separability: Separability::Linear,
+            seed: 42,
+        }
+    }
+}
+
+impl SyntheticSpec {
+    // File name under csv/ that identifies the settings that matter for reproducing it
+    pub fn file_name(&self) -> String {
+        format!(
+            "synthetic_{}_{}x{}_seed{}.csv",
+            self.separability.label(), self.samples, self.features, self.seed
+        )
+    }
+
+    /// Features drawn from a standard normal distribution and a 0/1 label column.
+    /// The split threshold is the score quantile that gives `positive_ratio`,
+    /// then each label is flipped with probability `noise`.
+    pub fn generate(&self) -> Result<(Array2<f64>, Array2<f64>), Box<dyn Error>> {
+        if self.samples == 0 || self.features == 0 {
+            return Err("A synthetic dataset needs at least one sample and one feature".into());
+        }
+        if !(0.0..=1.0).contains(&self.positive_ratio) || !(0.0..=0.5).contains(&self.noise) {
+            return Err("Class balance must be within 0-1 and noise within 0-0.5".into());
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let x = Array2::<f64>::random_using((self.samples, self.features), StandardNormal, &mut rng);
+        let scores: Array1<f64> = match self.separability {
+            Separability::Linear => {
+                let direction = Array1::<f64>::random_using(self.features, StandardNormal, &mut rng);
+                x.dot(&direction)
+            }
+            Separability::Nonlinear => x.mapv(|v| v * v).sum_axis(Axis(1)),
+        };
+
+        let mut sorted = scores.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let n_negative = ((1.0 - self.positive_ratio) * self.samples as f64).round() as usize;
+        let threshold = sorted.get(n_negative).copied().unwrap_or(f64::INFINITY);
+
+        let labels = scores.mapv(|score| {
+            let positive = score >= threshold;
+            let flipped = rng.gen::<f64>() < self.noise;
+            if positive != flipped { 1.0 } else { 0.0 }
+        });
+        Ok((x, labels.insert_axis(Axis(1))))
+    }
+}
+
+// Write a generated dataset in the loader's format: a header, features, label last
+pub fn write_dataset_csv(path: &str, x: &Array2<f64>, y: &Array2<f64>) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    let mut header: Vec<String> = (0..x.ncols()).map(|i| format!("feature_{}", i)).collect();
+    header.push("label".to_string());
+    wtr.write_record(&header)?;
+    for (row, label) in x.rows().into_iter().zip(y.column(0)) {
+        wtr.write_record(row.iter().chain(std::iter::once(label)).map(|v| v.to_string()))?;
+    }
+    wtr.flush()?;
+
+    println!("Synthetic dataset written to {}", path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_the_same_rows() {
+        let spec = SyntheticSpec { samples: 50, separability: Separability::Nonlinear, ..SyntheticSpec::default() };
+        assert_eq!(spec.generate().unwrap(), spec.generate().unwrap());
+        let (x, _) = SyntheticSpec { seed: 7, ..spec.clone() }.generate().unwrap();
+        assert_ne!(x, spec.generate().unwrap().0);
+    }
+
+    #[test]
+    fn noise_free_labels_follow_the_class_balance() {
+        for positive_ratio in [0.0, 0.2, 0.5, 1.0] {
+            let spec = SyntheticSpec { samples: 1000, noise: 0.0, positive_ratio, ..SyntheticSpec::default() };
+            let (_, y) = spec.generate().unwrap();
+            assert_eq!(y.sum(), (positive_ratio * 1000.0).round(), "positive_ratio {}", positive_ratio);
+        }
+    }
+
+    #[test]
+    fn noise_is_limited_to_half_the_labels() {
+        assert!(SyntheticSpec { noise: 0.5, ..SyntheticSpec::default() }.generate().is_ok());
+        for noise in [-0.1, 0.6] {
+            let err = SyntheticSpec { noise, ..SyntheticSpec::default() }.generate().unwrap_err();
+            assert_eq!(err.to_string(), "Class balance must be within 0-1 and noise within 0-0.5");
+        }
+    }
+}